@@ -0,0 +1,276 @@
+//! A wrapper around [mpmc_bus::Bus] which keeps track of how many
+//! messages have been broadcast and consumed, to help identify when a
+//! slow consumer is the bottleneck in a test.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use mpmc_bus::{Bus, Receiver, Sender, TryRecvError};
+
+/// Statistics about the messages passing through a [MeteredBus], see
+/// [MeteredBus::bus_stats()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusStats {
+    /// Total number of messages broadcast on the bus.
+    pub sent: u64,
+    /// Number of [MeteredReceiver]s which are currently subscribed.
+    pub receivers: usize,
+    /// Estimated number of messages which have been broadcast but not
+    /// yet consumed by the slowest [MeteredReceiver].
+    pub backlog: u64,
+}
+
+/// Message counts for an individual [MeteredReceiver].
+#[derive(Debug)]
+struct ReceiverCounter {
+    /// The value of [Counters::sent] when the receiver subscribed.
+    sent_at_subscribe: u64,
+    /// Number of messages consumed by the receiver.
+    consumed: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    sent: AtomicU64,
+    receivers: Mutex<Vec<Weak<ReceiverCounter>>>,
+}
+
+impl Counters {
+    fn add_receiver(&self) -> Arc<ReceiverCounter> {
+        let counter = Arc::new(ReceiverCounter {
+            sent_at_subscribe: self.sent.load(Ordering::SeqCst),
+            consumed: AtomicU64::new(0),
+        });
+        self.receivers
+            .lock()
+            .expect("error obtaining lock")
+            .push(Arc::downgrade(&counter));
+        counter
+    }
+
+    fn stats(&self) -> BusStats {
+        let sent = self.sent.load(Ordering::SeqCst);
+        let mut receivers = self.receivers.lock().expect("error obtaining lock");
+        receivers.retain(|receiver| receiver.strong_count() > 0);
+
+        let backlog = receivers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|receiver| {
+                sent.saturating_sub(receiver.sent_at_subscribe)
+                    .saturating_sub(receiver.consumed.load(Ordering::SeqCst))
+            })
+            .max()
+            .unwrap_or(0);
+
+        BusStats {
+            sent,
+            receivers: receivers.len(),
+            backlog,
+        }
+    }
+}
+
+/// A [Bus] which counts the messages broadcast via it (and its
+/// [MeteredSender]s), and consumed by its [MeteredReceiver]s.
+pub struct MeteredBus<T> {
+    bus: Bus<T>,
+    counters: Arc<Counters>,
+}
+
+impl<T> MeteredBus<T>
+where
+    T: Clone + Sync + Send + 'static,
+{
+    /// Create a new bus with the specified buffer `capacity`.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            bus: Bus::new(capacity),
+            counters: Default::default(),
+        }
+    }
+
+    /// Create a new [MeteredSender] for this bus.
+    pub fn broadcaster(&self) -> MeteredSender<T> {
+        MeteredSender {
+            sender: self.bus.broadcaster(),
+            counters: self.counters.clone(),
+        }
+    }
+
+    /// Subscribe a new [MeteredReceiver] to this bus, which will
+    /// receive all messages broadcast after it has subscribed.
+    pub fn subscribe(&self) -> MeteredReceiver<T> {
+        MeteredReceiver {
+            receiver: self.bus.subscribe(),
+            counter: self.counters.add_receiver(),
+        }
+    }
+
+    /// Broadcast a message to all the receivers of this bus.
+    pub fn broadcast(&self, message: T) -> eyre::Result<()> {
+        self.bus.broadcast(message)?;
+        self.counters.sent.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Obtain the current [BusStats] for this bus.
+    pub fn bus_stats(&self) -> BusStats {
+        self.counters.stats()
+    }
+}
+
+/// A [Sender] for a [MeteredBus].
+#[derive(Clone)]
+pub struct MeteredSender<T> {
+    sender: Sender<T>,
+    counters: Arc<Counters>,
+}
+
+impl<T> MeteredSender<T>
+where
+    T: Clone + Sync + Send + 'static,
+{
+    /// Broadcast a message to all the receivers of the bus.
+    pub fn broadcast(&self, message: T) -> eyre::Result<()> {
+        self.sender.broadcast(message)?;
+        self.counters.sent.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Obtain the current [BusStats] for the bus.
+    pub fn bus_stats(&self) -> BusStats {
+        self.counters.stats()
+    }
+}
+
+/// A [Receiver] for a [MeteredBus].
+pub struct MeteredReceiver<T> {
+    receiver: Receiver<T>,
+    counter: Arc<ReceiverCounter>,
+}
+
+impl<T> MeteredReceiver<T>
+where
+    T: Clone + Sync + Send + 'static,
+{
+    /// Block until a message is received.
+    pub fn recv(&mut self) -> eyre::Result<T> {
+        let message = self.receiver.recv()?;
+        self.counter.consumed.fetch_add(1, Ordering::SeqCst);
+        Ok(message)
+    }
+
+    /// Receive a message if one is available.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let message = self.receiver.try_recv()?;
+        self.counter.consumed.fetch_add(1, Ordering::SeqCst);
+        Ok(message)
+    }
+}
+
+/// Options for [broadcast_with_retry()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BroadcastRetryOptions {
+    /// How long to keep retrying a broadcast to a full bus before
+    /// giving up.
+    pub timeout: Duration,
+    /// How long to wait between attempts.
+    pub retry_interval: Duration,
+    /// If `true`, a message which could not be broadcast before the
+    /// [BroadcastRetryOptions::timeout] is dropped with a warning,
+    /// otherwise an error is returned.
+    pub drop_on_timeout: bool,
+}
+
+impl Default for BroadcastRetryOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            retry_interval: Duration::from_millis(10),
+            drop_on_timeout: false,
+        }
+    }
+}
+
+/// Broadcast the `message` via `tx` without blocking indefinitely if
+/// the bus buffer is full (because a receiver is not consuming its
+/// messages). The broadcast is retried until the
+/// [BroadcastRetryOptions::timeout] has elapsed, after which the
+/// message is either dropped or an error is returned, depending on
+/// [BroadcastRetryOptions::drop_on_timeout].
+pub fn broadcast_with_retry<T>(
+    tx: &Sender<T>,
+    message: T,
+    options: &BroadcastRetryOptions,
+) -> eyre::Result<()>
+where
+    T: Clone + Sync + Send + std::fmt::Debug + 'static,
+{
+    let start = Instant::now();
+
+    loop {
+        if tx.try_broadcast(message.clone()).is_ok() {
+            return Ok(());
+        }
+
+        if start.elapsed() >= options.timeout {
+            break;
+        }
+
+        std::thread::sleep(options.retry_interval);
+    }
+
+    if options.drop_on_timeout {
+        tracing::warn!(
+            "Bus remained full for {:?}, dropping message: {:?}",
+            options.timeout,
+            message
+        );
+        Ok(())
+    } else {
+        Err(eyre::eyre!(
+            "Bus remained full for {:?}, unable to broadcast message: {:?}",
+            options.timeout,
+            message
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MeteredBus;
+
+    /// Test that the sent count matches the number of broadcasts, and
+    /// the backlog reflects the messages not yet consumed.
+    #[test]
+    fn test_bus_stats() {
+        let bus = MeteredBus::<u8>::new(100);
+        let mut rx = bus.subscribe();
+        let tx = bus.broadcaster();
+
+        bus.broadcast(1).unwrap();
+        tx.broadcast(2).unwrap();
+        tx.broadcast(3).unwrap();
+
+        let stats = bus.bus_stats();
+        assert_eq!(3, stats.sent);
+        assert_eq!(1, stats.receivers);
+        assert_eq!(3, stats.backlog);
+
+        assert_eq!(1, rx.recv().unwrap());
+        assert_eq!(2, rx.try_recv().unwrap());
+
+        let stats = tx.bus_stats();
+        assert_eq!(3, stats.sent);
+        assert_eq!(1, stats.backlog);
+
+        drop(rx);
+        assert_eq!(0, bus.bus_stats().receivers);
+    }
+}
@@ -27,6 +27,7 @@ where
             if let CeremonyMessage::SuccessfulContribution {
                 contributor: _,
                 chunk: _,
+                seq: _,
             } = message
             {
                 state.contributions += 1
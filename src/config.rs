@@ -76,6 +76,12 @@ pub struct Config {
     /// See [SingleTestOptions::aleo_setup_repo] for useage examples.
     #[serde(default = "default_aleo_setup_coordinator_repo")]
     pub aleo_setup_coordinator_repo: Repo,
+
+    /// (Optional) Log filter directives (in the same format as the
+    /// `RUST_LOG` environment variable), overriding `RUST_LOG` if
+    /// specified. This field is hot-reloadable, see [crate::reload].
+    #[serde(default)]
+    pub log_filter: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -6,7 +6,8 @@ use crate::{
     join::MultiJoinable,
     process::MonitorProcessMessage,
     process::{
-        default_parse_exit_status, fallible_monitor, run_monitor_process, MonitorProcessJoin,
+        default_parse_exit_status, fallible_monitor, pause_process, run_monitor_process,
+        MonitorProcessJoin, MonitorProcessOptions,
     },
     test::ContributorStartConfig,
     AleoPublicKey, CeremonyMessage, ContributorRef, Environment,
@@ -20,6 +21,8 @@ use std::{
     fs::{File, OpenOptions},
     io::{BufRead, BufReader, Write},
     path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
 };
 
 #[derive(Deserialize)]
@@ -72,7 +75,7 @@ impl Contributor {
     /// The id used to reference this contributor by the coordinator,
     /// and within the ceremony transcript.
     pub fn id_on_coordinator(&self) -> String {
-        format!("{}.contributor", self.address)
+        self.as_contributor_ref().id_on_coordinator()
     }
 
     /// Obtains the [ContributorRef] referring to this [Contributor].
@@ -111,16 +114,50 @@ pub struct ContributorConfig {
     pub drop: Option<DropContributorConfig>,
     /// When this contributor is configured to start during the round.
     pub start: ContributorStartConfig,
+    /// If `Some`, the contributor process will be paused for this
+    /// duration when the round starts, before it has a chance to
+    /// contribute. Setting this longer than the coordinator's
+    /// `participant_lock_timeout` can be used to reliably cause the
+    /// contributor to be dropped.
+    pub contribution_delay: Option<Duration>,
 }
 
 /// Allows the threads created by [run_contributor()] to be joined.
-#[derive(Debug)]
 pub struct ContributorJoin {
     monitor_process_join: MonitorProcessJoin,
     monitor_ceremony_join: std::thread::JoinHandle<()>,
+    monitor_tx: Sender<MonitorProcessMessage>,
+}
+
+impl std::fmt::Debug for ContributorJoin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContributorJoin")
+            .field("monitor_process_join", &self.monitor_process_join)
+            .field("monitor_ceremony_join", &self.monitor_ceremony_join)
+            .finish()
+    }
 }
 
 impl ContributorJoin {
+    /// The operating system process id of the contributor process
+    /// (if it was available when the process was started).
+    pub fn pid(&self) -> Option<u32> {
+        self.monitor_process_join.pid()
+    }
+
+    /// The exit status of the contributor process, if it has exited.
+    pub fn exit_status(&self) -> Option<subprocess::ExitStatus> {
+        self.monitor_process_join.exit_status()
+    }
+
+    /// Tell the process monitor to terminate the contributor process,
+    /// without shutting down the rest of the ceremony.
+    pub fn terminate(&self) -> eyre::Result<()> {
+        self.monitor_tx
+            .broadcast(MonitorProcessMessage::Terminate)
+            .map_err(|error| eyre::eyre!("Error sending message to process monitor: {}", error))
+    }
+
     /// Joins the threads created by [run_contributor()].
     fn join(self) -> std::thread::Result<()> {
         self.monitor_process_join.join()?;
@@ -159,14 +196,19 @@ pub fn run_contributor(
         .expect("Should convert keys path to str")
         .to_owned();
 
-    let exec = subprocess::Exec::cmd(&config.contributor_bin_path.canonicalize()?)
-        .cwd(&config.out_dir)
-        .env("RUST_BACKTRACE", "1")
-        .env("RUST_LOG", "debug,hyper=warn")
-        .arg("contribute")
-        .args(&["--passphrase", "test"])
-        .args(&["--api-url", &config.coordinator_api_url])
-        .args(&["--keys-path", &keys_file_path_string]);
+    let contributor_bin_path = config.contributor_bin_path.canonicalize()?;
+    let out_dir = config.out_dir.clone();
+    let coordinator_api_url = config.coordinator_api_url.clone();
+    let exec = move || {
+        Ok(subprocess::Exec::cmd(&contributor_bin_path)
+            .cwd(&out_dir)
+            .env("RUST_BACKTRACE", "1")
+            .env("RUST_LOG", "debug,hyper=warn")
+            .arg("contribute")
+            .args(&["--passphrase", "test"])
+            .args(&["--api-url", &coordinator_api_url])
+            .args(&["--keys-path", &keys_file_path_string]))
+    };
 
     let log_file_path = config.out_dir.join("contributor.log");
 
@@ -174,15 +216,18 @@ pub fn run_contributor(
         config.id.to_string(),
         exec,
         default_parse_exit_status,
+        MonitorProcessOptions::default(),
         ceremony_tx,
         ceremony_rx.clone(),
-        fallible_monitor(move |stdout, _ceremony_tx, _monitor_tx| {
-            contributor_monitor(stdout, &log_file_path)
+        fallible_monitor(move |output, _ceremony_tx, _monitor_tx| {
+            contributor_monitor(output.stdout, &log_file_path)
         }),
     )?;
 
     let contributor_ref = config.contributor_ref.clone();
     let contributor_id = config.id.clone();
+    let pid = monitor_process_join.pid();
+    let join_monitor_tx = monitor_tx.clone();
 
     let monitor_ceremony_span = tracing::error_span!("ceremony");
 
@@ -191,6 +236,10 @@ pub fn run_contributor(
         let mut ceremony_rx = ceremony_rx;
 
         let mut n_contributions: u64 = 0;
+        // The contributor process is paused in a separate thread, so
+        // that the ceremony messages (e.g. a shutdown) are not missed
+        // while it is paused.
+        let mut pause: Option<ContributorPause> = None;
 
         loop {
             match ceremony_rx
@@ -198,19 +247,51 @@ pub fn run_contributor(
                 .expect("Error receiving message from ceremony")
             {
                 CeremonyMessage::Shutdown(_) => break,
+                CeremonyMessage::RoundStarted(round) => {
+                    if let Some(delay) = config.contribution_delay {
+                        match pid {
+                            Some(pid) => {
+                                tracing::info!(
+                                    "Delaying contributor {} in round {} by {:?}.",
+                                    contributor_id,
+                                    round,
+                                    delay
+                                );
+                                end_pause(&mut pause);
+                                let (resume_tx, resume_rx) = mpsc::channel();
+                                let pause_join = std::thread::spawn(move || {
+                                    if let Err(error) = pause_process(pid, delay, resume_rx) {
+                                        tracing::error!("{:?}", error);
+                                    }
+                                });
+                                pause = Some(ContributorPause {
+                                    resume_tx,
+                                    pause_join,
+                                });
+                            }
+                            None => tracing::error!(
+                                "Unable to delay contributor {}, the process id is not available.",
+                                contributor_id
+                            ),
+                        }
+                    }
+                }
                 CeremonyMessage::RoundFinished(round) => {
                     tracing::debug!(
                         "Finished contributing to round {}, terminating process.",
                         round
                     );
-                    monitor_tx
-                        .broadcast(MonitorProcessMessage::Terminate)
-                        .expect("Error sending message to process monitor");
+                    // The process may have already been terminated
+                    // (e.g. by the watchdog).
+                    if let Err(error) = monitor_tx.broadcast(MonitorProcessMessage::Terminate) {
+                        tracing::warn!("Error sending message to process monitor: {}", error);
+                    }
                     break;
                 }
                 CeremonyMessage::SuccessfulContribution {
                     contributor,
                     chunk: _,
+                    seq: _,
                 } => {
                     if let Some(drop_config) = &config.drop {
                         if contributor == contributor_ref {
@@ -235,25 +316,51 @@ pub fn run_contributor(
                             &contributor_ref,
                             n_contributions,
                         );
-                    monitor_tx
-                        .broadcast(MonitorProcessMessage::Terminate)
-                        .expect("Error sending message to process monitor");
+                    if let Err(error) = monitor_tx.broadcast(MonitorProcessMessage::Terminate) {
+                        tracing::warn!("Error sending message to process monitor: {}", error);
+                    }
                     break;
                 }
             }
         }
 
+        end_pause(&mut pause);
+
         tracing::debug!("Thread closing gracefully.")
     });
 
     let join = ContributorJoin {
         monitor_process_join,
         monitor_ceremony_join,
+        monitor_tx: join_monitor_tx,
     };
 
     Ok(join)
 }
 
+/// A pause of the contributor process in progress, see
+/// [ContributorConfig::contribution_delay].
+struct ContributorPause {
+    /// Dropped to resume the process early.
+    resume_tx: mpsc::Sender<()>,
+    pause_join: std::thread::JoinHandle<()>,
+}
+
+/// Resume the contributor process if it is paused, and wait for the
+/// thread pausing it to finish.
+fn end_pause(pause: &mut Option<ContributorPause>) {
+    if let Some(ContributorPause {
+        resume_tx,
+        pause_join,
+    }) = pause.take()
+    {
+        drop(resume_tx);
+        if pause_join.join().is_err() {
+            tracing::error!("Contributor pause thread panicked.");
+        }
+    }
+}
+
 /// Monitors the `setup1-contributor`, logs output to `log_file_path`
 /// file and `tracing::debug!()`.
 fn contributor_monitor(stdout: File, log_file_path: impl AsRef<Path>) -> eyre::Result<()> {
@@ -283,3 +390,118 @@ fn contributor_monitor(stdout: File, log_file_path: impl AsRef<Path>) -> eyre::R
 
     Ok(())
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::{
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    };
+
+    use mpmc_bus::{Bus, Sender};
+
+    use super::{run_contributor, ContributorConfig};
+    use crate::{
+        coordinator::classify_log_line,
+        util::{fake_bin, fake_contributor_config, TEST_ADDRESS},
+        CeremonyMessage, ParticipantRef, ShutdownReason,
+    };
+
+    /// Create a fake `setup1-contributor` which regularly prints a
+    /// timestamp (in nanoseconds), representing the heartbeat that
+    /// the real contributor sends to the coordinator.
+    fn fake_contributor_bin(dir: &Path) -> PathBuf {
+        fake_bin(
+            dir,
+            "setup1-contributor",
+            "while true; do date +%s%N; sleep 0.05; done",
+        )
+    }
+
+    /// Act as the coordinator for
+    /// [test_contribution_delay_exceeds_lock_timeout()]: watch the
+    /// heartbeats in the contributor's `log_file_path`, and drop the
+    /// contributor (by broadcasting the message which the coordinator
+    /// reporter parses from its output) once no heartbeat has been
+    /// received for longer than the `participant_lock_timeout`.
+    fn fake_coordinator_lock(
+        log_file_path: PathBuf,
+        participant_lock_timeout: Duration,
+        ceremony_tx: Sender<CeremonyMessage>,
+    ) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            let mut n_heartbeats = 0;
+            let mut last_heartbeat = Instant::now();
+            while Instant::now() < deadline {
+                let heartbeats = std::fs::read_to_string(&log_file_path)
+                    .map(|log| log.lines().count())
+                    .unwrap_or(0);
+                if heartbeats != n_heartbeats {
+                    n_heartbeats = heartbeats;
+                    last_heartbeat = Instant::now();
+                } else if n_heartbeats > 0 && last_heartbeat.elapsed() > participant_lock_timeout {
+                    let line = format!(
+                        "INFO phase1_coordinator: Dropping {}.contributor from the ceremony",
+                        TEST_ADDRESS
+                    );
+                    ceremony_tx
+                        .broadcast(classify_log_line(&line).unwrap())
+                        .unwrap();
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+        })
+    }
+
+    /// Test that a contributor with a `contribution_delay` longer than
+    /// the `participant_lock_timeout` misses its heartbeat deadline,
+    /// and is dropped by the coordinator. The contributor still shuts
+    /// down promptly while it is paused.
+    #[test]
+    fn test_contribution_delay_exceeds_lock_timeout() {
+        let participant_lock_timeout = Duration::from_millis(500);
+        let contribution_delay = Duration::from_secs(5);
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = ContributorConfig {
+            contribution_delay: Some(contribution_delay),
+            ..fake_contributor_config(out_dir.path(), fake_contributor_bin(out_dir.path()))
+        };
+        let contributor_ref = config.contributor_ref.clone();
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let join = run_contributor(config, bus.broadcaster(), bus.subscribe()).unwrap();
+        let coordinator_join = fake_coordinator_lock(
+            out_dir.path().join("contributor.log"),
+            participant_lock_timeout,
+            bus.broadcaster(),
+        );
+
+        std::thread::sleep(Duration::from_millis(300));
+        let round_started = Instant::now();
+        bus.broadcast(CeremonyMessage::RoundStarted(1)).unwrap();
+
+        loop {
+            if let CeremonyMessage::ParticipantDropped(participant) = rx.recv().unwrap() {
+                assert_eq!(ParticipantRef::Contributor(contributor_ref), participant);
+                break;
+            }
+        }
+        coordinator_join.join().unwrap();
+        assert!(round_started.elapsed() > participant_lock_timeout);
+
+        // The contributor is still paused, but responds to the
+        // shutdown without waiting for the delay to elapse.
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        join.join().unwrap();
+        assert!(
+            round_started.elapsed() < contribution_delay,
+            "expected the contributor to shut down before the end of its delay, took: {:?}",
+            round_started.elapsed()
+        );
+    }
+}
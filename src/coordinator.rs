@@ -2,37 +2,54 @@
 //! rocket server.
 
 use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    convert::TryFrom,
     fs::{File, OpenOptions},
-    io::{BufRead, BufReader, Write},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
     net::SocketAddr,
     num::{NonZeroU16, NonZeroU64, NonZeroU8, NonZeroUsize},
     path::{Path, PathBuf},
     str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime},
 };
 
 use eyre::Context;
-use mpmc_bus::{Receiver, Sender};
+use humantime::format_duration;
+use mpmc_bus::{Receiver, Sender, TryRecvError};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use subprocess::Exec;
+use subprocess::{Exec, Popen, Redirection};
 
 use crate::{
+    bus::{broadcast_with_retry, BroadcastRetryOptions},
     contributor::Contributor,
+    join::MultiJoinable,
     process::{
         default_parse_exit_status, fallible_monitor, run_monitor_process, MonitorProcessJoin,
+        MonitorProcessMessage, MonitorProcessOptions, ProcessOutput, DEFAULT_TERMINATE_GRACE,
     },
+    util::{copy_dir_all, create_dir_if_not_exists},
     verifier::Verifier,
     AleoPublicKey, CeremonyMessage, ContributorRef, Environment, ParticipantRef, ShutdownReason,
     VerifierRef,
 };
 
+/// The name of the SQLite database file which the coordinator stores
+/// its state in, relative to its working directory
+/// ([CoordinatorConfig::out_dir]).
+const SQLITE_FILE_NAME: &str = "setup.db3";
+
 /// The format of the configuration json configuration file, used with
 /// the `--config` command line option for `aleo-setup-coordinator`.
-#[derive(Debug, Serialize)]
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 struct CoordinatorTomlConfiguration {
     listen_address: SocketAddr,
 
-    /// Path to the SQLite db
+    /// Absolute path to the SQLite db, see
+    /// [CoordinatorConfig::sqlite_file()].
     sqlite_file: PathBuf,
 
     /// The setup we are going to run.
@@ -47,7 +64,7 @@ struct CoordinatorTomlConfiguration {
     runtime_parameters: RuntimeParameters,
 
     /// To extend the Environment
-    environment_parameters: EnvironmentParameters,
+    environment_parameters: TomlEnvironmentParameters,
 
     /// The settings related to the verifiers
     verifier_settings: VerifierSettings,
@@ -55,12 +72,96 @@ struct CoordinatorTomlConfiguration {
     /// Settings related to reliability checks
     reliability_check: ReliabilityCheckSettings,
 
-    /// Settings needed for the Twitter API.
-    twitter_settings: TwitterSettings,
+    /// Settings needed for the Twitter API, omitted when the
+    /// coordinator doesn't need them, see
+    /// [CoordinatorConfig::twitter_settings].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    twitter_settings: Option<TwitterSettings>,
+
+    /// `PRAGMA` settings applied to the SQLite db.
+    #[serde(default, skip_serializing_if = "SqlitePragmas::is_empty")]
+    sqlite_pragmas: SqlitePragmas,
+}
+
+/// The SQLite journal mode, see
+/// <https://www.sqlite.org/pragma.html#pragma_journal_mode>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SqliteJournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal,
+    Off,
+}
+
+impl SqliteJournalMode {
+    const ALL: [SqliteJournalMode; 6] = [
+        SqliteJournalMode::Delete,
+        SqliteJournalMode::Truncate,
+        SqliteJournalMode::Persist,
+        SqliteJournalMode::Memory,
+        SqliteJournalMode::Wal,
+        SqliteJournalMode::Off,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            SqliteJournalMode::Delete => "DELETE",
+            SqliteJournalMode::Truncate => "TRUNCATE",
+            SqliteJournalMode::Persist => "PERSIST",
+            SqliteJournalMode::Memory => "MEMORY",
+            SqliteJournalMode::Wal => "WAL",
+            SqliteJournalMode::Off => "OFF",
+        }
+    }
+}
+
+impl std::fmt::Display for SqliteJournalMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SqliteJournalMode {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|mode| mode.as_str().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "Invalid SQLite journal mode {:?}, expected one of: {}",
+                    s,
+                    Self::ALL
+                        .iter()
+                        .map(SqliteJournalMode::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+}
+
+/// The `sqlite_pragmas` section of the [CoordinatorTomlConfiguration].
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct SqlitePragmas {
+    /// See [CoordinatorConfig::sqlite_journal_mode].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    journal_mode: Option<SqliteJournalMode>,
+}
+
+impl SqlitePragmas {
+    fn is_empty(&self) -> bool {
+        self.journal_mode.is_none()
+    }
 }
 
 /// The parameters to configure runtime
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct RuntimeParameters {
     /// The delay between the calls to `operator.update()`, ms
     pub operator_update_loop_delay: NonZeroU64,
@@ -69,8 +170,17 @@ pub struct RuntimeParameters {
     pub rayon_global_pool_threads: NonZeroU16,
 }
 
+impl Default for RuntimeParameters {
+    fn default() -> Self {
+        Self {
+            operator_update_loop_delay: NonZeroU64::new(10_000).unwrap(),
+            rayon_global_pool_threads: NonZeroU16::new(30).unwrap(),
+        }
+    }
+}
+
 /// Additional parameters to extend Environment
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct EnvironmentParameters {
     pub minimum_contributors_per_round: NonZeroUsize,
     pub maximum_contributors_per_round: NonZeroUsize,
@@ -82,16 +192,48 @@ pub struct EnvironmentParameters {
     pub queue_seen_timeout: i64,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// The `environment_parameters` section of the
+/// [CoordinatorTomlConfiguration].
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct TomlEnvironmentParameters {
+    #[serde(flatten)]
+    parameters: EnvironmentParameters,
+    /// See [CoordinatorConfig::num_powers].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_powers: Option<u32>,
+}
+
+impl Default for EnvironmentParameters {
+    fn default() -> Self {
+        Self {
+            minimum_contributors_per_round: NonZeroUsize::new(1).unwrap(),
+            maximum_contributors_per_round: NonZeroUsize::new(5).unwrap(),
+            contributor_seen_timeout: 3600,
+            participant_lock_timeout: 900,
+            queue_seen_timeout: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct VerifierSettings {
-    /// The time which verifiers will have to complete
+    /// The time (in seconds) which verifiers will have to complete
     /// the task before it gets deleted from the cache.
     pub assigned_tasks_cache_ttl: NonZeroU64,
     /// Maximum number of records in the cache
     pub assigned_tasks_cache_records_cap: NonZeroUsize,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl Default for VerifierSettings {
+    fn default() -> Self {
+        Self {
+            assigned_tasks_cache_ttl: NonZeroU64::new(60).unwrap(),
+            assigned_tasks_cache_records_cap: NonZeroUsize::new(1000).unwrap(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ReliabilityCheckSettings {
     /// The option to define if we are checking the reliability
     /// score of the contributors or not. Defaults to false
@@ -115,8 +257,23 @@ pub struct ReliabilityCheckSettings {
     pub batch_size: u8,
 }
 
+impl Default for ReliabilityCheckSettings {
+    fn default() -> Self {
+        Self {
+            is_enabled: false,
+            accept_threshold: NonZeroU8::new(8).unwrap(),
+            maximum_score: 100,
+            estimation_interval: 60,
+            number_of_challenges: 10,
+            challenge_size: 6291456,
+            total_size: 11,
+            batch_size: 2,
+        }
+    }
+}
+
 /// Settings needed for the Twitter API.
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct TwitterSettings {
     /// The consumer token for the Aleo Setup app.
     pub consumer_token: String,
@@ -125,55 +282,164 @@ pub struct TwitterSettings {
     pub consumer_secret: String,
 }
 
-impl From<&CoordinatorConfig> for CoordinatorTomlConfiguration {
-    fn from(config: &CoordinatorConfig) -> Self {
-        let replacement_contributors = config
-            .replacement_contributors
-            .iter()
-            .map(|c| c.address.clone())
-            .collect();
+/// Environment variable read by [TwitterSettings::from_env()] for the
+/// [TwitterSettings::consumer_token].
+pub const TWITTER_CONSUMER_TOKEN_ENV: &str = "ALEO_TWITTER_CONSUMER_TOKEN";
+
+/// Environment variable read by [TwitterSettings::from_env()] for the
+/// [TwitterSettings::consumer_secret].
+pub const TWITTER_CONSUMER_SECRET_ENV: &str = "ALEO_TWITTER_CONSUMER_SECRET";
+
+impl TwitterSettings {
+    /// Read the settings from the [TWITTER_CONSUMER_TOKEN_ENV] and
+    /// [TWITTER_CONSUMER_SECRET_ENV] environment variables, so that
+    /// real credentials don't need to be kept in the source. A
+    /// placeholder is used for each of them which is not set.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
 
+    /// Read the settings in the same way as
+    /// [TwitterSettings::from_env()], looking up the value of each
+    /// environment variable with `lookup`.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
         Self {
-            listen_address: SocketAddr::from_str("0.0.0.0:9000").unwrap(),
-            sqlite_file: "setup.db3".into(),
+            consumer_token: lookup(TWITTER_CONSUMER_TOKEN_ENV)
+                .unwrap_or_else(|| "some_token".to_string()),
+            consumer_secret: lookup(TWITTER_CONSUMER_SECRET_ENV)
+                .unwrap_or_else(|| "some_secret".to_string()),
+        }
+    }
+}
+
+impl TryFrom<&CoordinatorConfig> for CoordinatorTomlConfiguration {
+    type Error = eyre::Error;
+
+    fn try_from(config: &CoordinatorConfig) -> Result<Self, Self::Error> {
+        let sqlite_file = config.sqlite_file();
+        let sqlite_dir = sqlite_file
+            .parent()
+            .ok_or_else(|| eyre::eyre!("Invalid sqlite file path {:?}", sqlite_file))?
+            .canonicalize()
+            .wrap_err("cannot canonicalize sqlite file directory")?;
+        let sqlite_file_name = sqlite_file
+            .file_name()
+            .ok_or_else(|| eyre::eyre!("Invalid sqlite file path {:?}", sqlite_file))?;
+
+        let replacement_contributors =
+            validate_replacement_contributors(&config.replacement_contributors)?;
+
+        Ok(Self {
+            listen_address: config.listen_address,
+            sqlite_file: sqlite_dir.join(sqlite_file_name),
             setup: config.environment,
             replacement_contributors,
-            runtime_parameters: RuntimeParameters {
-                operator_update_loop_delay: NonZeroU64::new(10_000).unwrap(),
-                rayon_global_pool_threads: NonZeroU16::new(30).unwrap(),
-            },
-            environment_parameters: EnvironmentParameters {
-                minimum_contributors_per_round: NonZeroUsize::new(1).unwrap(),
-                maximum_contributors_per_round: NonZeroUsize::new(5).unwrap(),
-                contributor_seen_timeout: 3600,
-                participant_lock_timeout: 900,
-                queue_seen_timeout: 3600,
-            },
-            verifier_settings: VerifierSettings {
-                assigned_tasks_cache_ttl: NonZeroU64::new(60).unwrap(),
-                assigned_tasks_cache_records_cap: NonZeroUsize::new(1000).unwrap(),
+            runtime_parameters: config.runtime_parameters.unwrap_or_default(),
+            environment_parameters: TomlEnvironmentParameters {
+                parameters: config.environment_parameters.clone().unwrap_or_default(),
+                num_powers: config.num_powers,
             },
-            reliability_check: ReliabilityCheckSettings {
-                is_enabled: false,
-                accept_threshold: NonZeroU8::new(8).unwrap(),
-                maximum_score: 100,
-                estimation_interval: 60,
-                number_of_challenges: 10,
-                challenge_size: 6291456,
-                total_size: 11,
-                batch_size: 2,
-            },
-            twitter_settings: TwitterSettings {
-                consumer_token: "some_token".to_string(),
-                consumer_secret: "some_secret".to_string(),
+            verifier_settings: config.verifier_settings.unwrap_or_default(),
+            reliability_check: config.reliability_check.clone().unwrap_or_default(),
+            twitter_settings: config.twitter_settings.clone(),
+            sqlite_pragmas: SqlitePragmas {
+                journal_mode: config.sqlite_journal_mode,
             },
+        })
+    }
+}
+
+/// Check that the [CoordinatorConfig::replacement_contributors] have
+/// well-formed addresses (they may have bypassed the validation in
+/// [AleoPublicKey]'s [FromStr] when deserialized), and that no
+/// address is specified more than once. Returns their addresses.
+fn validate_replacement_contributors(
+    replacement_contributors: &[ContributorRef],
+) -> eyre::Result<Vec<AleoPublicKey>> {
+    let mut addresses: HashSet<&AleoPublicKey> = HashSet::new();
+
+    for contributor in replacement_contributors {
+        AleoPublicKey::from_str(contributor.address.as_ref())
+            .wrap_err("Invalid replacement contributor address")?;
+
+        if !addresses.insert(&contributor.address) {
+            return Err(eyre::eyre!(
+                "Replacement contributor {} is specified more than once",
+                contributor
+            ));
+        }
+    }
+
+    Ok(replacement_contributors
+        .iter()
+        .map(|c| c.address.clone())
+        .collect())
+}
+
+/// Resource limits applied to the coordinator process, see
+/// [CoordinatorConfig::resource_limits].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ResourceLimits {
+    /// The maximum size of the process's virtual memory in bytes
+    /// (`RLIMIT_AS`), if `Some`.
+    pub max_memory_bytes: Option<u64>,
+    /// The maximum CPU time of the process in seconds
+    /// (`RLIMIT_CPU`), if `Some`.
+    pub max_cpu_seconds: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Whether any of the limits have been set.
+    pub fn is_limited(&self) -> bool {
+        self.max_memory_bytes.is_some() || self.max_cpu_seconds.is_some()
+    }
+
+    /// The arguments for the `prlimit` utility (from `util-linux`)
+    /// which apply these limits, before the command it executes.
+    fn prlimit_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(max_memory_bytes) = self.max_memory_bytes {
+            args.push(format!("--as={}", max_memory_bytes));
+        }
+        if let Some(max_cpu_seconds) = self.max_cpu_seconds {
+            args.push(format!("--cpu={}", max_cpu_seconds));
         }
+        args
     }
 }
 
+/// Parse the exit status of a coordinator launched with
+/// [CoordinatorConfig::resource_limits], see
+/// [default_parse_exit_status()]. An unexpected exit is noted as
+/// possibly being caused by a limit being exceeded.
+fn parse_limited_exit_status(exit_status: subprocess::ExitStatus) -> eyre::Result<()> {
+    default_parse_exit_status(exit_status).wrap_err(match exit_status {
+        // SIGXCPU
+        subprocess::ExitStatus::Signaled(24) => {
+            "The coordinator exceeded its CPU time limit (see CoordinatorConfig::resource_limits)"
+        }
+        _ => {
+            "The coordinator exited unexpectedly while running under resource limits, \
+                it may have exceeded its memory limit (see CoordinatorConfig::resource_limits)"
+        }
+    })
+}
+
+/// The [CeremonyMessage::CoordinatorExited] message which is
+/// broadcast by [run_coordinator()] when the coordinator process
+/// exits with `exit_status`.
+pub fn coordinator_exited_message(exit_status: subprocess::ExitStatus) -> CeremonyMessage {
+    let (code, signal) = match exit_status {
+        subprocess::ExitStatus::Exited(code) => (Some(code as i32), None),
+        subprocess::ExitStatus::Signaled(signal) => (None, Some(signal as i32)),
+        _ => (None, None),
+    };
+    CeremonyMessage::CoordinatorExited { code, signal }
+}
+
 /// Configuration for the [run_coordinator()] function to run
 /// `aleo-setup-coordinator` rocket server.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CoordinatorConfig {
     /// The location of the `aleo-setup-coordinator` repository.
     pub crate_dir: PathBuf,
@@ -184,343 +450,7929 @@ pub struct CoordinatorConfig {
     pub environment: Environment,
     /// The directory where all the artifacts produced while running
     /// the coordinator will be stored (and the current working
-    /// directory for the process).
+    /// directory for the process, unless
+    /// [CoordinatorConfig::working_dir] is specified).
     pub out_dir: PathBuf,
+    /// The current working directory for the coordinator process, see
+    /// [CoordinatorConfig::working_dir()], e.g. so that relative paths
+    /// to assets in the [CoordinatorConfig::crate_dir] resolve. The
+    /// coordinator writes its transcript relative to its working
+    /// directory, so the [CoordinatorConfig::transcript_dir()] is
+    /// located here, while all the other artifacts (logs, config and
+    /// the SQLite database) remain in the
+    /// [CoordinatorConfig::out_dir]. Default: the
+    /// [CoordinatorConfig::out_dir].
+    pub working_dir: Option<PathBuf>,
     /// List of replacement contributors in use for the ceremony.
     pub replacement_contributors: Vec<ContributorRef>,
+    /// How long to wait after the coordinator has booted for it to
+    /// create the [CoordinatorConfig::transcript_dir()], see
+    /// [wait_for_transcript_dir()].
+    pub transcript_dir_timeout: Duration,
+    /// Overrides the default [EnvironmentParameters] used by the
+    /// coordinator if `Some`.
+    pub environment_parameters: Option<EnvironmentParameters>,
+    /// The number of the last round expected to run during the test.
+    /// If `Some`, the [CeremonyMessage::Shutdown] is broadcast as soon
+    /// as this round has finished, instead of waiting for
+    /// participants for the next round.
+    pub max_rounds: Option<u64>,
+    /// How the configuration file is passed to the coordinator when
+    /// it is launched.
+    pub launch_mode: LaunchMode,
+    /// How long to wait for the coordinator process to exit after its
+    /// `stdout` has closed, before it is killed, see
+    /// [MonitorProcessOptions::eof_grace].
+    pub eof_grace: Duration,
+    /// How long to wait for the coordinator process to exit after it
+    /// has been sent `SIGTERM` (when the ceremony shuts down), before
+    /// it is killed, see [MonitorProcessOptions::terminate_grace].
+    pub terminate_grace: Duration,
+    /// The number of times to relaunch the coordinator if it exits
+    /// with an error before it has booted (e.g. because it failed to
+    /// bind its port), see [MonitorProcessOptions::startup_retries].
+    /// Crashes after the coordinator has booted are never retried.
+    pub startup_retries: u32,
+    /// How long to wait before the first relaunch of the coordinator,
+    /// doubled for each subsequent relaunch up to
+    /// [crate::process::MAX_STARTUP_BACKOFF], see
+    /// [CoordinatorConfig::startup_retries].
+    pub startup_backoff: Duration,
+    /// Overrides the number of powers (of tau) used by the
+    /// coordinator for the ceremony if `Some`, which determines the
+    /// size of the ceremony. Must be within [NUM_POWERS_RANGE].
+    pub num_powers: Option<u32>,
+    /// If `true`, check that the version of the
+    /// [CoordinatorConfig::setup_coordinator_bin] is supported by this
+    /// crate before starting it, see [check_coordinator_version()].
+    pub check_version: bool,
+    /// How messages are broadcast if the ceremony bus is full, so
+    /// that a slow consumer cannot stall the parsing of the
+    /// coordinator's log output, see [broadcast_with_retry()].
+    pub broadcast_retry: BroadcastRetryOptions,
+    /// If `true`, the transcript for each round is copied into its own
+    /// directory tree under [CoordinatorConfig::out_dir] (e.g.
+    /// `round_2/transcript/round_2`) for archival as soon as the round
+    /// finishes (see [archive_round()]), and the round's artifacts are
+    /// checked there instead of in the shared `transcript/round_N`
+    /// layout which the coordinator writes to. See
+    /// [CoordinatorConfig::round_dir()].
+    pub per_round_out_dir: bool,
+    /// Overrides the journal mode of the coordinator's SQLite
+    /// database if `Some`, which can significantly affect performance
+    /// under heavy load.
+    pub sqlite_journal_mode: Option<SqliteJournalMode>,
+    /// The address that the coordinator's server listens on. Each
+    /// coordinator running in parallel needs a distinct port. See
+    /// [default_listen_address()].
+    pub listen_address: SocketAddr,
+    /// Overrides the location of the coordinator's SQLite database if
+    /// `Some`. A relative path is relative to the
+    /// [CoordinatorConfig::out_dir]. See
+    /// [CoordinatorConfig::sqlite_file()].
+    pub sqlite_file: Option<PathBuf>,
+    /// Overrides the default [RuntimeParameters] used by the
+    /// coordinator if `Some`, e.g. for profiling the effect of the
+    /// number of aggregation threads.
+    pub runtime_parameters: Option<RuntimeParameters>,
+    /// Overrides the default (disabled) [ReliabilityCheckSettings]
+    /// used by the coordinator if `Some`, to exercise the
+    /// reliability-gated queue join.
+    pub reliability_check: Option<ReliabilityCheckSettings>,
+    /// Overrides the default [VerifierSettings] used by the
+    /// coordinator if `Some`, e.g. to exercise eviction from the
+    /// verifiers' assigned tasks cache.
+    pub verifier_settings: Option<VerifierSettings>,
+    /// The `RUST_LOG` filter directives passed to the coordinator
+    /// process, e.g. `trace` when debugging, or `info` to reduce the
+    /// volume of logs. See [CoordinatorConfig::validate_log_level()].
+    /// Default: [DEFAULT_COORDINATOR_LOG_LEVEL].
+    pub log_level: String,
+    /// The maximum size in bytes of the `coordinator.log` file, if
+    /// `Some`. When it would be exceeded, the file is rolled over to
+    /// `coordinator.log.1` (replacing any previous one) and a new
+    /// file is started, to avoid filling the disk during long
+    /// running tests.
+    pub max_log_size: Option<u64>,
+    /// Whether to also write the coordinator's output to a
+    /// `coordinator.jsonl` file, with one [CoordinatorLogRecord] per
+    /// line, so that it can be processed without parsing the plain
+    /// text log.
+    pub json_log: bool,
+    /// Whether to prefix each line written to `coordinator.log` (and
+    /// `coordinator.stderr.log`) with an RFC3339 timestamp of when it
+    /// was read from the coordinator, to help correlate it with the
+    /// logs of the other processes. The coordinator's output follows
+    /// the timestamp unchanged, after a single space.
+    pub log_timestamps: bool,
+    /// If `Some`, shut the ceremony down with [ShutdownReason::Error]
+    /// when the coordinator's state (see [CoordinatorStateHistory])
+    /// has not changed within this duration, so that a wedged
+    /// coordinator fails the test quickly. Time spent paused for
+    /// maintenance does not count.
+    pub stall_timeout: Option<Duration>,
+    /// If `Some`, poll the transcript directory at this interval for
+    /// the `state.json` file of each round (see
+    /// [CoordinatorConfig::round_state_file()]), and broadcast
+    /// [CeremonyMessage::RoundStateWritten] when it appears, see
+    /// [run_round_state_watcher()].
+    pub round_state_poll_interval: Option<Duration>,
+    /// If `Some`, broadcast a [CeremonyMessage::Heartbeat] with the
+    /// current state of the ceremony at this interval, see
+    /// [run_heartbeat()].
+    pub heartbeat_interval: Option<Duration>,
+    /// Resource limits to run the coordinator under, to catch
+    /// regressions in its resource usage. The coordinator is
+    /// launched with the `prlimit` utility (from `util-linux`, so the
+    /// limits are only supported on Linux), which sets the limits
+    /// before it executes the coordinator, so the coordinator is
+    /// limited from the start and is still the monitored process.
+    /// When the coordinator is killed or fails because of a limit,
+    /// the ceremony is shut down with [ShutdownReason::CoordinatorDied]
+    /// and the error notes the limits. Default: no limits.
+    pub resource_limits: ResourceLimits,
+    /// Patterns to use in place of the built-in patterns for parsing
+    /// the coordinator's output. Default: no overrides.
+    pub log_pattern_overrides: LogPatternOverrides,
+    /// The [TwitterSettings] for the coordinator, if it needs them.
+    /// When `None`, the `[twitter_settings]` section is omitted from
+    /// the coordinator's configuration file. Read from the
+    /// environment by [CoordinatorConfig::from_profile()], see
+    /// [TwitterSettings::from_env()].
+    pub twitter_settings: Option<TwitterSettings>,
+    /// Whether to shut the ceremony down (with
+    /// [ShutdownReason::TestFinished]) when a round is rolled back
+    /// by the coordinator, see [CeremonyMessage::RoundRolledBack].
+    /// When `false` the ceremony waits for round 1 to start again.
+    pub shutdown_on_rollback: bool,
+    /// If `true`, [launch_coordinator()] only writes the
+    /// coordinator's `config.toml` (see
+    /// [CoordinatorTomlConfiguration]), without executing the
+    /// coordinator or checking its binary. Useful for testing the
+    /// generated configuration without the coordinator. Default:
+    /// `false`.
+    pub dry_run: bool,
 }
 
-impl CoordinatorConfig {
-    /// Calculates where the directory containing the ceremony
-    /// transcript is located.
-    pub fn transcript_dir(&self) -> PathBuf {
-        if let Environment::Development = self.environment {
-            self.out_dir.join("transcript/development")
-        } else {
-            self.out_dir.join("transcript")
-        }
-    }
+/// How the path to the coordinator's configuration file (see
+/// [CoordinatorTomlConfiguration]) is passed to the
+/// [CoordinatorConfig::setup_coordinator_bin] when it is launched.
+/// This allows the test to drive wrapper scripts which do not accept
+/// the `--config` command line option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchMode {
+    /// Pass the path with the `--config <path>` command line option.
+    Arg,
+    /// Set the environment variable with the specified name to the
+    /// path.
+    EnvVar(String),
+    /// Redirect the contents of the configuration file to the
+    /// process's `stdin`.
+    Stdin,
 }
 
-/// Run the `aleo-setup-coordinator` rocket server.
-pub fn run_coordinator(
-    config: &CoordinatorConfig,
-    ceremony_tx: Sender<CeremonyMessage>,
-    ceremony_rx: Receiver<CeremonyMessage>,
-) -> eyre::Result<MonitorProcessJoin> {
-    let span = tracing::error_span!("coordinator");
-    let _guard = span.enter();
-
-    let toml_config = CoordinatorTomlConfiguration::from(config);
-    let toml_config_str = toml::to_string_pretty(&toml_config)
-        .wrap_err("Error while serializing coordinator toml config")?;
-    let toml_config_path = config.out_dir.join("config.toml");
-    std::fs::write(&toml_config_path, &toml_config_str)
-        .wrap_err("Error while writing corodinator config.toml file")?;
+impl Default for LaunchMode {
+    fn default() -> Self {
+        Self::Arg
+    }
+}
 
-    tracing::info!("Starting setup coordinator.");
+/// Default value for [CoordinatorConfig::listen_address]
+/// (`0.0.0.0:9000`).
+pub fn default_listen_address() -> SocketAddr {
+    SocketAddr::from_str("0.0.0.0:9000").unwrap()
+}
 
-    let exec = Exec::cmd(config.setup_coordinator_bin.canonicalize()?)
-        .cwd(&config.out_dir)
-        .env("RUST_BACKTRACE", "1")
-        .env("RUST_LOG", "debug")
-        .arg("--config")
-        .arg(
-            toml_config_path
-                .canonicalize()
-                .wrap_err("cannot canonicalize toml config path")?,
-        );
+/// Default value for [CoordinatorConfig::transcript_dir_timeout].
+pub const DEFAULT_TRANSCRIPT_DIR_TIMEOUT: Duration = Duration::from_secs(30);
 
-    let log_file_path = config.out_dir.join("coordinator.log");
+/// Default value for [CoordinatorConfig::log_level].
+pub const DEFAULT_COORDINATOR_LOG_LEVEL: &str = "debug";
 
-    let (join, _) = run_monitor_process(
-        "coordinator".to_string(),
-        exec,
-        default_parse_exit_status,
-        ceremony_tx,
-        ceremony_rx,
-        fallible_monitor(move |stdout, ceremony_tx, _monitor_tx| {
-            monitor_coordinator(stdout, ceremony_tx, &log_file_path)
-        }),
-    )?;
+/// The values allowed for [CoordinatorConfig::num_powers]. The
+/// ceremony is made up of `2^num_powers` powers, so it needs at least
+/// one power, and `2^num_powers` must fit in the coordinator's 64 bit
+/// sizes. The limits of each environment are defined by the
+/// coordinator itself (which this crate doesn't duplicate), so a
+/// value within this range which the environment doesn't support is
+/// reported by the coordinator when it starts.
+pub const NUM_POWERS_RANGE: std::ops::RangeInclusive<u32> = 1..=63;
 
-    Ok(join)
+/// Standard coordinator configurations for common test scenarios,
+/// used with [CoordinatorConfig::from_profile()] to avoid duplicating
+/// tuned values across tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoordinatorConfigProfile {
+    /// A quick check that a ceremony can complete, using the
+    /// development environment with a single contributor per round
+    /// and short timeouts so that failures surface quickly.
+    Smoke,
+    /// A full-sized ceremony using the universal environment, with
+    /// the coordinator's default parameters.
+    Full,
+    /// Many contributors per round using the development environment,
+    /// with generous timeouts to tolerate a heavily loaded machine.
+    Stress,
 }
 
-#[derive(Debug)]
-#[non_exhaustive]
-enum CoordinatorState {
-    /// The process has just started.
-    ProcessStarted,
-    /// The coordinator is ready and the specified round is waiting
-    /// for participants before it can start.
-    RoundWaitingForParticipants(u64),
-    /// The specified round has started and is running.
-    RoundRunning(u64),
-    /// The round has completed contributions and verifications, and
-    /// the coordinator is aggregating chunks.
-    RoundAggregating(u64),
-    /// The round has completed aggregation and is now waiting for the
-    /// final report.
-    RoundWaitingForFinish(u64),
-    /// The round has finished. Waiting to confirm that the next round
-    /// is awaiting participants.
-    RoundFinished(u64),
-}
+impl CoordinatorConfigProfile {
+    /// The [Environment] used by this profile.
+    pub fn environment(&self) -> Environment {
+        match self {
+            CoordinatorConfigProfile::Smoke => Environment::Development,
+            CoordinatorConfigProfile::Full => Environment::Universal,
+            CoordinatorConfigProfile::Stress => Environment::Development,
+        }
+    }
 
-/// This struct keeps track of the current state of the coordinator.
-struct CoordinatorStateReporter {
-    ceremony_tx: Sender<CeremonyMessage>,
-    current_state: CoordinatorState,
-}
+    /// The [EnvironmentParameters] used by this profile.
+    pub fn environment_parameters(&self) -> EnvironmentParameters {
+        match self {
+            CoordinatorConfigProfile::Smoke => EnvironmentParameters {
+                minimum_contributors_per_round: NonZeroUsize::new(1).unwrap(),
+                maximum_contributors_per_round: NonZeroUsize::new(1).unwrap(),
+                contributor_seen_timeout: 300,
+                participant_lock_timeout: 300,
+                queue_seen_timeout: 300,
+            },
+            CoordinatorConfigProfile::Full => EnvironmentParameters::default(),
+            CoordinatorConfigProfile::Stress => EnvironmentParameters {
+                minimum_contributors_per_round: NonZeroUsize::new(1).unwrap(),
+                maximum_contributors_per_round: NonZeroUsize::new(20).unwrap(),
+                contributor_seen_timeout: 3600,
+                participant_lock_timeout: 1800,
+                queue_seen_timeout: 3600,
+            },
+        }
+    }
 
-lazy_static::lazy_static! {
-    static ref BOOTED_RE: Regex = Regex::new(".*Coordinator has booted up.*").unwrap();
-    static ref ROUND_STARTED_RE: Regex = Regex::new(".*Advanced ceremony to round (?P<round>[0-9]+).*").unwrap();
-    static ref ROUND_STARTED_AGGREGATION_RE: Regex = Regex::new(".*Starting aggregation on round (?P<round>[0-9]+)").unwrap();
-    static ref ROUND_AGGREGATED_RE: Regex = Regex::new(".*Round (?P<round>[0-9]+) is aggregated.*").unwrap();
-    static ref ROUND_FINISHED_RE: Regex = Regex::new(".*Round (?P<round>[0-9]+) is finished.*").unwrap();
-    static ref DROPPED_PARTICIPANT_RE: Regex = Regex::new(".*Dropping (?P<address>aleo[a-z0-9]+)[.](?P<participant_type>contributor|verifier) from the ceremony").unwrap();
-    static ref SUCCESSFUL_CONTRIBUTION_RE: Regex = Regex::new(".*((?P<address>aleo[a-z0-9]+)[.]contributor) added a contribution to chunk (?P<chunk>[0-9]+)").unwrap();
-    static ref ROUND_RESTARTED_NO_CONTRIBUTORS_RE: Regex = Regex::new(".*No contributors remaining to reset and complete the current round. Rolling back to round 0 to wait and accept new participants.*").unwrap();
+    /// The [CoordinatorConfig::transcript_dir_timeout] used by this
+    /// profile.
+    pub fn transcript_dir_timeout(&self) -> Duration {
+        match self {
+            CoordinatorConfigProfile::Smoke => DEFAULT_TRANSCRIPT_DIR_TIMEOUT,
+            CoordinatorConfigProfile::Full => Duration::from_secs(120),
+            CoordinatorConfigProfile::Stress => Duration::from_secs(60),
+        }
+    }
 }
 
-impl CoordinatorStateReporter {
-    /// Create a new [CoordinatorStateReporter] with the state that
-    /// the process has just been started.
-    fn process_started(ceremony_tx: Sender<CeremonyMessage>) -> Self {
-        Self {
-            ceremony_tx,
-            current_state: CoordinatorState::ProcessStarted,
-        }
+impl CoordinatorConfig {
+    /// Create a [CoordinatorConfig] using the environment, parameters
+    /// and timeouts of the specified `profile`.
+    pub fn from_profile(
+        profile: CoordinatorConfigProfile,
+        crate_dir: PathBuf,
+        setup_coordinator_bin: PathBuf,
+        out_dir: PathBuf,
+    ) -> Self {
+        Self::builder()
+            .crate_dir(crate_dir)
+            .setup_coordinator_bin(setup_coordinator_bin)
+            .environment(profile.environment())
+            .out_dir(out_dir)
+            .transcript_dir_timeout(profile.transcript_dir_timeout())
+            .environment_parameters(profile.environment_parameters())
+            .build()
+            .expect("all the required fields are set")
     }
 
-    /// Check whether a participant has been dropped from the round
-    /// (and broadcast this fact with [CeremonyMessage::ParticipantDropped]).
-    fn check_participant_dropped(&mut self, line: &str) -> eyre::Result<()> {
-        if let Some(captures) = DROPPED_PARTICIPANT_RE.captures(line) {
-            let address_str = captures
-                .name("address")
-                .expect("expected address group to be captured")
-                .as_str()
-                .to_string();
-            let participant_type_s = captures
-                .name("participant_type")
-                .expect("expected participant_type group to be captured")
-                .as_str();
-
-            let address = AleoPublicKey::from_str(&address_str)?;
-
-            let participant = match participant_type_s {
-                "contributor" => ParticipantRef::Contributor(ContributorRef { address }),
-                "verifier" => ParticipantRef::Verifier(VerifierRef { address }),
-                _ => {
-                    return Err(eyre::eyre!(
-                        "unknown participant type: {}",
-                        participant_type_s
-                    ))
-                }
-            };
+    /// Create a [CoordinatorConfigBuilder], for constructing a
+    /// [CoordinatorConfig] without having to specify every field.
+    pub fn builder() -> CoordinatorConfigBuilder {
+        CoordinatorConfigBuilder::default()
+    }
 
-            self.ceremony_tx
-                .broadcast(CeremonyMessage::ParticipantDropped(participant))?;
+    /// Check that [CoordinatorConfig::num_powers] (if specified) is
+    /// within [NUM_POWERS_RANGE].
+    pub fn validate_num_powers(&self) -> eyre::Result<()> {
+        if let Some(num_powers) = self.num_powers {
+            if !NUM_POWERS_RANGE.contains(&num_powers) {
+                return Err(eyre::eyre!(
+                    "num_powers {} is outside the supported range {:?}",
+                    num_powers,
+                    NUM_POWERS_RANGE
+                ));
+            }
         }
 
         Ok(())
     }
 
-    /// Parse stdout line from the `coordinator` process, broadcast
-    /// messages to the ceremony when the coordinator state changes.
-    /// Keeps track of the current state of the ceremony.
-    ///
-    /// TODO: verify that the round number in the regular expression
-    /// group matches the current state.
-    fn parse_output_line(&mut self, line: &str) -> eyre::Result<()> {
-        match self.current_state {
-            CoordinatorState::ProcessStarted => {
-                if BOOTED_RE.is_match(line) {
-                    tracing::debug!("Coordinator process has started");
-                    self.ceremony_tx
-                        .broadcast(CeremonyMessage::RoundWaitingForParticipants(1))?;
-                    self.current_state = CoordinatorState::RoundWaitingForParticipants(1);
-                }
-            }
-            CoordinatorState::RoundWaitingForParticipants(round) => {
-                // Check whether any participants have beend dropped
-                // while the round is waiting for participants before
-                // the round has started.
-                self.check_participant_dropped(line)?;
-                if ROUND_STARTED_RE.is_match(line) {
-                    tracing::debug!(
-                        "Detected that round {} is now waiting for participants.",
-                        round
-                    );
-                    self.ceremony_tx
-                        .broadcast(CeremonyMessage::RoundStarted(round))?;
-                    self.current_state = CoordinatorState::RoundRunning(round);
-                }
-            }
-            CoordinatorState::RoundRunning(round) => {
-                // Check whether any participants have beend dropped
-                // while the round is running.
-                self.check_participant_dropped(line)?;
-                if ROUND_STARTED_AGGREGATION_RE.is_match(line) {
-                    tracing::debug!("Detected that round {} is has started running.", round);
-                    self.ceremony_tx
-                        .broadcast(CeremonyMessage::RoundStartedAggregation(round))?;
-                    self.current_state = CoordinatorState::RoundAggregating(round);
-                }
-
-                if ROUND_RESTARTED_NO_CONTRIBUTORS_RE.is_match(line) {
-                    tracing::debug!(
-                        "Detected that round {} has restarted with no remaining contributors.",
-                        round
-                    );
-                    self.ceremony_tx
-                        .broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))?;
-                    self.current_state = CoordinatorState::RoundFinished(round);
-                }
-
-                if let Some(captures) = SUCCESSFUL_CONTRIBUTION_RE.captures(line) {
-                    let address_str = captures
-                        .name("address")
-                        .expect("expected address group to be captured")
-                        .as_str()
-                        .to_string();
+    /// Check that [CoordinatorConfig::log_level] is a valid set of
+    /// `RUST_LOG` filter directives.
+    pub fn validate_log_level(&self) -> eyre::Result<()> {
+        tracing_subscriber::EnvFilter::try_new(&self.log_level)
+            .map(|_| ())
+            .wrap_err_with(|| eyre::eyre!("Invalid coordinator log level {:?}", self.log_level))
+    }
 
-                    let chunk = u64::from_str(
-                        captures
-                            .name("chunk")
-                            .expect("exprected chunk address to be captured")
-                            .as_str(),
-                    )?;
+    /// Check that [CoordinatorConfig::setup_coordinator_bin] exists,
+    /// is a file, and (on unix platforms) is executable.
+    pub fn validate_coordinator_bin(&self) -> eyre::Result<()> {
+        let bin_path = &self.setup_coordinator_bin;
+        let metadata = std::fs::metadata(bin_path)
+            .wrap_err_with(|| eyre::eyre!("Coordinator binary {:?} does not exist", bin_path))?;
 
-                    let address = AleoPublicKey::from_str(&address_str)?;
+        if !metadata.is_file() {
+            return Err(eyre::eyre!(
+                "Coordinator binary {:?} is not a file",
+                bin_path
+            ));
+        }
 
-                    tracing::debug!(
-                        "Contributor {} made a successful contribution to chunk {}.",
-                        &address,
-                        &chunk
-                    );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
 
-                    let contributor = ContributorRef { address };
-                    self.ceremony_tx
-                        .broadcast(CeremonyMessage::SuccessfulContribution {
-                            contributor,
-                            chunk,
-                        })?;
-                }
-            }
-            CoordinatorState::RoundAggregating(round) => {
-                if ROUND_AGGREGATED_RE.is_match(line) {
-                    tracing::debug!("Detected that round {} is aggregating.", round);
-                    self.ceremony_tx
-                        .broadcast(CeremonyMessage::RoundAggregated(round))?;
-                    self.current_state = CoordinatorState::RoundWaitingForFinish(round);
-                }
-            }
-            CoordinatorState::RoundWaitingForFinish(round) => {
-                if ROUND_FINISHED_RE.is_match(line) {
-                    tracing::debug!("Detected that round {} has finished.", round);
-                    self.ceremony_tx
-                        .broadcast(CeremonyMessage::RoundFinished(round))?;
-                    self.current_state = CoordinatorState::RoundFinished(round);
-                }
-            }
-            CoordinatorState::RoundFinished(round) => {
-                self.ceremony_tx
-                    .broadcast(CeremonyMessage::RoundWaitingForParticipants(round + 1))?;
-                self.current_state = CoordinatorState::RoundWaitingForParticipants(round + 1);
-                return Ok(());
+            if metadata.permissions().mode() & 0o111 == 0 {
+                return Err(eyre::eyre!(
+                    "Coordinator binary {:?} is not executable",
+                    bin_path
+                ));
             }
         }
 
         Ok(())
     }
-}
-
-/// Monitor the setup coordinator. Parses the `stderr`/`stdout` and
-/// emits messages/alters state when certain events occur, and also
-/// pipes the output to the [tracing::debug!()], and
-/// `coordinator_log.txt` log file.
-fn monitor_coordinator(
-    stdout: File,
-    ceremony_tx: Sender<CeremonyMessage>,
-    log_file_path: impl AsRef<Path>,
-) -> eyre::Result<()> {
-    let buf_pipe = BufReader::new(stdout);
-    let mut state_reporter = CoordinatorStateReporter::process_started(ceremony_tx);
 
-    let mut log_file = OpenOptions::new()
-        .append(true)
-        .create(true)
-        .open(log_file_path)
-        .wrap_err("unable to open log file")?;
+    /// The location of the transcript relative to the coordinator's
+    /// out directory, for each [Environment]:
+    ///
+    /// + [Environment::Development]: `transcript/development`
+    /// + [Environment::Inner]: `transcript`
+    /// + [Environment::Outer]: `transcript`
+    /// + [Environment::Universal]: `transcript`
+    fn transcript_path(&self) -> &'static str {
+        match self.environment {
+            Environment::Development => "transcript/development",
+            Environment::Inner => "transcript",
+            Environment::Outer => "transcript",
+            Environment::Universal => "transcript",
+        }
+    }
 
-    // It's expected that if the process closes, the stdout will also
-    // close and this iterator will complete gracefully.
-    for line_result in buf_pipe.lines() {
-        match line_result {
-            Ok(line) => {
-                state_reporter.parse_output_line(&line)?;
+    /// Calculates where the directory containing the ceremony
+    /// transcript is located, which the coordinator creates relative
+    /// to its [CoordinatorConfig::working_dir()].
+    pub fn transcript_dir(&self) -> PathBuf {
+        self.working_dir().join(self.transcript_path())
+    }
 
-                // Write to log file.
-                log_file.write_all(line.as_ref())?;
-                log_file.write_all("\n".as_ref())?;
-            }
-            Err(error) => {
-                tracing::error!(
-                    "Error reading line from pipe to coordinator process: {}",
-                    error
-                )
-            }
+    /// The directory containing all the artifacts for the specified
+    /// `round`. This is the [CoordinatorConfig::out_dir] unless
+    /// [CoordinatorConfig::per_round_out_dir] is enabled.
+    pub fn round_out_dir(&self, round: u64) -> PathBuf {
+        if self.per_round_out_dir {
+            self.out_dir.join(format!("round_{}", round))
+        } else {
+            self.out_dir.clone()
         }
     }
 
-    Ok(())
-}
+    /// Calculates where the transcript directory for the specified
+    /// `round` is located once the round has finished, taking into
+    /// account [CoordinatorConfig::per_round_out_dir].
+    pub fn round_dir(&self, round: u64) -> PathBuf {
+        self.round_out_dir(round)
+            .join(self.transcript_path())
+            .join(format!("round_{}", round))
+    }
 
-#[derive(Deserialize)]
-struct RoundState {
-    /// The ids of the contributors in the round.
-    #[serde(rename = "contributorIds")]
-    contributor_ids: Vec<String>,
-    /// The ids of the verifiers in the round.
-    #[serde(rename = "verifierIds")]
-    verifier_ids: Vec<String>,
-}
+    /// The transcript directory for the specified `round` which the
+    /// coordinator writes to while the round is in progress, in the
+    /// [CoordinatorConfig::transcript_dir()].
+    pub fn live_round_dir(&self, round: u64) -> PathBuf {
+        self.transcript_dir().join(format!("round_{}", round))
+    }
 
-/// Check that the specified participants are in the specified round
-/// transcript.
-pub fn check_participants_in_round(
-    config: &CoordinatorConfig,
-    round: u64,
-    contributors: &[Contributor],
-    verifiers: &[Verifier],
-) -> eyre::Result<()> {
-    let state_file = config
-        .transcript_dir()
-        .join(format!("round_{}", round))
-        .join("state.json");
+    /// Calculates where the `state.json` file for the specified
+    /// `round` is located, in the [CoordinatorConfig::live_round_dir()]
+    /// so that it can be read while the round is in progress.
+    pub fn round_state_file(&self, round: u64) -> PathBuf {
+        self.live_round_dir(round).join("state.json")
+    }
+
+    /// The current working directory for the coordinator process,
+    /// [CoordinatorConfig::working_dir] if specified, otherwise the
+    /// [CoordinatorConfig::out_dir].
+    pub fn working_dir(&self) -> &Path {
+        self.working_dir.as_deref().unwrap_or(&self.out_dir)
+    }
+
+    /// The path to the file which the coordinator's output is logged
+    /// to by [run_coordinator()], `coordinator.log` in the
+    /// [CoordinatorConfig::out_dir].
+    pub fn log_file(&self) -> PathBuf {
+        self.out_dir.join("coordinator.log")
+    }
+
+    /// The path to the file which the coordinator's `stderr` is logged
+    /// to by [run_coordinator()] when it is read separately (see
+    /// [ProcessOutput::stderr]), `coordinator.stderr.log` in the
+    /// [CoordinatorConfig::out_dir].
+    pub fn stderr_log_file(&self) -> PathBuf {
+        self.out_dir.join("coordinator.stderr.log")
+    }
+
+    /// The path to the SQLite database file which the coordinator
+    /// stores its state in, [CoordinatorConfig::sqlite_file] if
+    /// specified, otherwise [SQLITE_FILE_NAME] in the
+    /// [CoordinatorConfig::out_dir].
+    pub fn sqlite_file(&self) -> PathBuf {
+        self.out_dir.join(
+            self.sqlite_file
+                .as_deref()
+                .unwrap_or_else(|| Path::new(SQLITE_FILE_NAME)),
+        )
+    }
+}
+
+/// A builder for a [CoordinatorConfig], see
+/// [CoordinatorConfig::builder()]. The
+/// [CoordinatorConfig::crate_dir],
+/// [CoordinatorConfig::setup_coordinator_bin],
+/// [CoordinatorConfig::environment] and [CoordinatorConfig::out_dir]
+/// are required, every other field has a default.
+#[derive(Debug, Clone)]
+pub struct CoordinatorConfigBuilder {
+    crate_dir: Option<PathBuf>,
+    setup_coordinator_bin: Option<PathBuf>,
+    environment: Option<Environment>,
+    out_dir: Option<PathBuf>,
+    working_dir: Option<PathBuf>,
+    replacement_contributors: Vec<ContributorRef>,
+    transcript_dir_timeout: Duration,
+    environment_parameters: Option<EnvironmentParameters>,
+    max_rounds: Option<u64>,
+    launch_mode: LaunchMode,
+    eof_grace: Duration,
+    terminate_grace: Duration,
+    startup_retries: u32,
+    startup_backoff: Duration,
+    num_powers: Option<u32>,
+    check_version: bool,
+    broadcast_retry: BroadcastRetryOptions,
+    per_round_out_dir: bool,
+    sqlite_journal_mode: Option<SqliteJournalMode>,
+    listen_address: SocketAddr,
+    sqlite_file: Option<PathBuf>,
+    runtime_parameters: Option<RuntimeParameters>,
+    reliability_check: Option<ReliabilityCheckSettings>,
+    verifier_settings: Option<VerifierSettings>,
+    log_level: String,
+    max_log_size: Option<u64>,
+    json_log: bool,
+    log_timestamps: bool,
+    stall_timeout: Option<Duration>,
+    round_state_poll_interval: Option<Duration>,
+    heartbeat_interval: Option<Duration>,
+    resource_limits: ResourceLimits,
+    log_pattern_overrides: LogPatternOverrides,
+    twitter_settings: Option<TwitterSettings>,
+    shutdown_on_rollback: bool,
+    dry_run: bool,
+}
+
+impl Default for CoordinatorConfigBuilder {
+    fn default() -> Self {
+        Self {
+            crate_dir: None,
+            setup_coordinator_bin: None,
+            environment: None,
+            out_dir: None,
+            working_dir: None,
+            replacement_contributors: Vec::new(),
+            transcript_dir_timeout: DEFAULT_TRANSCRIPT_DIR_TIMEOUT,
+            environment_parameters: None,
+            max_rounds: None,
+            launch_mode: LaunchMode::default(),
+            eof_grace: MonitorProcessOptions::default().eof_grace,
+            terminate_grace: MonitorProcessOptions::default().terminate_grace,
+            startup_retries: MonitorProcessOptions::default().startup_retries,
+            startup_backoff: MonitorProcessOptions::default().startup_backoff,
+            num_powers: None,
+            check_version: false,
+            broadcast_retry: BroadcastRetryOptions::default(),
+            per_round_out_dir: false,
+            sqlite_journal_mode: None,
+            listen_address: default_listen_address(),
+            sqlite_file: None,
+            runtime_parameters: None,
+            reliability_check: None,
+            verifier_settings: None,
+            log_level: DEFAULT_COORDINATOR_LOG_LEVEL.to_string(),
+            max_log_size: None,
+            json_log: false,
+            log_timestamps: false,
+            stall_timeout: None,
+            round_state_poll_interval: None,
+            heartbeat_interval: None,
+            resource_limits: ResourceLimits::default(),
+            log_pattern_overrides: LogPatternOverrides::default(),
+            twitter_settings: Some(TwitterSettings::from_env()),
+            shutdown_on_rollback: true,
+            dry_run: false,
+        }
+    }
+}
+
+impl CoordinatorConfigBuilder {
+    /// See [CoordinatorConfig::crate_dir].
+    pub fn crate_dir(mut self, crate_dir: impl Into<PathBuf>) -> Self {
+        self.crate_dir = Some(crate_dir.into());
+        self
+    }
+
+    /// See [CoordinatorConfig::setup_coordinator_bin].
+    pub fn setup_coordinator_bin(mut self, setup_coordinator_bin: impl Into<PathBuf>) -> Self {
+        self.setup_coordinator_bin = Some(setup_coordinator_bin.into());
+        self
+    }
+
+    /// See [CoordinatorConfig::environment].
+    pub fn environment(mut self, environment: Environment) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
+    /// See [CoordinatorConfig::out_dir].
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// See [CoordinatorConfig::working_dir].
+    pub fn working_dir(mut self, working_dir: impl Into<PathBuf>) -> Self {
+        self.working_dir = Some(working_dir.into());
+        self
+    }
+
+    /// See [CoordinatorConfig::replacement_contributors].
+    pub fn replacement_contributors(
+        mut self,
+        replacement_contributors: Vec<ContributorRef>,
+    ) -> Self {
+        self.replacement_contributors = replacement_contributors;
+        self
+    }
+
+    /// See [CoordinatorConfig::transcript_dir_timeout].
+    pub fn transcript_dir_timeout(mut self, transcript_dir_timeout: Duration) -> Self {
+        self.transcript_dir_timeout = transcript_dir_timeout;
+        self
+    }
+
+    /// See [CoordinatorConfig::environment_parameters].
+    pub fn environment_parameters(mut self, environment_parameters: EnvironmentParameters) -> Self {
+        self.environment_parameters = Some(environment_parameters);
+        self
+    }
+
+    /// See [CoordinatorConfig::max_rounds].
+    pub fn max_rounds(mut self, max_rounds: u64) -> Self {
+        self.max_rounds = Some(max_rounds);
+        self
+    }
+
+    /// See [CoordinatorConfig::launch_mode].
+    pub fn launch_mode(mut self, launch_mode: LaunchMode) -> Self {
+        self.launch_mode = launch_mode;
+        self
+    }
+
+    /// See [CoordinatorConfig::eof_grace].
+    pub fn eof_grace(mut self, eof_grace: Duration) -> Self {
+        self.eof_grace = eof_grace;
+        self
+    }
+
+    /// See [CoordinatorConfig::terminate_grace].
+    pub fn terminate_grace(mut self, terminate_grace: Duration) -> Self {
+        self.terminate_grace = terminate_grace;
+        self
+    }
+
+    /// See [CoordinatorConfig::startup_retries].
+    pub fn startup_retries(mut self, startup_retries: u32) -> Self {
+        self.startup_retries = startup_retries;
+        self
+    }
+
+    /// See [CoordinatorConfig::startup_backoff].
+    pub fn startup_backoff(mut self, startup_backoff: Duration) -> Self {
+        self.startup_backoff = startup_backoff;
+        self
+    }
+
+    /// See [CoordinatorConfig::num_powers].
+    pub fn num_powers(mut self, num_powers: u32) -> Self {
+        self.num_powers = Some(num_powers);
+        self
+    }
+
+    /// See [CoordinatorConfig::check_version].
+    pub fn check_version(mut self, check_version: bool) -> Self {
+        self.check_version = check_version;
+        self
+    }
+
+    /// See [CoordinatorConfig::broadcast_retry].
+    pub fn broadcast_retry(mut self, broadcast_retry: BroadcastRetryOptions) -> Self {
+        self.broadcast_retry = broadcast_retry;
+        self
+    }
+
+    /// See [CoordinatorConfig::per_round_out_dir].
+    pub fn per_round_out_dir(mut self, per_round_out_dir: bool) -> Self {
+        self.per_round_out_dir = per_round_out_dir;
+        self
+    }
+
+    /// See [CoordinatorConfig::sqlite_journal_mode].
+    pub fn sqlite_journal_mode(mut self, sqlite_journal_mode: SqliteJournalMode) -> Self {
+        self.sqlite_journal_mode = Some(sqlite_journal_mode);
+        self
+    }
+
+    /// See [CoordinatorConfig::listen_address].
+    pub fn listen_address(mut self, listen_address: SocketAddr) -> Self {
+        self.listen_address = listen_address;
+        self
+    }
+
+    /// See [CoordinatorConfig::sqlite_file].
+    pub fn sqlite_file(mut self, sqlite_file: PathBuf) -> Self {
+        self.sqlite_file = Some(sqlite_file);
+        self
+    }
+
+    /// See [CoordinatorConfig::runtime_parameters].
+    pub fn runtime_parameters(mut self, runtime_parameters: RuntimeParameters) -> Self {
+        self.runtime_parameters = Some(runtime_parameters);
+        self
+    }
+
+    /// See [CoordinatorConfig::reliability_check].
+    pub fn reliability_check(mut self, reliability_check: ReliabilityCheckSettings) -> Self {
+        self.reliability_check = Some(reliability_check);
+        self
+    }
+
+    /// See [CoordinatorConfig::verifier_settings].
+    pub fn verifier_settings(mut self, verifier_settings: VerifierSettings) -> Self {
+        self.verifier_settings = Some(verifier_settings);
+        self
+    }
+
+    /// See [CoordinatorConfig::log_level].
+    pub fn log_level(mut self, log_level: impl Into<String>) -> Self {
+        self.log_level = log_level.into();
+        self
+    }
+
+    /// See [CoordinatorConfig::max_log_size].
+    pub fn max_log_size(mut self, max_log_size: u64) -> Self {
+        self.max_log_size = Some(max_log_size);
+        self
+    }
+
+    /// See [CoordinatorConfig::json_log].
+    pub fn json_log(mut self, json_log: bool) -> Self {
+        self.json_log = json_log;
+        self
+    }
+
+    /// See [CoordinatorConfig::log_timestamps].
+    pub fn log_timestamps(mut self, log_timestamps: bool) -> Self {
+        self.log_timestamps = log_timestamps;
+        self
+    }
+
+    /// See [CoordinatorConfig::stall_timeout].
+    pub fn stall_timeout(mut self, stall_timeout: Duration) -> Self {
+        self.stall_timeout = Some(stall_timeout);
+        self
+    }
+
+    /// See [CoordinatorConfig::round_state_poll_interval].
+    pub fn round_state_poll_interval(mut self, round_state_poll_interval: Duration) -> Self {
+        self.round_state_poll_interval = Some(round_state_poll_interval);
+        self
+    }
+
+    /// See [CoordinatorConfig::heartbeat_interval].
+    pub fn heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = Some(heartbeat_interval);
+        self
+    }
+
+    /// See [CoordinatorConfig::resource_limits].
+    pub fn resource_limits(mut self, resource_limits: ResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
+
+    /// See [CoordinatorConfig::log_pattern_overrides].
+    pub fn log_pattern_overrides(mut self, log_pattern_overrides: LogPatternOverrides) -> Self {
+        self.log_pattern_overrides = log_pattern_overrides;
+        self
+    }
+
+    /// See [CoordinatorConfig::twitter_settings].
+    pub fn twitter_settings(mut self, twitter_settings: TwitterSettings) -> Self {
+        self.twitter_settings = Some(twitter_settings);
+        self
+    }
+
+    /// See [CoordinatorConfig::shutdown_on_rollback].
+    pub fn shutdown_on_rollback(mut self, shutdown_on_rollback: bool) -> Self {
+        self.shutdown_on_rollback = shutdown_on_rollback;
+        self
+    }
+
+    /// See [CoordinatorConfig::dry_run].
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Build the [CoordinatorConfig], returning an error if any of
+    /// the required fields have not been set.
+    pub fn build(self) -> eyre::Result<CoordinatorConfig> {
+        Ok(CoordinatorConfig {
+            crate_dir: self
+                .crate_dir
+                .ok_or_else(|| eyre::eyre!("CoordinatorConfig::crate_dir is required"))?,
+            setup_coordinator_bin: self.setup_coordinator_bin.ok_or_else(|| {
+                eyre::eyre!("CoordinatorConfig::setup_coordinator_bin is required")
+            })?,
+            environment: self
+                .environment
+                .ok_or_else(|| eyre::eyre!("CoordinatorConfig::environment is required"))?,
+            out_dir: self
+                .out_dir
+                .ok_or_else(|| eyre::eyre!("CoordinatorConfig::out_dir is required"))?,
+            working_dir: self.working_dir,
+            replacement_contributors: self.replacement_contributors,
+            transcript_dir_timeout: self.transcript_dir_timeout,
+            environment_parameters: self.environment_parameters,
+            max_rounds: self.max_rounds,
+            launch_mode: self.launch_mode,
+            eof_grace: self.eof_grace,
+            terminate_grace: self.terminate_grace,
+            startup_retries: self.startup_retries,
+            startup_backoff: self.startup_backoff,
+            num_powers: self.num_powers,
+            check_version: self.check_version,
+            broadcast_retry: self.broadcast_retry,
+            per_round_out_dir: self.per_round_out_dir,
+            sqlite_journal_mode: self.sqlite_journal_mode,
+            listen_address: self.listen_address,
+            sqlite_file: self.sqlite_file,
+            runtime_parameters: self.runtime_parameters,
+            reliability_check: self.reliability_check,
+            verifier_settings: self.verifier_settings,
+            log_level: self.log_level,
+            max_log_size: self.max_log_size,
+            json_log: self.json_log,
+            log_timestamps: self.log_timestamps,
+            stall_timeout: self.stall_timeout,
+            round_state_poll_interval: self.round_state_poll_interval,
+            heartbeat_interval: self.heartbeat_interval,
+            resource_limits: self.resource_limits,
+            log_pattern_overrides: self.log_pattern_overrides,
+            twitter_settings: self.twitter_settings,
+            shutdown_on_rollback: self.shutdown_on_rollback,
+            dry_run: self.dry_run,
+        })
+    }
+}
+
+/// Wait for the coordinator to create its transcript directory (see
+/// [CoordinatorConfig::transcript_dir()]), which is expected to
+/// happen shortly after it has booted. Returns an error if the
+/// directory does not appear within
+/// [CoordinatorConfig::transcript_dir_timeout], so that a
+/// misconfigured coordinator fails fast instead of the test hanging
+/// until a round times out.
+///
+/// The timeout starts once [CeremonyMessage::CoordinatorReady] is
+/// received on `ceremony_rx` (which should be subscribed before the
+/// coordinator is launched), so that the time taken to boot does not
+/// count towards it.
+pub fn wait_for_transcript_dir(
+    config: &CoordinatorConfig,
+    mut ceremony_rx: Receiver<CeremonyMessage>,
+) -> eyre::Result<()> {
+    loop {
+        match ceremony_rx.recv()? {
+            CeremonyMessage::CoordinatorReady => break,
+            CeremonyMessage::Shutdown(reason) => {
+                return Err(eyre::eyre!(
+                    "The ceremony shut down ({}) before the coordinator booted",
+                    reason
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let transcript_dir = config.transcript_dir();
+    let start_time = Instant::now();
+
+    loop {
+        if transcript_dir.exists() {
+            return Ok(());
+        }
+
+        if start_time.elapsed() > config.transcript_dir_timeout {
+            return Err(eyre::eyre!(
+                "The coordinator did not create the transcript directory {:?} within {} \
+                    of booting. This is likely caused by a problem with the coordinator's \
+                    configuration, or it lacking permission to write to {:?}.",
+                transcript_dir,
+                format_duration(config.transcript_dir_timeout),
+                config.working_dir(),
+            ));
+        }
+
+        // Sleep occasionally because otherwise this loop will run too fast.
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// The major version of `aleo-setup-coordinator` that this crate's
+/// configuration file format and log parsing is compatible with.
+pub const SUPPORTED_COORDINATOR_MAJOR_VERSION: u64 = 0;
+
+/// The version of the `aleo-setup-coordinator` binary, as reported by
+/// `aleo-setup-coordinator --version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CoordinatorVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl CoordinatorVersion {
+    /// Whether this version is supported by this crate, see
+    /// [SUPPORTED_COORDINATOR_MAJOR_VERSION].
+    pub fn is_supported(&self) -> bool {
+        self.major == SUPPORTED_COORDINATOR_MAJOR_VERSION
+    }
+}
+
+impl std::fmt::Display for CoordinatorVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref VERSION_RE: Regex = Regex::new("(?P<major>[0-9]+)[.](?P<minor>[0-9]+)[.](?P<patch>[0-9]+)").unwrap();
+}
+
+impl FromStr for CoordinatorVersion {
+    type Err = eyre::Error;
+
+    /// Parse the version from the output of `--version`, e.g.
+    /// `aleo-setup-coordinator 0.1.0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let captures = VERSION_RE
+            .captures(s)
+            .ok_or_else(|| eyre::eyre!("Unable to find a version number in {:?}", s))?;
+
+        let parse = |name: &str| -> eyre::Result<u64> {
+            u64::from_str(&captures[name])
+                .wrap_err_with(|| eyre::eyre!("Unable to parse {} version in {:?}", name, s))
+        };
+
+        Ok(Self {
+            major: parse("major")?,
+            minor: parse("minor")?,
+            patch: parse("patch")?,
+        })
+    }
+}
+
+/// Run the [CoordinatorConfig::setup_coordinator_bin] with
+/// `--version`, and return an error if the reported version is not
+/// supported by this crate (see [CoordinatorVersion::is_supported()]),
+/// to catch drift between the configuration schema used by this crate
+/// and the coordinator before the test starts.
+pub fn check_coordinator_version(config: &CoordinatorConfig) -> eyre::Result<CoordinatorVersion> {
+    let capture = Exec::cmd(&config.setup_coordinator_bin)
+        .arg("--version")
+        .stdout(Redirection::Pipe)
+        .stderr(Redirection::Merge)
+        .capture()
+        .wrap_err("Error while running the coordinator with `--version`")?;
+
+    default_parse_exit_status(capture.exit_status)
+        .wrap_err("The coordinator does not support the `--version` option")?;
+
+    let output = capture.stdout_str();
+    let version = CoordinatorVersion::from_str(output.trim())
+        .wrap_err("Unable to parse coordinator version")?;
+
+    if !version.is_supported() {
+        return Err(eyre::eyre!(
+            "The coordinator version {} is not supported, only versions {}.x are supported",
+            version,
+            SUPPORTED_COORDINATOR_MAJOR_VERSION
+        ));
+    }
+
+    tracing::info!("Coordinator version {} is supported.", version);
+
+    Ok(version)
+}
+
+/// How long [validate_config()] runs the coordinator for, when it
+/// does not support the `--check-config` option, while waiting for it
+/// to report an error parsing its configuration (or to boot).
+const VALIDATE_CONFIG_LAUNCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Check that the coordinator binary at `bin` accepts the
+/// configuration file at `toml_path` (see
+/// [CoordinatorTomlConfiguration]), to catch drift between the
+/// configuration schema used by this crate and the coordinator before
+/// the test starts. Returns a [ConfigParseError] (with the relevant
+/// section of the configuration) if it is rejected.
+///
+/// The coordinator is first run with `--check-config`. If it does not
+/// recognize that option, it is instead launched with the
+/// configuration for up to [VALIDATE_CONFIG_LAUNCH_TIMEOUT], until it
+/// reports an error parsing the configuration, boots, or exits. In
+/// this case the coordinator may create the files specified in the
+/// configuration (e.g. the SQLite database), and it is run in the
+/// directory containing `toml_path`.
+pub fn validate_config(bin: &Path, toml_path: &Path) -> eyre::Result<()> {
+    let toml_path = toml_path
+        .canonicalize()
+        .wrap_err_with(|| eyre::eyre!("Unable to find coordinator config {:?}", toml_path))?;
+    let toml_config = std::fs::read_to_string(&toml_path)
+        .wrap_err_with(|| eyre::eyre!("Unable to read coordinator config {:?}", toml_path))?;
+
+    let result = match check_config_option(bin, &toml_path) {
+        Ok(true) => Ok(()),
+        Ok(false) => {
+            tracing::debug!(
+                "The coordinator does not support `--check-config`, \
+                    launching it to validate its configuration."
+            );
+            check_config_launch(bin, &toml_path)
+        }
+        Err(error) => Err(error),
+    };
+
+    result.map_err(|error| with_config_section(error, &toml_path, &toml_config))
+}
+
+/// Run the coordinator with `--check-config`, see
+/// [validate_config()]. Returns `false` if the option is not
+/// supported, or if the coordinator is still running after
+/// [VALIDATE_CONFIG_LAUNCH_TIMEOUT] (e.g. because it ignored the
+/// option and started serving), in which case it is stopped.
+fn check_config_option(bin: &Path, toml_path: &Path) -> eyre::Result<bool> {
+    let mut process = Exec::cmd(bin)
+        .arg("--check-config")
+        .arg("--config")
+        .arg(toml_path)
+        .stdout(Redirection::Pipe)
+        .stderr(Redirection::Merge)
+        .popen()
+        .wrap_err("Error while running the coordinator with `--check-config`")?;
+
+    // The output is read in a separate thread, so that the
+    // coordinator can't block writing to a full pipe while it is
+    // being waited for.
+    let mut stdout = process
+        .stdout
+        .take()
+        .expect("the coordinator's stdout should be piped");
+    let reader_join = std::thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let exit_status = process.wait_timeout(VALIDATE_CONFIG_LAUNCH_TIMEOUT)?;
+    if exit_status.is_none() {
+        stop_validation_process(&mut process)?;
+    }
+    let output = reader_join
+        .join()
+        .map_err(|_| eyre::eyre!("The coordinator output reader thread panicked"))?
+        .wrap_err("Error while reading the coordinator's output")?;
+
+    if output
+        .lines()
+        .any(|line| UNSUPPORTED_OPTION_RE.is_match(line))
+    {
+        return Ok(false);
+    }
+
+    if let Some(line) = output
+        .lines()
+        .find(|line| CONFIG_PARSE_ERROR_RE.is_match(line))
+    {
+        return Err(ConfigParseError {
+            line: line.to_string(),
+        }
+        .into());
+    }
+
+    let exit_status = match exit_status {
+        Some(exit_status) => exit_status,
+        None => {
+            tracing::debug!(
+                "The coordinator was still running after {:?} with `--check-config`.",
+                VALIDATE_CONFIG_LAUNCH_TIMEOUT
+            );
+            return Ok(false);
+        }
+    };
+
+    default_parse_exit_status(exit_status).wrap_err_with(|| {
+        eyre::eyre!(
+            "The coordinator rejected its configuration: {:?}",
+            output.trim()
+        )
+    })?;
+
+    Ok(true)
+}
+
+/// Launch the coordinator briefly and scan its output for errors
+/// parsing its configuration, see [validate_config()].
+fn check_config_launch(bin: &Path, toml_path: &Path) -> eyre::Result<()> {
+    let mut exec = Exec::cmd(bin)
+        .arg("--config")
+        .arg(toml_path)
+        .stdout(Redirection::Pipe)
+        .stderr(Redirection::Merge);
+    if let Some(dir) = toml_path.parent() {
+        exec = exec.cwd(dir);
+    }
+    let mut process = exec
+        .popen()
+        .wrap_err("Error while launching the coordinator to validate its configuration")?;
+
+    let stdout = process
+        .stdout
+        .take()
+        .expect("the coordinator's stdout should be piped");
+    let (line_tx, line_rx) = mpsc::channel();
+    let reader_join = std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            match line {
+                Ok(line) if line_tx.send(line).is_ok() => {}
+                _ => break,
+            }
+        }
+    });
+
+    let deadline = Instant::now() + VALIDATE_CONFIG_LAUNCH_TIMEOUT;
+    let result = loop {
+        match line_rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(line) => {
+                if CONFIG_PARSE_ERROR_RE.is_match(&line) {
+                    break Err(ConfigParseError { line }.into());
+                }
+                if BOOTED_RE.is_match(&line) {
+                    break Ok(());
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                break default_parse_exit_status(process.wait()?).wrap_err(
+                    "The coordinator exited before booting while validating its configuration",
+                );
+            }
+            // No error was reported within the timeout.
+            Err(mpsc::RecvTimeoutError::Timeout) => break Ok(()),
+        }
+    };
+
+    if process.poll().is_none() {
+        stop_validation_process(&mut process)?;
+    }
+    drop(line_rx);
+    reader_join
+        .join()
+        .map_err(|_| eyre::eyre!("The coordinator output reader thread panicked"))?;
+
+    result
+}
+
+/// Terminate a coordinator `process` started by [validate_config()],
+/// killing it if it doesn't exit within [DEFAULT_TERMINATE_GRACE].
+fn stop_validation_process(process: &mut Popen) -> eyre::Result<()> {
+    process.terminate()?;
+    if process.wait_timeout(DEFAULT_TERMINATE_GRACE)?.is_none() {
+        process.kill()?;
+        process.wait()?;
+    }
+    Ok(())
+}
+
+/// Construct the command used to launch the coordinator, passing
+/// the configuration file at `toml_config_path` as described by
+/// [CoordinatorConfig::launch_mode].
+fn coordinator_exec(config: &CoordinatorConfig, toml_config_path: &Path) -> eyre::Result<Exec> {
+    let toml_config_path = toml_config_path
+        .canonicalize()
+        .wrap_err("cannot canonicalize toml config path")?;
+
+    let setup_coordinator_bin = config.setup_coordinator_bin.canonicalize()?;
+    let exec = if config.resource_limits.is_limited() {
+        Exec::cmd("prlimit")
+            .args(&config.resource_limits.prlimit_args())
+            .arg("--")
+            .arg(setup_coordinator_bin)
+    } else {
+        Exec::cmd(setup_coordinator_bin)
+    };
+
+    let exec = exec
+        .cwd(config.working_dir())
+        .env("RUST_BACKTRACE", "1")
+        .env("RUST_LOG", &config.log_level);
+
+    let exec = match &config.launch_mode {
+        LaunchMode::Arg => exec.arg("--config").arg(toml_config_path),
+        LaunchMode::EnvVar(name) => exec.env(name, toml_config_path),
+        LaunchMode::Stdin => {
+            let toml_config_file =
+                File::open(&toml_config_path).wrap_err("cannot open toml config file for stdin")?;
+            exec.stdin(toml_config_file)
+        }
+    };
+
+    Ok(exec)
+}
+
+/// If the `error` is a [ConfigParseError], add the section of the
+/// configuration file (located at `toml_config_path` with the
+/// contents `toml_config`) that it refers to, so that it is obvious
+/// that the configuration is the problem.
+fn with_config_section(
+    error: eyre::Error,
+    toml_config_path: &Path,
+    toml_config: &str,
+) -> eyre::Error {
+    let section = match error.downcast_ref::<ConfigParseError>() {
+        Some(parse_error) => parse_error.relevant_section(toml_config),
+        None => return error,
+    };
+
+    match section {
+        Some(section) => error.wrap_err(format!(
+            "Invalid coordinator configuration {:?}, relevant section:\n{}",
+            toml_config_path, section
+        )),
+        None => error.wrap_err(format!(
+            "Invalid coordinator configuration {:?}",
+            toml_config_path
+        )),
+    }
+}
+
+/// Serialize the [CoordinatorTomlConfiguration] for the `config`,
+/// and write it to `config.toml` in the [CoordinatorConfig::out_dir].
+/// The [CoordinatorConfig::out_dir] and the directory of the
+/// [CoordinatorConfig::sqlite_file()] which the configuration refers
+/// to are created if they do not exist yet. Returns the path of the
+/// file and its contents.
+fn write_toml_config(config: &CoordinatorConfig) -> eyre::Result<(PathBuf, String)> {
+    // The directory of the SQLite file is canonicalized when the
+    // configuration is serialized, so it needs to exist beforehand.
+    create_dir_if_not_exists(&config.out_dir)?;
+    if let Some(sqlite_dir) = config.sqlite_file().parent() {
+        create_dir_if_not_exists(sqlite_dir)?;
+    }
+
+    let toml_config = CoordinatorTomlConfiguration::try_from(config)?;
+    let toml_config_str = toml::to_string_pretty(&toml_config)
+        .wrap_err("Error while serializing coordinator toml config")?;
+    let toml_config_path = config.out_dir.join("config.toml");
+    std::fs::write(&toml_config_path, &toml_config_str)
+        .wrap_err("Error while writing corodinator config.toml file")?;
+    Ok((toml_config_path, toml_config_str))
+}
+
+/// The result of [launch_coordinator()].
+#[derive(Debug)]
+pub enum CoordinatorLaunch {
+    /// The coordinator process was started, see [run_coordinator()].
+    Started(CoordinatorJoin),
+    /// [CoordinatorConfig::dry_run] is set, so only the
+    /// configuration file was written, and no process was started.
+    DryRun {
+        /// The path of the `config.toml` which was written.
+        toml_config_path: PathBuf,
+    },
+}
+
+/// Run the `aleo-setup-coordinator` rocket server with
+/// [run_coordinator()], or if [CoordinatorConfig::dry_run] is set,
+/// only write its configuration file.
+pub fn launch_coordinator(
+    config: &CoordinatorConfig,
+    ceremony_tx: Sender<CeremonyMessage>,
+    ceremony_rx: Receiver<CeremonyMessage>,
+) -> eyre::Result<CoordinatorLaunch> {
+    if !config.dry_run {
+        return run_coordinator(config, ceremony_tx, ceremony_rx).map(CoordinatorLaunch::Started);
+    }
+
+    let span = tracing::error_span!("coordinator");
+    let _guard = span.enter();
+
+    config.validate_num_powers()?;
+    config.validate_log_level()?;
+
+    let (toml_config_path, _) = write_toml_config(config)?;
+    tracing::info!(
+        "Dry run, wrote {:?} without starting setup coordinator.",
+        toml_config_path
+    );
+
+    Ok(CoordinatorLaunch::DryRun { toml_config_path })
+}
+
+/// Run the `aleo-setup-coordinator` rocket server. Returns an error
+/// if [CoordinatorConfig::dry_run] is set, see
+/// [launch_coordinator()].
+pub fn run_coordinator(
+    config: &CoordinatorConfig,
+    ceremony_tx: Sender<CeremonyMessage>,
+    ceremony_rx: Receiver<CeremonyMessage>,
+) -> eyre::Result<CoordinatorJoin> {
+    let span = tracing::error_span!("coordinator");
+    let _guard = span.enter();
+
+    if config.dry_run {
+        return Err(eyre::eyre!(
+            "Unable to run the coordinator with `CoordinatorConfig::dry_run` set, \
+                use `launch_coordinator()` instead"
+        ));
+    }
+
+    config.validate_coordinator_bin()?;
+    config.validate_num_powers()?;
+    config.validate_log_level()?;
+
+    if config.startup_retries > 0 && matches!(config.launch_mode, LaunchMode::Stdin) {
+        // The configuration file piped to `stdin` is consumed by the
+        // first launch.
+        return Err(eyre::eyre!(
+            "Coordinator startup retries are not supported with `LaunchMode::Stdin`"
+        ));
+    }
+
+    let state_history = CoordinatorStateHistory::default();
+
+    if config.check_version {
+        state_history.set_version(check_coordinator_version(config)?);
+    }
+
+    let (toml_config_path, toml_config_str) = write_toml_config(config)?;
+
+    tracing::info!("Starting setup coordinator.");
+
+    let exec_config = config.clone();
+    let exec_toml_config_path = toml_config_path.clone();
+    let exec = move || coordinator_exec(&exec_config, &exec_toml_config_path);
+
+    let log_options = CoordinatorLogOptions {
+        log_file_path: config.log_file(),
+        stderr_log_file_path: config.stderr_log_file(),
+        max_log_size: config.max_log_size,
+        json_log_path: if config.json_log {
+            Some(config.out_dir.join("coordinator.jsonl"))
+        } else {
+            None
+        },
+        timestamps: config.log_timestamps,
+    };
+    let reporter_options = ReporterOptions {
+        max_rounds: config.max_rounds,
+        shutdown_on_rollback: config.shutdown_on_rollback,
+        startup_retries: config.startup_retries,
+        log_pattern_overrides: config.log_pattern_overrides.clone(),
+        round_archive: if config.per_round_out_dir {
+            Some(config.clone())
+        } else {
+            None
+        },
+    };
+    let broadcast_retry = config.broadcast_retry;
+    let monitor_state_history = state_history.clone();
+
+    let stall_monitor_join = config.stall_timeout.map(|stall_timeout| {
+        run_stall_monitor(
+            stall_timeout,
+            state_history.clone(),
+            ceremony_tx.clone(),
+            ceremony_rx.clone(),
+            broadcast_retry,
+        )
+    });
+
+    let round_state_watcher_join = config.round_state_poll_interval.map(|poll_interval| {
+        run_round_state_watcher(
+            config.clone(),
+            poll_interval,
+            ceremony_tx.clone(),
+            ceremony_rx.clone(),
+        )
+    });
+
+    let heartbeat_join = config.heartbeat_interval.map(|heartbeat_interval| {
+        run_heartbeat(
+            heartbeat_interval,
+            state_history.clone(),
+            ceremony_tx.clone(),
+            ceremony_rx.clone(),
+            broadcast_retry,
+        )
+    });
+
+    let (process_join, _) = run_monitor_process(
+        "coordinator".to_string(),
+        exec,
+        if config.resource_limits.is_limited() {
+            parse_limited_exit_status
+        } else {
+            default_parse_exit_status
+        },
+        MonitorProcessOptions {
+            eof_grace: config.eof_grace,
+            terminate_grace: config.terminate_grace,
+            startup_retries: config.startup_retries,
+            startup_backoff: config.startup_backoff,
+            separate_stderr: true,
+            exit_message: Some(coordinator_exited_message),
+            exit_error_reason: ShutdownReason::CoordinatorDied,
+        },
+        ceremony_tx,
+        ceremony_rx,
+        fallible_monitor(move |output, ceremony_tx, monitor_tx| {
+            monitor_coordinator(
+                output,
+                ceremony_tx,
+                Some(monitor_tx),
+                log_options.clone(),
+                reporter_options.clone(),
+                monitor_state_history.clone(),
+                broadcast_retry,
+            )
+            .map_err(|error| with_config_section(error, &toml_config_path, &toml_config_str))
+        }),
+    )?;
+
+    Ok(CoordinatorJoin {
+        process_join,
+        stall_monitor_join,
+        round_state_watcher_join,
+        heartbeat_join,
+        state_history,
+    })
+}
+
+/// How often the thread started by [run_stall_monitor()] checks for
+/// state transitions.
+const STALL_MONITOR_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Run a thread which broadcasts a [CeremonyMessage::Shutdown] with
+/// [ShutdownReason::Error] if no state transition is recorded in the
+/// `state_history` within `stall_timeout`, see
+/// [CoordinatorConfig::stall_timeout]. The timeout only starts once
+/// the coordinator has booted ([CeremonyMessage::CoordinatorReady]),
+/// so that the time it takes to boot doesn't count as a stall. The
+/// thread closes when a [CeremonyMessage::Shutdown] is received.
+fn run_stall_monitor(
+    stall_timeout: Duration,
+    state_history: CoordinatorStateHistory,
+    ceremony_tx: Sender<CeremonyMessage>,
+    mut ceremony_rx: Receiver<CeremonyMessage>,
+    broadcast_retry: BroadcastRetryOptions,
+) -> JoinHandle<eyre::Result<()>> {
+    let span = tracing::error_span!("stall_monitor", timeout = %format_duration(stall_timeout));
+
+    std::thread::spawn(move || {
+        let _guard = span.enter();
+        let mut ready = false;
+        let mut transitions = state_history.states().len();
+        let mut last_transition = Instant::now();
+
+        loop {
+            std::thread::sleep(STALL_MONITOR_POLL_INTERVAL);
+
+            loop {
+                match ceremony_rx.try_recv() {
+                    Ok(CeremonyMessage::Shutdown(_)) => {
+                        tracing::debug!("Thread closing gracefully.");
+                        return Ok(());
+                    }
+                    Ok(CeremonyMessage::CoordinatorReady) if !ready => {
+                        ready = true;
+                        transitions = state_history.states().len();
+                        last_transition = Instant::now();
+                    }
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        return Err(eyre::eyre!("`ceremony_rx` disconnected"));
+                    }
+                }
+            }
+
+            if !ready {
+                continue;
+            }
+
+            let current_transitions = state_history.states().len();
+            if current_transitions != transitions || state_history.is_paused() {
+                transitions = current_transitions;
+                last_transition = Instant::now();
+                continue;
+            }
+
+            if last_transition.elapsed() > stall_timeout {
+                tracing::error!(
+                    "The coordinator's state has not changed from {:?} within {}, \
+                        telling ceremony to shutdown.",
+                    state_history.current_state(),
+                    format_duration(stall_timeout)
+                );
+                broadcast_with_retry(
+                    &ceremony_tx,
+                    CeremonyMessage::Shutdown(ShutdownReason::Error),
+                    &broadcast_retry,
+                )?;
+                return Err(eyre::eyre!(
+                    "The coordinator stalled in state {:?} for more than {}.",
+                    state_history.current_state(),
+                    format_duration(stall_timeout)
+                ));
+            }
+        }
+    })
+}
+
+/// Run a thread which broadcasts a [CeremonyMessage::Heartbeat] with
+/// the current state recorded in the `state_history` every
+/// `heartbeat_interval`, see [CoordinatorConfig::heartbeat_interval].
+/// The thread closes when a [CeremonyMessage::Shutdown] is received.
+fn run_heartbeat(
+    heartbeat_interval: Duration,
+    state_history: CoordinatorStateHistory,
+    ceremony_tx: Sender<CeremonyMessage>,
+    mut ceremony_rx: Receiver<CeremonyMessage>,
+    broadcast_retry: BroadcastRetryOptions,
+) -> JoinHandle<eyre::Result<()>> {
+    let span = tracing::error_span!("heartbeat", interval = %format_duration(heartbeat_interval));
+    let poll_interval = heartbeat_interval.min(STALL_MONITOR_POLL_INTERVAL);
+
+    std::thread::spawn(move || {
+        let _guard = span.enter();
+        let mut last_heartbeat = Instant::now();
+
+        loop {
+            std::thread::sleep(poll_interval);
+
+            loop {
+                match ceremony_rx.try_recv() {
+                    Ok(CeremonyMessage::Shutdown(_)) => {
+                        tracing::debug!("Thread closing gracefully.");
+                        return Ok(());
+                    }
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        return Err(eyre::eyre!("`ceremony_rx` disconnected"));
+                    }
+                }
+            }
+
+            if last_heartbeat.elapsed() < heartbeat_interval {
+                continue;
+            }
+
+            let state = state_history
+                .current_state()
+                .unwrap_or(PublicCoordinatorState::ProcessStarted);
+            broadcast_with_retry(
+                &ceremony_tx,
+                CeremonyMessage::Heartbeat {
+                    round: state.round(),
+                    state_name: state.name().to_string(),
+                },
+                &broadcast_retry,
+            )?;
+            last_heartbeat = Instant::now();
+        }
+    })
+}
+
+/// Run a thread which polls for the `state.json` file of each round
+/// (see [CoordinatorConfig::round_state_file()]) every
+/// `poll_interval`, starting with round 1, and broadcasts a
+/// [CeremonyMessage::RoundStateWritten] once it exists and is not
+/// empty (with [CoordinatorConfig::broadcast_retry]). Each round is
+/// only reported once. The thread closes when a
+/// [CeremonyMessage::Shutdown] is received.
+fn run_round_state_watcher(
+    config: CoordinatorConfig,
+    poll_interval: Duration,
+    ceremony_tx: Sender<CeremonyMessage>,
+    mut ceremony_rx: Receiver<CeremonyMessage>,
+) -> JoinHandle<eyre::Result<()>> {
+    let span = tracing::error_span!("round_state_watcher");
+
+    std::thread::spawn(move || {
+        let _guard = span.enter();
+        let mut next_round: u64 = 1;
+
+        loop {
+            loop {
+                match ceremony_rx.try_recv() {
+                    Ok(CeremonyMessage::Shutdown(_)) => {
+                        tracing::debug!("Thread closing gracefully.");
+                        return Ok(());
+                    }
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        return Err(eyre::eyre!("`ceremony_rx` disconnected"));
+                    }
+                }
+            }
+
+            while std::fs::metadata(config.round_state_file(next_round))
+                .map(|metadata| metadata.len() > 0)
+                .unwrap_or(false)
+            {
+                tracing::debug!("Detected the state file for round {}.", next_round);
+                broadcast_with_retry(
+                    &ceremony_tx,
+                    CeremonyMessage::RoundStateWritten(next_round),
+                    &config.broadcast_retry,
+                )?;
+                next_round += 1;
+            }
+
+            std::thread::sleep(poll_interval);
+        }
+    })
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+enum CoordinatorState {
+    /// The process has just started.
+    ProcessStarted,
+    /// The coordinator is ready and the specified round is waiting
+    /// for participants before it can start.
+    RoundWaitingForParticipants(u64),
+    /// The specified round has started and is running.
+    RoundRunning(u64),
+    /// The round has completed contributions and verifications, and
+    /// the coordinator is aggregating chunks.
+    RoundAggregating(u64),
+    /// The round has completed aggregation and is now waiting for the
+    /// final report.
+    RoundWaitingForFinish(u64),
+    /// The round has finished. Waiting to confirm that the next round
+    /// is awaiting participants.
+    RoundFinished(u64),
+    /// The round was rolled back to round 0 because no contributors
+    /// remained to complete it. Waiting to confirm that round 1 is
+    /// awaiting participants again.
+    RoundRolledBack(u64),
+    /// The last expected round (see [CoordinatorConfig::max_rounds])
+    /// has finished, and the test has been told to shut down.
+    LastRoundFinished(u64),
+}
+
+/// The states that the coordinator passes through during the
+/// ceremony, as tracked by the coordinator's log monitor. See
+/// [CoordinatorStateHistory].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub enum PublicCoordinatorState {
+    /// The process has just started.
+    ProcessStarted,
+    /// The coordinator is ready and the specified round is waiting
+    /// for participants before it can start.
+    RoundWaitingForParticipants(u64),
+    /// The specified round has started and is running.
+    RoundRunning(u64),
+    /// The round has completed contributions and verifications, and
+    /// the coordinator is aggregating chunks.
+    RoundAggregating(u64),
+    /// The round has completed aggregation and is now waiting for the
+    /// final report.
+    RoundWaitingForFinish(u64),
+    /// The round has finished.
+    RoundFinished(u64),
+    /// The round was rolled back to round 0 because no contributors
+    /// remained to complete it.
+    RoundRolledBack(u64),
+    /// The last expected round (see [CoordinatorConfig::max_rounds])
+    /// has finished.
+    LastRoundFinished(u64),
+}
+
+impl PublicCoordinatorState {
+    /// The round which this state is for, `None` before the
+    /// coordinator has booted.
+    pub fn round(&self) -> Option<u64> {
+        match *self {
+            Self::ProcessStarted => None,
+            Self::RoundWaitingForParticipants(round)
+            | Self::RoundRunning(round)
+            | Self::RoundAggregating(round)
+            | Self::RoundWaitingForFinish(round)
+            | Self::RoundFinished(round)
+            | Self::RoundRolledBack(round)
+            | Self::LastRoundFinished(round) => Some(round),
+        }
+    }
+
+    /// The name of this state's variant, e.g. `RoundRunning`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ProcessStarted => "ProcessStarted",
+            Self::RoundWaitingForParticipants(_) => "RoundWaitingForParticipants",
+            Self::RoundRunning(_) => "RoundRunning",
+            Self::RoundAggregating(_) => "RoundAggregating",
+            Self::RoundWaitingForFinish(_) => "RoundWaitingForFinish",
+            Self::RoundFinished(_) => "RoundFinished",
+            Self::RoundRolledBack(_) => "RoundRolledBack",
+            Self::LastRoundFinished(_) => "LastRoundFinished",
+        }
+    }
+}
+
+impl std::fmt::Display for PublicCoordinatorState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ProcessStarted => f.write_str("process started"),
+            Self::RoundWaitingForParticipants(round) => {
+                write!(f, "round {} waiting for participants", round)
+            }
+            Self::RoundRunning(round) => write!(f, "round {} running", round),
+            Self::RoundAggregating(round) => write!(f, "round {} aggregating", round),
+            Self::RoundWaitingForFinish(round) => write!(f, "round {} waiting for finish", round),
+            Self::RoundFinished(round) => write!(f, "round {} finished", round),
+            Self::RoundRolledBack(round) => write!(f, "round {} rolled back", round),
+            Self::LastRoundFinished(round) => write!(f, "last round {} finished", round),
+        }
+    }
+}
+
+impl std::fmt::Display for CoordinatorState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        PublicCoordinatorState::from(self).fmt(f)
+    }
+}
+
+impl CoordinatorState {
+    /// The round which this state is for, `None` before the
+    /// coordinator has booted.
+    fn round(&self) -> Option<u64> {
+        match *self {
+            CoordinatorState::ProcessStarted => None,
+            CoordinatorState::RoundWaitingForParticipants(round)
+            | CoordinatorState::RoundRunning(round)
+            | CoordinatorState::RoundAggregating(round)
+            | CoordinatorState::RoundWaitingForFinish(round)
+            | CoordinatorState::RoundFinished(round)
+            | CoordinatorState::RoundRolledBack(round)
+            | CoordinatorState::LastRoundFinished(round) => Some(round),
+        }
+    }
+}
+
+impl From<&CoordinatorState> for PublicCoordinatorState {
+    fn from(state: &CoordinatorState) -> Self {
+        match *state {
+            CoordinatorState::ProcessStarted => Self::ProcessStarted,
+            CoordinatorState::RoundWaitingForParticipants(round) => {
+                Self::RoundWaitingForParticipants(round)
+            }
+            CoordinatorState::RoundRunning(round) => Self::RoundRunning(round),
+            CoordinatorState::RoundAggregating(round) => Self::RoundAggregating(round),
+            CoordinatorState::RoundWaitingForFinish(round) => Self::RoundWaitingForFinish(round),
+            CoordinatorState::RoundFinished(round) => Self::RoundFinished(round),
+            CoordinatorState::RoundRolledBack(round) => Self::RoundRolledBack(round),
+            CoordinatorState::LastRoundFinished(round) => Self::LastRoundFinished(round),
+        }
+    }
+}
+
+/// Progress of the currently running round, see
+/// [CoordinatorStateHistory::round_progress()].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RoundProgress {
+    /// The round which is running.
+    pub round: u64,
+    /// Number of successful contributions observed so far.
+    pub contributions: usize,
+    /// Number of verifications observed so far.
+    pub verifications: usize,
+    /// The number of contributions expected in the round (the number
+    /// of chunks multiplied by the number of contributors), if
+    /// known. Each contribution is also expected to be verified.
+    pub expected_contributions: Option<usize>,
+}
+
+impl RoundProgress {
+    /// The percentage (from `0.0` to `100.0`) of the expected
+    /// contributions and verifications which have been observed, or
+    /// `0.0` if the expected number is not known.
+    pub fn percent_complete(&self) -> f64 {
+        match self.expected_contributions {
+            Some(expected) if expected > 0 => {
+                let observed = (self.contributions + self.verifications) as f64;
+                (100.0 * observed / (2 * expected) as f64).min(100.0)
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// A shared record of the states (see [PublicCoordinatorState]) that
+/// the coordinator has passed through, in order, and the
+/// [RoundProgress] of the current round, updated by the coordinator's
+/// log monitor while it is running.
+#[derive(Debug, Clone, Default)]
+pub struct CoordinatorStateHistory {
+    states: Arc<Mutex<Vec<PublicCoordinatorState>>>,
+    progress: Arc<Mutex<RoundProgress>>,
+    version: Arc<Mutex<Option<CoordinatorVersion>>>,
+    paused: Arc<Mutex<bool>>,
+    chunk_contributions: Arc<Mutex<HashMap<u64, usize>>>,
+    round_states: Arc<Mutex<BTreeMap<u64, PublicCoordinatorState>>>,
+}
+
+impl CoordinatorStateHistory {
+    fn record_chunk_contribution(&self, chunk: u64) {
+        *self
+            .chunk_contributions
+            .lock()
+            .expect("error obtaining lock")
+            .entry(chunk)
+            .or_insert(0) += 1;
+    }
+
+    fn reset_chunk_contributions(&self) {
+        self.chunk_contributions
+            .lock()
+            .expect("error obtaining lock")
+            .clear();
+    }
+
+    /// The number of successful contributions made to each chunk
+    /// (keyed by the chunk id) in the current round. Reset when the
+    /// next round starts waiting for participants, so this can still
+    /// be checked after the round has finished.
+    pub fn chunk_contributions(&self) -> HashMap<u64, usize> {
+        self.chunk_contributions
+            .lock()
+            .expect("error obtaining lock")
+            .clone()
+    }
+
+    fn set_paused(&self, paused: bool) {
+        *self.paused.lock().expect("error obtaining lock") = paused;
+    }
+
+    /// Whether the coordinator is currently paused for maintenance,
+    /// see [CeremonyMessage::CoordinatorPaused].
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().expect("error obtaining lock")
+    }
+
+    fn set_version(&self, version: CoordinatorVersion) {
+        *self.version.lock().expect("error obtaining lock") = Some(version);
+    }
+
+    /// The version of the coordinator, if it was reported by the
+    /// coordinator's log or checked with
+    /// [check_coordinator_version()].
+    pub fn coordinator_version(&self) -> Option<CoordinatorVersion> {
+        *self.version.lock().expect("error obtaining lock")
+    }
+
+    fn push(&self, state: PublicCoordinatorState) {
+        self.states
+            .lock()
+            .expect("error obtaining lock")
+            .push(state);
+    }
+
+    fn update_progress(&self, update: impl FnOnce(&mut RoundProgress)) {
+        update(&mut self.progress.lock().expect("error obtaining lock"));
+    }
+
+    /// Reset the progress when the `round` starts running, keeping
+    /// the expected number of contributions if it was already set
+    /// for this round.
+    fn start_round_progress(&self, round: u64) {
+        self.update_progress(|progress| {
+            let expected_contributions = if progress.round == round {
+                progress.expected_contributions
+            } else {
+                None
+            };
+
+            *progress = RoundProgress {
+                round,
+                expected_contributions,
+                ..RoundProgress::default()
+            };
+        });
+    }
+
+    /// Set the number of contributions expected in `round`, used to
+    /// calculate the [CoordinatorStateHistory::round_progress()].
+    pub fn set_expected_contributions(&self, round: u64, expected_contributions: usize) {
+        self.update_progress(|progress| {
+            if progress.round != round {
+                *progress = RoundProgress {
+                    round,
+                    ..RoundProgress::default()
+                };
+            }
+            progress.expected_contributions = Some(expected_contributions);
+        });
+    }
+
+    /// The progress of the current round.
+    pub fn progress(&self) -> RoundProgress {
+        *self.progress.lock().expect("error obtaining lock")
+    }
+
+    /// The percentage (from `0.0` to `100.0`) of the current round
+    /// which is complete, see [RoundProgress::percent_complete()].
+    pub fn round_progress(&self) -> f64 {
+        self.progress().percent_complete()
+    }
+
+    /// The states that the coordinator has passed through so far.
+    pub fn states(&self) -> Vec<PublicCoordinatorState> {
+        self.states.lock().expect("error obtaining lock").clone()
+    }
+
+    /// The state that the coordinator is currently in.
+    pub fn current_state(&self) -> Option<PublicCoordinatorState> {
+        self.states
+            .lock()
+            .expect("error obtaining lock")
+            .last()
+            .copied()
+    }
+
+    /// A snapshot of the state of each round which is currently in
+    /// progress, keyed by the round number. More than one round may
+    /// be in progress when consecutive rounds overlap, e.g. when a
+    /// round starts while the previous round is still aggregating.
+    /// Empty until the coordinator has booted.
+    pub fn round_states(&self) -> BTreeMap<u64, PublicCoordinatorState> {
+        self.round_states
+            .lock()
+            .expect("error obtaining lock")
+            .clone()
+    }
+
+    fn set_round_states(&self, rounds: &BTreeMap<u64, CoordinatorState>) {
+        *self.round_states.lock().expect("error obtaining lock") = rounds
+            .iter()
+            .map(|(round, state)| (*round, PublicCoordinatorState::from(state)))
+            .collect();
+    }
+
+    /// Returns an error unless the coordinator passed through exactly
+    /// the `expected` states, in order, with no skipped or extra
+    /// transitions.
+    pub fn assert_state_sequence(&self, expected: &[PublicCoordinatorState]) -> eyre::Result<()> {
+        let states = self.states();
+
+        if let Some((index, (actual, expected))) = states
+            .iter()
+            .zip(expected.iter())
+            .enumerate()
+            .find(|(_, (actual, expected))| actual != expected)
+        {
+            return Err(eyre::eyre!(
+                "Coordinator state transition {} was {:?}, expected {:?}. All states: {:?}",
+                index,
+                actual,
+                expected,
+                states
+            ));
+        }
+
+        if states.len() != expected.len() {
+            return Err(eyre::eyre!(
+                "Coordinator passed through {} states, expected {}. All states: {:?}",
+                states.len(),
+                expected.len(),
+                states
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Allows the threads created by [run_coordinator()] to be joined,
+/// and provides access to the [CoordinatorStateHistory] while the
+/// coordinator is running.
+#[derive(Debug)]
+pub struct CoordinatorJoin {
+    process_join: MonitorProcessJoin,
+    /// See [run_stall_monitor()].
+    stall_monitor_join: Option<JoinHandle<eyre::Result<()>>>,
+    /// See [run_round_state_watcher()].
+    round_state_watcher_join: Option<JoinHandle<eyre::Result<()>>>,
+    /// See [run_heartbeat()].
+    heartbeat_join: Option<JoinHandle<eyre::Result<()>>>,
+    state_history: CoordinatorStateHistory,
+}
+
+impl CoordinatorJoin {
+    /// See [MonitorProcessJoin::pid()].
+    pub fn pid(&self) -> Option<u32> {
+        self.process_join.pid()
+    }
+
+    /// The history of the states the coordinator has passed through.
+    pub fn state_history(&self) -> &CoordinatorStateHistory {
+        &self.state_history
+    }
+
+    /// See [MonitorProcessJoin::terminate()].
+    pub fn terminate(&self) -> eyre::Result<()> {
+        self.process_join.terminate()
+    }
+
+    /// See [CoordinatorStateHistory::current_state()].
+    pub fn current_state(&self) -> Option<PublicCoordinatorState> {
+        self.state_history.current_state()
+    }
+
+    /// Joins the threads created by [run_coordinator()].
+    pub fn join(self) -> std::thread::Result<()> {
+        let result = self.process_join.join();
+
+        if let Some(stall_monitor_join) = self.stall_monitor_join {
+            if let Err(error) = stall_monitor_join.join()? {
+                tracing::error!("Error in coordinator stall monitor: {:?}", error);
+            }
+        }
+
+        if let Some(round_state_watcher_join) = self.round_state_watcher_join {
+            if let Err(error) = round_state_watcher_join.join()? {
+                tracing::error!("Error in coordinator round state watcher: {:?}", error);
+            }
+        }
+
+        if let Some(heartbeat_join) = self.heartbeat_join {
+            if let Err(error) = heartbeat_join.join()? {
+                tracing::error!("Error in coordinator heartbeat: {:?}", error);
+            }
+        }
+
+        result
+    }
+}
+
+impl MultiJoinable for CoordinatorJoin {
+    fn join(self: Box<Self>) -> std::thread::Result<()> {
+        CoordinatorJoin::join(*self)
+    }
+}
+
+/// A panic which occurred in the coordinator, along with its
+/// backtrace (if one was printed), collected from the many lines of
+/// output that the panic produces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoordinatorBacktrace {
+    /// The panic message.
+    pub message: String,
+    /// The location in the source code where the panic occurred.
+    pub location: String,
+    /// The frames of the backtrace, each including its `at` source
+    /// location line (if present).
+    pub frames: Vec<String>,
+}
+
+/// Collects the lines of the coordinator's output belonging to a
+/// panic into a [CoordinatorBacktrace].
+#[derive(Debug, Default)]
+struct BacktraceCollector {
+    /// The backtrace currently being collected.
+    current: Option<CoordinatorBacktrace>,
+    /// Whether the `stack backtrace:` line has been seen for the
+    /// current backtrace.
+    in_stack: bool,
+    /// Whether the next line is the message of the current panic.
+    /// Newer versions of Rust print the location of the panic first,
+    /// followed by the message on its own line.
+    awaiting_message: bool,
+}
+
+impl BacktraceCollector {
+    /// Process a line of output, returning a completed backtrace if
+    /// this line marks the end of one.
+    fn push_line(&mut self, line: &str) -> Option<CoordinatorBacktrace> {
+        if let Some(captures) = PANIC_RE.captures(line) {
+            let completed = self.finish();
+            self.current = Some(CoordinatorBacktrace {
+                message: captures["message"].to_string(),
+                location: captures["location"].to_string(),
+                frames: Vec::new(),
+            });
+            return completed;
+        }
+
+        if let Some(captures) = PANIC_LOCATION_FIRST_RE.captures(line) {
+            let completed = self.finish();
+            self.current = Some(CoordinatorBacktrace {
+                message: String::new(),
+                location: captures["location"].to_string(),
+                frames: Vec::new(),
+            });
+            self.awaiting_message = true;
+            return completed;
+        }
+
+        let current = self.current.as_mut()?;
+
+        if self.awaiting_message {
+            self.awaiting_message = false;
+            current.message = line.trim().to_string();
+            return None;
+        }
+
+        if BACKTRACE_START_RE.is_match(line) {
+            self.in_stack = true;
+            None
+        } else if BACKTRACE_NOTE_RE.is_match(line) {
+            self.finish()
+        } else if self.in_stack && BACKTRACE_FRAME_RE.is_match(line) {
+            current.frames.push(line.trim().to_string());
+            None
+        } else if self.in_stack && BACKTRACE_FRAME_LOCATION_RE.is_match(line) {
+            match current.frames.last_mut() {
+                Some(frame) => {
+                    frame.push('\n');
+                    frame.push_str(line.trim());
+                }
+                None => current.frames.push(line.trim().to_string()),
+            }
+            None
+        } else {
+            // Any other line marks the end of the backtrace.
+            self.finish()
+        }
+    }
+
+    /// Complete the backtrace currently being collected (if any), for
+    /// example when the end of the output has been reached.
+    fn finish(&mut self) -> Option<CoordinatorBacktrace> {
+        self.in_stack = false;
+        self.awaiting_message = false;
+        self.current.take()
+    }
+}
+
+/// The source of the wall-clock time used by the
+/// [CoordinatorStateReporter] to measure how long each round spends in
+/// each phase, replaced in tests.
+type Clock = Arc<dyn Fn() -> Instant + Send + Sync>;
+
+/// The times at which a round entered each of its phases, see
+/// [CeremonyMessage::RoundTimings].
+#[derive(Debug, Default, Clone, Copy)]
+struct RoundPhaseStarts {
+    waiting: Option<Instant>,
+    running: Option<Instant>,
+    aggregating: Option<Instant>,
+    finishing: Option<Instant>,
+}
+
+/// This struct keeps track of the current state of the coordinator.
+struct CoordinatorStateReporter {
+    ceremony_tx: Sender<CeremonyMessage>,
+    /// The state of each round which is in progress, keyed by the
+    /// round number. Empty until the coordinator has booted.
+    rounds: BTreeMap<u64, CoordinatorState>,
+    /// The rounds which have finished or been rolled back, and are no
+    /// longer tracked in [CoordinatorStateReporter::rounds].
+    completed_rounds: HashSet<u64>,
+    /// See [CoordinatorConfig::max_rounds].
+    max_rounds: Option<u64>,
+    /// See [CoordinatorConfig::shutdown_on_rollback].
+    shutdown_on_rollback: bool,
+    /// See [CoordinatorConfig::startup_retries].
+    startup_retries: u32,
+    /// Verifiers which have disconnected from the coordinator, and
+    /// not yet reconnected or been dropped.
+    disconnected_verifiers: HashSet<VerifierRef>,
+    /// The chunks that each contributor has contributed to during
+    /// the current round, to detect duplicate contributions (see
+    /// [CeremonyMessage::DuplicateContribution]).
+    round_contributions: HashSet<(ContributorRef, u64)>,
+    /// The `seq` of the next [CeremonyMessage::SuccessfulContribution]
+    /// to be broadcast during the current round.
+    round_contribution_seq: u64,
+    /// The contributors which have been dropped during the current
+    /// round, and whose slots have not yet been taken over by a
+    /// replacement contributor, oldest first.
+    pending_replacements: VecDeque<ContributorRef>,
+    /// When each round which is in progress entered each of its
+    /// phases, keyed by the round number.
+    round_phase_starts: HashMap<u64, RoundPhaseStarts>,
+    clock: Clock,
+    /// See [ReporterOptions::round_archive].
+    round_archive: Option<CoordinatorConfig>,
+    /// Whether the coordinator is currently paused for maintenance
+    /// (see [CeremonyMessage::CoordinatorPaused]).
+    paused: bool,
+    /// Collects any panic backtrace printed by the coordinator.
+    backtrace_collector: BacktraceCollector,
+    /// Messages broadcast since the last call to
+    /// [CoordinatorStateReporter::take_broadcast_messages()].
+    broadcast_messages: Vec<CeremonyMessage>,
+    /// Record of the states that this reporter has transitioned
+    /// through.
+    state_history: CoordinatorStateHistory,
+    /// See [CoordinatorConfig::broadcast_retry].
+    broadcast_retry: BroadcastRetryOptions,
+    /// The patterns used to parse the coordinator's output, selected
+    /// from the version it reports (if any), with the
+    /// `log_pattern_overrides` applied.
+    log_patterns: LogPatterns,
+    /// See [CoordinatorConfig::log_pattern_overrides].
+    log_pattern_overrides: LogPatternOverrides,
+}
+
+lazy_static::lazy_static! {
+    static ref BOOTED_RE: Regex = Regex::new(".*Coordinator has booted up.*").unwrap();
+    static ref ROUND_STARTED_RE: Regex = Regex::new(".*Advanced ceremony to round (?P<round>[0-9]+).*").unwrap();
+    static ref ROUND_STARTED_AGGREGATION_RE: Regex = Regex::new(".*Starting aggregation on round (?P<round>[0-9]+)").unwrap();
+    static ref AGGREGATION_PROGRESS_RE: Regex = Regex::new(".*Aggregated chunk (?P<done>[0-9]+) of (?P<total>[0-9]+)").unwrap();
+    static ref ROUND_AGGREGATED_RE: Regex = Regex::new(".*Round (?P<round>[0-9]+) is aggregated.*").unwrap();
+    static ref ROUND_FINISHED_RE: Regex = Regex::new(".*Round (?P<round>[0-9]+) is finished.*").unwrap();
+    static ref DROPPED_PARTICIPANT_RE: Regex = Regex::new(".*Dropping (?P<address>aleo[a-z0-9]+)[.](?P<participant_type>contributor|verifier) from the ceremony").unwrap();
+    static ref REPLACEMENT_ACTIVATED_RE: Regex = Regex::new("(?i).*(adding|assigning|activating) replacement contributor (?P<replacement>aleo[a-z0-9]+)[.]contributor( (to|for|in place of|replacing) (?P<dropped>aleo[a-z0-9]+)[.]contributor)?").unwrap();
+    static ref SUCCESSFUL_CONTRIBUTION_RE: Regex = Regex::new(".*((?P<address>aleo[a-z0-9]+)[.]contributor) added a contribution to chunk (?P<chunk>[0-9]+)").unwrap();
+    static ref SUCCESSFUL_CONTRIBUTION_V0_2_RE: Regex = Regex::new(".*Contributor (?P<address>aleo[a-z0-9]+)[.]contributor contributed to chunk (?P<chunk>[0-9]+)").unwrap();
+    static ref COORDINATOR_VERSION_LINE_RE: Regex = Regex::new("(?i)aleo-setup-coordinator v?(?P<version>[0-9]+[.][0-9]+[.][0-9]+)").unwrap();
+    static ref VERIFIED_CONTRIBUTION_RE: Regex = Regex::new(".*(?P<address>aleo[a-z0-9]+)[.]verifier verified chunk (?P<chunk>[0-9]+)").unwrap();
+    static ref SUCCESSFUL_VERIFICATION_RE: Regex = Regex::new(".*(?P<address>aleo[a-z0-9]+)[.]verifier verified contribution to chunk (?P<chunk>[0-9]+)").unwrap();
+    static ref ROUND_RESTARTED_NO_CONTRIBUTORS_RE: Regex = Regex::new(".*No contributors remaining to reset and complete the current round. Rolling back to round 0 to wait and accept new participants.*").unwrap();
+    static ref WAITING_FOR_PARTICIPANTS_RE: Regex = Regex::new("(?i).*waiting for participants( (in|for) round (?P<round>[0-9]+))?").unwrap();
+    static ref ROUND_RESTARTED_RE: Regex = Regex::new(".*Resetting current round (?P<round>[0-9]+)( with [0-9]+ contributors remaining)?$").unwrap();
+    static ref PARTICIPANT_JOINED_QUEUE_RE: Regex = Regex::new(".*(?P<address>aleo[a-z0-9]+)[.](?P<participant_type>contributor|verifier) (has )?joined the queue.*").unwrap();
+    static ref VERIFIER_DISCONNECTED_RE: Regex = Regex::new(".*(?P<address>aleo[a-z0-9]+)[.]verifier (has )?disconnected.*").unwrap();
+    static ref VERIFIER_RECONNECTED_RE: Regex = Regex::new(".*(?P<address>aleo[a-z0-9]+)[.]verifier (has )?reconnected.*").unwrap();
+    static ref COORDINATOR_PAUSED_RE: Regex = Regex::new("(?i).*(entering maintenance mode|coordinator (is )?paused).*").unwrap();
+    static ref COORDINATOR_RESUMED_RE: Regex = Regex::new("(?i).*((leaving|exiting) maintenance mode|coordinator (is )?resumed).*").unwrap();
+    static ref PANIC_RE: Regex = Regex::new("thread '(?P<thread>[^']*)' panicked at '(?P<message>.*)', (?P<location>[^ ]+)").unwrap();
+    static ref PANIC_LOCATION_FIRST_RE: Regex = Regex::new("thread '(?P<thread>[^']*)' panicked at (?P<location>[^ ']+):\\s*$").unwrap();
+    static ref BACKTRACE_START_RE: Regex = Regex::new("^stack backtrace:").unwrap();
+    static ref BACKTRACE_FRAME_RE: Regex = Regex::new("^\\s*[0-9]+: ").unwrap();
+    static ref BACKTRACE_FRAME_LOCATION_RE: Regex = Regex::new("^\\s+at ").unwrap();
+    static ref BACKTRACE_NOTE_RE: Regex = Regex::new("^note: .*RUST_BACKTRACE").unwrap();
+    static ref STARTUP_LOCK_RE: Regex = Regex::new("(?i).*(database is locked|database table is locked|unable to (acquire|obtain) .*lock|lock file .*exists).*").unwrap();
+    static ref CONFIG_PARSE_ERROR_RE: Regex = Regex::new("(?i).*(unable to (parse|load|read) config|failed to (parse|load|read) config|error (parsing|loading|reading) config|config(uration)? parse error|toml parse error|unknown field `|missing field `|invalid type: ).*").unwrap();
+    static ref UNSUPPORTED_OPTION_RE: Regex = Regex::new("(?i).*(found argument '[^']*' which wasn't expected|unexpected argument|unrecognized (option|argument)|unknown (option|argument|flag)).*").unwrap();
+    static ref CONFIG_PARSE_FIELD_RE: Regex = Regex::new("field `(?P<field>[A-Za-z0-9_]+)`").unwrap();
+    static ref CONFIG_PARSE_LINE_RE: Regex = Regex::new("(?i)line (?P<line>[0-9]+)").unwrap();
+}
+
+/// The coordinator rejected its configuration file (see
+/// [CoordinatorTomlConfiguration]) at startup, e.g. because it is
+/// malformed or contains a field which the coordinator binary does
+/// not recognize.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigParseError {
+    /// The line of output from the coordinator reporting the error.
+    pub line: String,
+}
+
+impl std::fmt::Display for ConfigParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The coordinator was unable to parse its configuration file: {:?}",
+            self.line
+        )
+    }
+}
+
+impl std::error::Error for ConfigParseError {}
+
+impl ConfigParseError {
+    /// Find the section of the `toml_config` which the error refers
+    /// to, using the field name, or line number, mentioned in the
+    /// error. Returns `None` if the error doesn't refer to a location
+    /// in the config.
+    pub fn relevant_section(&self, toml_config: &str) -> Option<String> {
+        let lines: Vec<&str> = toml_config.lines().collect();
+
+        let error_line_index = if let Some(captures) = CONFIG_PARSE_FIELD_RE.captures(&self.line) {
+            let field = &captures["field"];
+            lines.iter().position(|line| {
+                let line = line.trim();
+                line.starts_with(&format!("{} ", field))
+                    || line.starts_with(&format!("{}=", field))
+                    || (line.starts_with('[')
+                        && line
+                            .trim_matches(|c| c == '[' || c == ']')
+                            .split('.')
+                            .any(|name| name == field))
+            })?
+        } else {
+            let captures = CONFIG_PARSE_LINE_RE.captures(&self.line)?;
+            let line_number: usize = captures["line"].parse().ok()?;
+            if line_number == 0 || line_number > lines.len() {
+                return None;
+            }
+            line_number - 1
+        };
+
+        let is_header = |line: &&str| line.trim_start().starts_with('[');
+        let start = lines[..=error_line_index]
+            .iter()
+            .rposition(is_header)
+            .unwrap_or(0);
+        let end = lines[error_line_index + 1..]
+            .iter()
+            .position(is_header)
+            .map(|offset| error_line_index + 1 + offset)
+            .unwrap_or_else(|| lines.len());
+
+        Some(lines[start..end].join("\n").trim_end().to_string())
+    }
+}
+
+/// Parse the participant captured in the `address` and
+/// `participant_type` groups of the specified regular expression, if
+/// the `line` matches it.
+fn parse_participant(regex: &Regex, line: &str) -> eyre::Result<Option<ParticipantRef>> {
+    let captures = match regex.captures(line) {
+        Some(captures) => captures,
+        None => return Ok(None),
+    };
+
+    let address_str = captures
+        .name("address")
+        .expect("expected address group to be captured")
+        .as_str()
+        .to_string();
+    let participant_type_s = captures
+        .name("participant_type")
+        .expect("expected participant_type group to be captured")
+        .as_str();
+
+    let address = AleoPublicKey::from_str(&address_str)?;
+
+    let participant = match participant_type_s {
+        "contributor" => ParticipantRef::Contributor(ContributorRef { address }),
+        "verifier" => ParticipantRef::Verifier(VerifierRef { address }),
+        _ => {
+            return Err(eyre::eyre!(
+                "unknown participant type: {}",
+                participant_type_s
+            ))
+        }
+    };
+
+    Ok(Some(participant))
+}
+
+/// Parse a line reporting that a replacement contributor has been
+/// activated. Returns the replacement contributor, and the dropped
+/// contributor whose slot it takes over if the line specifies it.
+fn parse_replacement_activated(
+    line: &str,
+) -> eyre::Result<Option<(ContributorRef, Option<ContributorRef>)>> {
+    let captures = match REPLACEMENT_ACTIVATED_RE.captures(line) {
+        Some(captures) => captures,
+        None => return Ok(None),
+    };
+
+    let replacement = ContributorRef {
+        address: AleoPublicKey::from_str(&captures["replacement"])?,
+    };
+    let dropped = captures
+        .name("dropped")
+        .map(|dropped| {
+            Ok::<_, eyre::Error>(ContributorRef {
+                address: AleoPublicKey::from_str(dropped.as_str())?,
+            })
+        })
+        .transpose()?;
+
+    Ok(Some((replacement, dropped)))
+}
+
+/// Parse the verifier captured in the `address` group of the
+/// specified regular expression, if the `line` matches it.
+fn parse_verifier(regex: &Regex, line: &str) -> eyre::Result<Option<VerifierRef>> {
+    let captures = match regex.captures(line) {
+        Some(captures) => captures,
+        None => return Ok(None),
+    };
+
+    let address = AleoPublicKey::from_str(
+        captures
+            .name("address")
+            .expect("expected address group to be captured")
+            .as_str(),
+    )?;
+
+    Ok(Some(VerifierRef { address }))
+}
+
+/// Patterns used to recognise events in the coordinator's log output
+/// which may be phrased differently by different versions of the
+/// coordinator. See [LogPatterns::for_version()] and
+/// [LogPatternOverrides].
+#[derive(Debug, Clone)]
+pub struct LogPatterns {
+    /// Matches the coordinator having booted up.
+    pub booted: Regex,
+    /// Matches a round having started, capturing the `round`.
+    pub round_started: Regex,
+    /// Matches the aggregation of a round having started, capturing
+    /// the `round`.
+    pub round_started_aggregation: Regex,
+    /// Matches a round having been aggregated, capturing the `round`.
+    pub round_aggregated: Regex,
+    /// Matches a round having finished, capturing the `round`.
+    pub round_finished: Regex,
+    /// Matches a successful contribution, capturing the contributor's
+    /// `address` and the `chunk`.
+    pub successful_contribution: Regex,
+}
+
+impl Default for LogPatterns {
+    /// The patterns used by coordinator versions before `0.2.0`, and
+    /// when the version is not known.
+    fn default() -> Self {
+        Self {
+            booted: BOOTED_RE.clone(),
+            round_started: ROUND_STARTED_RE.clone(),
+            round_started_aggregation: ROUND_STARTED_AGGREGATION_RE.clone(),
+            round_aggregated: ROUND_AGGREGATED_RE.clone(),
+            round_finished: ROUND_FINISHED_RE.clone(),
+            successful_contribution: SUCCESSFUL_CONTRIBUTION_RE.clone(),
+        }
+    }
+}
+
+impl LogPatterns {
+    /// The built-in patterns for the specified coordinator `version`,
+    /// falling back to the [LogPatterns::default()] patterns.
+    pub fn for_version(version: &CoordinatorVersion) -> Self {
+        let v0_2 = CoordinatorVersion {
+            major: 0,
+            minor: 2,
+            patch: 0,
+        };
+
+        if *version >= v0_2 {
+            Self {
+                successful_contribution: SUCCESSFUL_CONTRIBUTION_V0_2_RE.clone(),
+                ..Self::default()
+            }
+        } else {
+            Self::default()
+        }
+    }
+
+    /// Replace these patterns with those which are specified in
+    /// `overrides`.
+    pub fn with_overrides(self, overrides: &LogPatternOverrides) -> Self {
+        let LogPatternOverrides {
+            booted,
+            round_started,
+            round_started_aggregation,
+            round_aggregated,
+            round_finished,
+            successful_contribution,
+        } = overrides.clone();
+
+        Self {
+            booted: booted.unwrap_or(self.booted),
+            round_started: round_started.unwrap_or(self.round_started),
+            round_started_aggregation: round_started_aggregation
+                .unwrap_or(self.round_started_aggregation),
+            round_aggregated: round_aggregated.unwrap_or(self.round_aggregated),
+            round_finished: round_finished.unwrap_or(self.round_finished),
+            successful_contribution: successful_contribution
+                .unwrap_or(self.successful_contribution),
+        }
+    }
+}
+
+/// Custom patterns to use in place of the built-in [LogPatterns], to
+/// allow testing against a coordinator whose log output has changed
+/// without recompiling. Each pattern which is `None` falls back to
+/// the built-in pattern for the coordinator's version. The patterns
+/// must capture the same groups as those they replace. See
+/// [CoordinatorConfig::log_pattern_overrides].
+#[derive(Debug, Clone, Default)]
+pub struct LogPatternOverrides {
+    /// See [LogPatterns::booted].
+    pub booted: Option<Regex>,
+    /// See [LogPatterns::round_started].
+    pub round_started: Option<Regex>,
+    /// See [LogPatterns::round_started_aggregation].
+    pub round_started_aggregation: Option<Regex>,
+    /// See [LogPatterns::round_aggregated].
+    pub round_aggregated: Option<Regex>,
+    /// See [LogPatterns::round_finished].
+    pub round_finished: Option<Regex>,
+    /// See [LogPatterns::successful_contribution].
+    pub successful_contribution: Option<Regex>,
+}
+
+/// Parse the contributor and chunk of a successful contribution, if
+/// the `line` reports one.
+fn parse_successful_contribution(
+    patterns: &LogPatterns,
+    line: &str,
+) -> eyre::Result<Option<(ContributorRef, u64)>> {
+    let captures = match patterns.successful_contribution.captures(line) {
+        Some(captures) => captures,
+        None => return Ok(None),
+    };
+
+    let address_str = captures
+        .name("address")
+        .expect("expected address group to be captured")
+        .as_str()
+        .to_string();
+
+    let chunk = u64::from_str(
+        captures
+            .name("chunk")
+            .expect("exprected chunk address to be captured")
+            .as_str(),
+    )?;
+
+    let address = AleoPublicKey::from_str(&address_str)?;
+
+    Ok(Some((ContributorRef { address }, chunk)))
+}
+
+/// Parse the number of chunks aggregated so far, and the total
+/// number of chunks, from a line reporting the progress of the
+/// aggregation of a round.
+fn parse_aggregation_progress(line: &str) -> eyre::Result<Option<(u64, u64)>> {
+    let captures = match AGGREGATION_PROGRESS_RE.captures(line) {
+        Some(captures) => captures,
+        None => return Ok(None),
+    };
+
+    let done = u64::from_str(
+        captures
+            .name("done")
+            .expect("expected done group to be captured")
+            .as_str(),
+    )?;
+    let total = u64::from_str(
+        captures
+            .name("total")
+            .expect("expected total group to be captured")
+            .as_str(),
+    )?;
+
+    Ok(Some((done, total)))
+}
+
+/// Parse the verifier and the chunk from a line reporting that the
+/// verifier has verified a contribution, see
+/// [CeremonyMessage::SuccessfulVerification].
+fn parse_successful_verification(line: &str) -> eyre::Result<Option<(VerifierRef, u64)>> {
+    let verifier = match parse_verifier(&SUCCESSFUL_VERIFICATION_RE, line)? {
+        Some(verifier) => verifier,
+        None => return Ok(None),
+    };
+
+    let chunk = u64::from_str(
+        SUCCESSFUL_VERIFICATION_RE
+            .captures(line)
+            .and_then(|captures| captures.name("chunk"))
+            .expect("expected chunk group to be captured")
+            .as_str(),
+    )?;
+
+    Ok(Some((verifier, chunk)))
+}
+
+/// Parse the `round` group captured by the specified regular
+/// expression, if the `line` matches it.
+fn parse_round(regex: &Regex, line: &str) -> Option<u64> {
+    regex
+        .captures(line)
+        .and_then(|captures| captures.name("round"))
+        .and_then(|round| u64::from_str(round.as_str()).ok())
+}
+
+/// Classify a single line of the coordinator's log output, returning
+/// the [CeremonyMessage] that it corresponds to (if any).
+///
+/// Unlike the monitor started by [run_coordinator()], this is
+/// stateless: the round numbers are taken from the line itself rather
+/// than the tracked state of the ceremony, and lines are not checked
+/// for being received in the expected order. This allows external
+/// tools to reuse the parsing of the coordinator's output. As a
+/// consequence, the `seq` of a
+/// [CeremonyMessage::SuccessfulContribution] is always `0`.
+pub fn classify_log_line(line: &str) -> Option<CeremonyMessage> {
+    if BOOTED_RE.is_match(line) {
+        return Some(CeremonyMessage::RoundWaitingForParticipants(1));
+    }
+
+    if let Some(round) = parse_round(&ROUND_STARTED_RE, line) {
+        return Some(CeremonyMessage::RoundStarted(round));
+    }
+
+    if let Some(round) = parse_round(&ROUND_STARTED_AGGREGATION_RE, line) {
+        return Some(CeremonyMessage::RoundStartedAggregation(round));
+    }
+
+    if let Some(round) = parse_round(&ROUND_AGGREGATED_RE, line) {
+        return Some(CeremonyMessage::RoundAggregated(round));
+    }
+
+    if let Some(round) = parse_round(&ROUND_FINISHED_RE, line) {
+        return Some(CeremonyMessage::RoundFinished(round));
+    }
+
+    if ROUND_RESTARTED_NO_CONTRIBUTORS_RE.is_match(line) {
+        return Some(CeremonyMessage::Shutdown(ShutdownReason::TestFinished));
+    }
+
+    if let Some(round) = parse_round(&ROUND_RESTARTED_RE, line) {
+        return Some(CeremonyMessage::RoundRestarted(round));
+    }
+
+    if let Ok(Some(participant)) = parse_participant(&DROPPED_PARTICIPANT_RE, line) {
+        return Some(CeremonyMessage::ParticipantDropped(participant));
+    }
+
+    if let Ok(Some((replacement, Some(dropped)))) = parse_replacement_activated(line) {
+        return Some(CeremonyMessage::ReplacementActivated {
+            dropped,
+            replacement,
+        });
+    }
+
+    if let Ok(Some((contributor, chunk))) =
+        parse_successful_contribution(&LogPatterns::default(), line)
+    {
+        return Some(CeremonyMessage::SuccessfulContribution {
+            contributor,
+            chunk,
+            seq: 0,
+        });
+    }
+
+    if let Ok(Some((verifier, chunk))) = parse_successful_verification(line) {
+        return Some(CeremonyMessage::SuccessfulVerification { verifier, chunk });
+    }
+
+    if let Ok(Some(participant)) = parse_participant(&PARTICIPANT_JOINED_QUEUE_RE, line) {
+        return Some(CeremonyMessage::ParticipantJoinedQueue(participant));
+    }
+
+    if let Ok(Some(verifier)) = parse_verifier(&VERIFIER_DISCONNECTED_RE, line) {
+        return Some(CeremonyMessage::VerifierDisconnected(verifier));
+    }
+
+    if let Ok(Some(verifier)) = parse_verifier(&VERIFIER_RECONNECTED_RE, line) {
+        return Some(CeremonyMessage::VerifierReconnected(verifier));
+    }
+
+    if COORDINATOR_PAUSED_RE.is_match(line) {
+        return Some(CeremonyMessage::CoordinatorPaused);
+    }
+
+    if COORDINATOR_RESUMED_RE.is_match(line) {
+        return Some(CeremonyMessage::CoordinatorResumed);
+    }
+
+    None
+}
+
+impl CoordinatorStateReporter {
+    /// Create a new [CoordinatorStateReporter] with the state that
+    /// the process has just been started.
+    fn process_started(
+        ceremony_tx: Sender<CeremonyMessage>,
+        max_rounds: Option<u64>,
+        state_history: CoordinatorStateHistory,
+        broadcast_retry: BroadcastRetryOptions,
+    ) -> Self {
+        state_history.push(PublicCoordinatorState::from(
+            &CoordinatorState::ProcessStarted,
+        ));
+
+        Self {
+            ceremony_tx,
+            rounds: BTreeMap::new(),
+            completed_rounds: HashSet::new(),
+            max_rounds,
+            shutdown_on_rollback: true,
+            startup_retries: 0,
+            disconnected_verifiers: HashSet::new(),
+            round_contributions: HashSet::new(),
+            round_contribution_seq: 0,
+            pending_replacements: VecDeque::new(),
+            round_phase_starts: HashMap::new(),
+            clock: Arc::new(Instant::now),
+            round_archive: None,
+            paused: false,
+            backtrace_collector: BacktraceCollector::default(),
+            broadcast_messages: Vec::new(),
+            state_history,
+            broadcast_retry,
+            log_patterns: LogPatterns::default(),
+            log_pattern_overrides: LogPatternOverrides::default(),
+        }
+    }
+
+    /// Select the [LogPatterns] for the coordinator's version if the
+    /// `line` reports it.
+    fn check_version_line(&mut self, line: &str) {
+        let version = COORDINATOR_VERSION_LINE_RE
+            .captures(line)
+            .and_then(|captures| CoordinatorVersion::from_str(&captures["version"]).ok());
+
+        if let Some(version) = version {
+            tracing::debug!("Using log patterns for coordinator version {}.", version);
+            self.log_patterns =
+                LogPatterns::for_version(&version).with_overrides(&self.log_pattern_overrides);
+            self.state_history.set_version(version);
+        }
+    }
+
+    /// Broadcast a message to the ceremony, see
+    /// [broadcast_with_retry()].
+    fn broadcast(&mut self, message: CeremonyMessage) -> eyre::Result<()> {
+        self.broadcast_messages.push(message.clone());
+        broadcast_with_retry(&self.ceremony_tx, message, &self.broadcast_retry)
+    }
+
+    /// Take the messages which have been broadcast since this was
+    /// last called, e.g. those caused by the most recent call to
+    /// [CoordinatorStateReporter::parse_output_line()].
+    fn take_broadcast_messages(&mut self) -> Vec<CeremonyMessage> {
+        std::mem::take(&mut self.broadcast_messages)
+    }
+
+    /// Transition the round which `state` is for to a new state,
+    /// recording it in the [CoordinatorStateHistory].
+    fn set_state(&mut self, state: CoordinatorState) {
+        if let CoordinatorState::RoundRunning(round) = state {
+            self.state_history.start_round_progress(round);
+        }
+        self.record_phase_start(&state);
+        self.state_history
+            .push(PublicCoordinatorState::from(&state));
+        if let Some(round) = state.round() {
+            self.rounds.insert(round, state);
+            self.state_history.set_round_states(&self.rounds);
+        }
+    }
+
+    /// Record the time at which a round entered the phase of the
+    /// `state`. Only the first time is recorded for each phase, so a
+    /// restarted round is timed from when it first started running.
+    fn record_phase_start(&mut self, state: &CoordinatorState) {
+        let now = (self.clock)();
+        match *state {
+            CoordinatorState::RoundWaitingForParticipants(round) => {
+                self.round_phase_starts.insert(
+                    round,
+                    RoundPhaseStarts {
+                        waiting: Some(now),
+                        ..RoundPhaseStarts::default()
+                    },
+                );
+            }
+            CoordinatorState::RoundRunning(round) => {
+                let starts = self.round_phase_starts.entry(round).or_default();
+                starts.running.get_or_insert(now);
+            }
+            CoordinatorState::RoundAggregating(round) => {
+                let starts = self.round_phase_starts.entry(round).or_default();
+                starts.aggregating.get_or_insert(now);
+            }
+            CoordinatorState::RoundWaitingForFinish(round) => {
+                let starts = self.round_phase_starts.entry(round).or_default();
+                starts.finishing.get_or_insert(now);
+            }
+            CoordinatorState::RoundRolledBack(round) => {
+                self.round_phase_starts.remove(&round);
+            }
+            _ => {}
+        }
+    }
+
+    /// Broadcast [CeremonyMessage::RoundTimings] for the `round`,
+    /// which has just finished. Nothing is broadcast if the start of
+    /// one of the round's phases was not seen, e.g. because the
+    /// coordinator was already part way through the round.
+    fn broadcast_round_timings(&mut self, round: u64) -> eyre::Result<()> {
+        let finished = (self.clock)();
+        let starts = self.round_phase_starts.remove(&round).unwrap_or_default();
+
+        let (waiting, running, aggregating, finishing) = match (
+            starts.waiting,
+            starts.running,
+            starts.aggregating,
+            starts.finishing,
+        ) {
+            (Some(waiting), Some(running), Some(aggregating), Some(finishing)) => {
+                (waiting, running, aggregating, finishing)
+            }
+            _ => {
+                tracing::debug!(
+                    "Not reporting the timings for round {}, the start of some of its \
+                        phases was not seen: {:?}",
+                    round,
+                    starts
+                );
+                return Ok(());
+            }
+        };
+
+        self.broadcast(CeremonyMessage::RoundTimings {
+            round,
+            waiting: running.saturating_duration_since(waiting),
+            running: aggregating.saturating_duration_since(running),
+            aggregating: finishing.saturating_duration_since(aggregating),
+            finishing: finished.saturating_duration_since(finishing),
+        })
+    }
+
+    /// Log the progress of the current round.
+    fn report_progress(&self) {
+        let progress = self.state_history.progress();
+        tracing::debug!(
+            "Round {}: {:.0}% complete.",
+            progress.round,
+            progress.percent_complete()
+        );
+    }
+
+    /// Check whether the `line` is part of a panic printed by the
+    /// coordinator, and once the complete backtrace has been
+    /// collected, broadcast a [CeremonyMessage::Shutdown] with
+    /// [ShutdownReason::CoordinatorPanicked].
+    fn check_backtrace(&mut self, line: &str) -> eyre::Result<()> {
+        if let Some(backtrace) = self.backtrace_collector.push_line(line) {
+            self.report_backtrace(backtrace)?;
+        }
+
+        Ok(())
+    }
+
+    /// Called when the coordinator's output has ended, to report any
+    /// backtrace which was still being collected.
+    fn finish(&mut self) -> eyre::Result<()> {
+        if let Some(backtrace) = self.backtrace_collector.finish() {
+            self.report_backtrace(backtrace)?;
+        }
+
+        Ok(())
+    }
+
+    fn report_backtrace(&mut self, backtrace: CoordinatorBacktrace) -> eyre::Result<()> {
+        tracing::error!(
+            "The coordinator panicked at {}: {}",
+            backtrace.location,
+            backtrace.message
+        );
+
+        // The coordinator may be relaunched when it fails to boot,
+        // in which case the ceremony shuts down if it runs out of
+        // retries.
+        if self.startup_retries > 0 && self.rounds.is_empty() {
+            return Ok(());
+        }
+
+        self.broadcast(CeremonyMessage::Shutdown(
+            ShutdownReason::CoordinatorPanicked(backtrace),
+        ))?;
+        Ok(())
+    }
+
+    /// Check whether a participant has been dropped from the round
+    /// (and broadcast this fact with [CeremonyMessage::ParticipantDropped]).
+    fn check_participant_dropped(&mut self, line: &str) -> eyre::Result<()> {
+        if let Some(participant) = parse_participant(&DROPPED_PARTICIPANT_RE, line)? {
+            match &participant {
+                ParticipantRef::Verifier(verifier) => {
+                    // The verifier has been permanently removed.
+                    self.disconnected_verifiers.remove(verifier);
+                }
+                ParticipantRef::Contributor(contributor) => {
+                    self.pending_replacements.push_back(contributor.clone());
+                }
+            }
+
+            self.broadcast(CeremonyMessage::ParticipantDropped(participant))?;
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a replacement contributor has taken over the slot
+    /// of a dropped contributor (and broadcast this fact with
+    /// [CeremonyMessage::ReplacementActivated]). If the line doesn't
+    /// specify which contributor is being replaced, the replacement is
+    /// assumed to take over the slot of the contributor which was
+    /// dropped the earliest.
+    fn check_replacement_activated(&mut self, line: &str) -> eyre::Result<()> {
+        let (replacement, dropped) = match parse_replacement_activated(line)? {
+            Some(parsed) => parsed,
+            None => return Ok(()),
+        };
+
+        let dropped = match dropped {
+            Some(dropped) => {
+                self.pending_replacements
+                    .retain(|pending| pending != &dropped);
+                dropped
+            }
+            None => match self.pending_replacements.pop_front() {
+                Some(dropped) => dropped,
+                None => {
+                    tracing::warn!(
+                        "Replacement contributor {} was activated, but no dropped \
+                            contributor is awaiting replacement.",
+                        replacement
+                    );
+                    return Ok(());
+                }
+            },
+        };
+
+        tracing::debug!(
+            "Replacement contributor {} has taken over from dropped contributor {}.",
+            replacement,
+            dropped
+        );
+        self.broadcast(CeremonyMessage::ReplacementActivated {
+            dropped,
+            replacement,
+        })?;
+
+        Ok(())
+    }
+
+    /// Check whether a participant has joined the coordinator's queue
+    /// (and broadcast this fact with
+    /// [CeremonyMessage::ParticipantJoinedQueue]).
+    fn check_participant_joined_queue(&mut self, line: &str) -> eyre::Result<()> {
+        if let Some(participant) = parse_participant(&PARTICIPANT_JOINED_QUEUE_RE, line)? {
+            tracing::debug!("Participant {:?} joined the queue.", participant);
+            self.broadcast(CeremonyMessage::ParticipantJoinedQueue(participant))?;
+        }
+
+        Ok(())
+    }
+
+    /// Check whether a verifier has disconnected from, or reconnected
+    /// to the coordinator (and broadcast this fact with
+    /// [CeremonyMessage::VerifierDisconnected] or
+    /// [CeremonyMessage::VerifierReconnected]). A reconnection is only
+    /// reported for a verifier that was previously seen to disconnect.
+    fn check_verifier_connection(&mut self, line: &str) -> eyre::Result<()> {
+        if let Some(verifier) = parse_verifier(&VERIFIER_DISCONNECTED_RE, line)? {
+            tracing::debug!("Verifier {} has disconnected.", verifier.address);
+            self.disconnected_verifiers.insert(verifier.clone());
+            self.broadcast(CeremonyMessage::VerifierDisconnected(verifier))?;
+        } else if let Some(verifier) = parse_verifier(&VERIFIER_RECONNECTED_RE, line)? {
+            if self.disconnected_verifiers.remove(&verifier) {
+                tracing::debug!("Verifier {} has reconnected.", verifier.address);
+                self.broadcast(CeremonyMessage::VerifierReconnected(verifier))?;
+            } else {
+                tracing::debug!(
+                    "Verifier {} reconnected without having disconnected.",
+                    verifier.address
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the coordinator has entered or left a
+    /// maintenance pause, and if so, broadcast it (as
+    /// [CeremonyMessage::CoordinatorPaused] or
+    /// [CeremonyMessage::CoordinatorResumed]). The tracked state of
+    /// the ceremony is unchanged by a pause, and continues from where
+    /// it was when the coordinator resumes.
+    fn check_pause(&mut self, line: &str) -> eyre::Result<()> {
+        if !self.paused && COORDINATOR_PAUSED_RE.is_match(line) {
+            tracing::info!("Coordinator has paused for maintenance.");
+            self.paused = true;
+            self.state_history.set_paused(true);
+            self.broadcast(CeremonyMessage::CoordinatorPaused)?;
+        } else if self.paused && COORDINATOR_RESUMED_RE.is_match(line) {
+            tracing::info!("Coordinator has resumed after maintenance.");
+            self.paused = false;
+            self.state_history.set_paused(false);
+            self.broadcast(CeremonyMessage::CoordinatorResumed)?;
+        }
+
+        Ok(())
+    }
+
+    /// The round with the oldest state matching `predicate`, if any.
+    fn find_round(&self, predicate: impl Fn(&CoordinatorState) -> bool) -> Option<u64> {
+        self.rounds
+            .iter()
+            .find(|(_, state)| predicate(state))
+            .map(|(round, _)| *round)
+    }
+
+    /// Whether a round reported as started by the coordinator, which
+    /// is not yet being tracked, starts while the rounds before it
+    /// are still completing (e.g. round `N + 1` starts while round
+    /// `N` is aggregating).
+    fn is_interleaved_round_start(&self, round: u64) -> bool {
+        let latest_round = self.rounds.keys().next_back().copied().unwrap_or(0);
+        round == latest_round + 1
+            && self.rounds.values().all(|state| {
+                matches!(
+                    state,
+                    CoordinatorState::RoundAggregating(_)
+                        | CoordinatorState::RoundWaitingForFinish(_)
+                )
+            })
+    }
+
+    /// Start tracking the specified `round`, which is waiting for
+    /// participants. The contributions counted for the previous round
+    /// are reset.
+    fn wait_for_participants(&mut self, round: u64) -> eyre::Result<()> {
+        self.state_history.reset_chunk_contributions();
+        self.round_contributions.clear();
+        self.round_contribution_seq = 0;
+        self.pending_replacements.clear();
+        self.broadcast(CeremonyMessage::RoundWaitingForParticipants(round))?;
+        self.set_state(CoordinatorState::RoundWaitingForParticipants(round));
+        Ok(())
+    }
+
+    /// Stop tracking the rounds which have finished or been rolled
+    /// back, and start tracking the round which the coordinator is
+    /// then expected to wait for participants for (unless it is
+    /// already being tracked). After a round has finished, the next
+    /// round is only tracked once the `line` confirms that the
+    /// coordinator is waiting for participants for it (see
+    /// [CoordinatorStateReporter::confirms_waiting_for_participants()]),
+    /// because its transcript may not have been initialized yet.
+    fn complete_rounds(&mut self, line: &str) -> eyre::Result<()> {
+        let completed: Vec<(u64, u64, bool)> = self
+            .rounds
+            .iter()
+            .filter_map(|(round, state)| match state {
+                CoordinatorState::RoundFinished(_) => Some((*round, round + 1, true)),
+                CoordinatorState::RoundRolledBack(_) => Some((*round, 1, false)),
+                _ => None,
+            })
+            .collect();
+
+        for (round, next_round, requires_confirmation) in completed {
+            if requires_confirmation
+                && !self.rounds.contains_key(&next_round)
+                && !self.confirms_waiting_for_participants(next_round, line)
+            {
+                continue;
+            }
+
+            // Remove the completed round before checking whether the
+            // next round is tracked, because after rolling back round
+            // 1 the next round is the same round.
+            self.rounds.remove(&round);
+            self.completed_rounds.insert(round);
+            self.state_history.set_round_states(&self.rounds);
+            if !self.rounds.contains_key(&next_round) {
+                self.wait_for_participants(next_round)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the `line` confirms that the coordinator is waiting
+    /// for participants for the specified `round`: either it reports
+    /// that it is waiting for participants (for this round, if the
+    /// line includes a round number), or it reports that this round
+    /// has already started.
+    fn confirms_waiting_for_participants(&self, round: u64, line: &str) -> bool {
+        if let Some(captures) = WAITING_FOR_PARTICIPANTS_RE.captures(line) {
+            return captures.name("round").map_or(true, |waiting_round| {
+                u64::from_str(waiting_round.as_str()).ok() == Some(round)
+            });
+        }
+
+        parse_round(&self.log_patterns.round_started, line) == Some(round)
+    }
+
+    /// Use the specified `overrides` in place of the built-in
+    /// [LogPatterns].
+    fn set_log_pattern_overrides(&mut self, overrides: LogPatternOverrides) {
+        self.log_patterns = self.log_patterns.clone().with_overrides(&overrides);
+        self.log_pattern_overrides = overrides;
+    }
+
+    /// Parse a line reporting a change in the state of the round
+    /// given in the line (see [RoundEvent]), and route it to that
+    /// round. Events reported late for a round which has already
+    /// completed are ignored. Returns an error if the round was never
+    /// started, which indicates that the tracked state of the ceremony
+    /// has diverged from the coordinator's.
+    fn check_round_event(&mut self, line: &str) -> eyre::Result<()> {
+        let patterns = &self.log_patterns;
+        let (regex, event) = match [
+            (&patterns.round_started, RoundEvent::Started),
+            (
+                &patterns.round_started_aggregation,
+                RoundEvent::StartedAggregation,
+            ),
+            (&patterns.round_aggregated, RoundEvent::Aggregated),
+            (&patterns.round_finished, RoundEvent::Finished),
+        ]
+        .iter()
+        .find(|(regex, _)| regex.is_match(line))
+        {
+            Some(&(regex, event)) => (regex, event),
+            None => return Ok(()),
+        };
+
+        let round = parse_round(regex, line).ok_or_else(|| {
+            eyre::eyre!(
+                "Unable to parse the round number from coordinator output: {:?}",
+                line
+            )
+        })?;
+
+        match (self.rounds.get(&round), event) {
+            (Some(CoordinatorState::RoundWaitingForParticipants(_)), RoundEvent::Started) => {
+                tracing::debug!(
+                    "Detected that round {} is now waiting for participants.",
+                    round
+                );
+                self.round_contributions.clear();
+                self.round_contribution_seq = 0;
+                self.broadcast(CeremonyMessage::RoundStarted(round))?;
+                self.set_state(CoordinatorState::RoundRunning(round));
+            }
+            (Some(CoordinatorState::RoundRunning(_)), RoundEvent::StartedAggregation) => {
+                tracing::debug!("Detected that round {} is has started running.", round);
+                self.broadcast(CeremonyMessage::RoundStartedAggregation(round))?;
+                self.set_state(CoordinatorState::RoundAggregating(round));
+            }
+            (Some(CoordinatorState::RoundAggregating(_)), RoundEvent::Aggregated) => {
+                tracing::debug!("Detected that round {} is aggregating.", round);
+                self.broadcast(CeremonyMessage::RoundAggregated(round))?;
+                self.set_state(CoordinatorState::RoundWaitingForFinish(round));
+            }
+            (Some(CoordinatorState::RoundWaitingForFinish(_)), RoundEvent::Finished) => {
+                tracing::debug!("Detected that round {} has finished.", round);
+                // Archive the round first, so that the receivers can
+                // check the round's artifacts as soon as it finishes.
+                if let Some(config) = &self.round_archive {
+                    archive_round(config, round)?;
+                }
+                self.broadcast(CeremonyMessage::RoundFinished(round))?;
+                self.broadcast_round_timings(round)?;
+
+                if self.max_rounds.map(|max| round >= max).unwrap_or(false) {
+                    tracing::debug!(
+                        "Round {} is the last expected round, the test is finished.",
+                        round
+                    );
+                    self.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))?;
+                    self.set_state(CoordinatorState::LastRoundFinished(round));
+                } else {
+                    self.set_state(CoordinatorState::RoundFinished(round));
+                }
+            }
+            (Some(state), _) => {
+                tracing::debug!(
+                    "Ignoring {:?} event for round {} in state {:?}.",
+                    event,
+                    round,
+                    state
+                );
+            }
+            (None, RoundEvent::Started) if self.is_interleaved_round_start(round) => {
+                tracing::debug!(
+                    "Detected that round {} has started while the previous round completes.",
+                    round
+                );
+                self.wait_for_participants(round)?;
+                self.broadcast(CeremonyMessage::RoundStarted(round))?;
+                self.set_state(CoordinatorState::RoundRunning(round));
+            }
+            (None, _) if self.completed_rounds.contains(&round) => {
+                tracing::debug!(
+                    "Ignoring {:?} event for round {}, which has already completed.",
+                    event,
+                    round
+                );
+            }
+            (None, _) => {
+                return Err(eyre::eyre!(
+                    "Expected the coordinator to report an event for one of the rounds \
+                        in progress {:?}, but it was for round {}: {:?}",
+                    self.rounds.keys().collect::<Vec<_>>(),
+                    round,
+                    line
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse a line reported while the specified `round` is running,
+    /// for contributions, verifications, or the round being rolled
+    /// back.
+    fn parse_running_round_line(&mut self, round: u64, line: &str) -> eyre::Result<()> {
+        if ROUND_RESTARTED_NO_CONTRIBUTORS_RE.is_match(line) {
+            tracing::debug!(
+                "Detected that round {} has been rolled back with no remaining contributors.",
+                round
+            );
+            self.broadcast(CeremonyMessage::RoundRolledBack(round))?;
+            if self.shutdown_on_rollback {
+                self.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))?;
+            }
+            self.set_state(CoordinatorState::RoundRolledBack(round));
+        }
+
+        if let Some(restarted_round) = parse_round(&ROUND_RESTARTED_RE, line) {
+            if restarted_round != round {
+                tracing::warn!(
+                    "Ignoring the restart of round {} reported while round {} is running: {:?}",
+                    restarted_round,
+                    round,
+                    line
+                );
+                return Ok(());
+            }
+
+            tracing::debug!(
+                "Detected that round {} has been restarted with contributors remaining.",
+                round
+            );
+            self.state_history.reset_chunk_contributions();
+            self.round_contributions.clear();
+            self.round_contribution_seq = 0;
+            self.broadcast(CeremonyMessage::RoundRestarted(round))?;
+            self.set_state(CoordinatorState::RoundRunning(round));
+            return Ok(());
+        }
+
+        if let Some((contributor, chunk)) = parse_successful_contribution(&self.log_patterns, line)?
+        {
+            tracing::debug!(
+                "Contributor {} made a successful contribution to chunk {}.",
+                &contributor,
+                &chunk
+            );
+
+            let duplicate = !self
+                .round_contributions
+                .insert((contributor.clone(), chunk));
+            self.broadcast(CeremonyMessage::SuccessfulContribution {
+                contributor: contributor.clone(),
+                chunk,
+                seq: self.round_contribution_seq,
+            })?;
+            self.round_contribution_seq += 1;
+            if duplicate {
+                tracing::warn!(
+                    "Contributor {} contributed to chunk {} more than once in round {}.",
+                    &contributor,
+                    &chunk,
+                    round
+                );
+                self.broadcast(CeremonyMessage::DuplicateContribution { contributor, chunk })?;
+            }
+            self.state_history.record_chunk_contribution(chunk);
+            self.state_history
+                .update_progress(|progress| progress.contributions += 1);
+            self.report_progress();
+        }
+
+        if let Some((verifier, chunk)) = parse_successful_verification(line)? {
+            tracing::debug!(
+                "Verifier {} verified the contribution to chunk {}.",
+                &verifier,
+                &chunk
+            );
+
+            self.broadcast(CeremonyMessage::SuccessfulVerification { verifier, chunk })?;
+            self.state_history
+                .update_progress(|progress| progress.verifications += 1);
+            self.report_progress();
+        } else if VERIFIED_CONTRIBUTION_RE.is_match(line) {
+            self.state_history
+                .update_progress(|progress| progress.verifications += 1);
+            self.report_progress();
+        }
+
+        Ok(())
+    }
+
+    /// Parse a line of the combined `stdout`/`stderr` output from the
+    /// `coordinator` process, see
+    /// [CoordinatorStateReporter::parse_stdout_line()] and
+    /// [CoordinatorStateReporter::parse_stderr_line()].
+    fn parse_output_line(&mut self, line: &str) -> eyre::Result<()> {
+        self.check_backtrace(line)?;
+        self.parse_stdout_line(line)
+    }
+
+    /// Parse a line of the `coordinator` process's `stderr`, when it
+    /// is read separately from `stdout` (see [ProcessOutput::stderr]).
+    /// Only panics and errors which prevent the coordinator from
+    /// booting are detected. The state of the rounds is only parsed
+    /// from `stdout`, because the order of the lines on one stream
+    /// relative to the other is not preserved.
+    fn parse_stderr_line(&mut self, line: &str) -> eyre::Result<()> {
+        self.check_backtrace(line)?;
+
+        if self.rounds.is_empty() {
+            self.check_startup_error(line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the `line` reports an error which prevents the
+    /// coordinator from booting, and return it.
+    fn check_startup_error(&mut self, line: &str) -> eyre::Result<()> {
+        if STARTUP_LOCK_RE.is_match(line) {
+            return Err(eyre::eyre!(
+                "The coordinator refused to start because of an existing lock: {:?}. \
+                        A previous coordinator process may still be running, or may have \
+                        left behind a stale lock file or SQLite database (`setup.db3`) in \
+                        the coordinator's out directory. Stop any remaining coordinator \
+                        processes and remove the stale files before running again.",
+                line
+            ));
+        }
+
+        if CONFIG_PARSE_ERROR_RE.is_match(line) {
+            let error = ConfigParseError {
+                line: line.to_string(),
+            };
+            // The coordinator may be relaunched when it fails to
+            // boot, see `report_backtrace()`.
+            if self.startup_retries == 0 {
+                self.broadcast(CeremonyMessage::Shutdown(
+                    ShutdownReason::CoordinatorConfigRejected(error.clone()),
+                ))?;
+            }
+            return Err(error.into());
+        }
+
+        Ok(())
+    }
+
+    /// Parse stdout line from the `coordinator` process, broadcast
+    /// messages to the ceremony when the coordinator state changes.
+    /// Keeps track of the state of each round in progress, so that
+    /// the events of consecutive rounds which overlap (e.g. round
+    /// `N + 1` starting while round `N` is aggregating) are routed to
+    /// the round they are for. Returns an error if a round event is
+    /// reported for a round which is not in progress, see
+    /// [CoordinatorStateReporter::check_round_event()].
+    fn parse_stdout_line(&mut self, line: &str) -> eyre::Result<()> {
+        if self.rounds.is_empty() {
+            self.check_version_line(line);
+            self.check_startup_error(line)?;
+
+            if self.log_patterns.booted.is_match(line) {
+                tracing::debug!("Coordinator process has started");
+                self.broadcast(CeremonyMessage::CoordinatorReady)?;
+                self.wait_for_participants(1)?;
+            }
+
+            return Ok(());
+        }
+
+        self.check_participant_joined_queue(line)?;
+        self.check_verifier_connection(line)?;
+        self.check_pause(line)?;
+
+        if self
+            .find_round(|state| matches!(state, CoordinatorState::LastRoundFinished(_)))
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        self.complete_rounds(line)?;
+
+        // Check whether any participants have been dropped (or
+        // replaced) while a round is waiting for participants or
+        // running.
+        if self
+            .find_round(|state| {
+                matches!(
+                    state,
+                    CoordinatorState::RoundWaitingForParticipants(_)
+                        | CoordinatorState::RoundRunning(_)
+                )
+            })
+            .is_some()
+        {
+            self.check_participant_dropped(line)?;
+            self.check_replacement_activated(line)?;
+        }
+
+        if let Some(round) =
+            self.find_round(|state| matches!(state, CoordinatorState::RoundRunning(_)))
+        {
+            self.parse_running_round_line(round, line)?;
+        }
+
+        if let Some(round) =
+            self.find_round(|state| matches!(state, CoordinatorState::RoundAggregating(_)))
+        {
+            if let Some((done, total)) = parse_aggregation_progress(line)? {
+                tracing::debug!(
+                    "Aggregated {} of {} chunks in round {}.",
+                    done,
+                    total,
+                    round
+                );
+                self.broadcast(CeremonyMessage::AggregationProgress { round, done, total })?;
+            }
+        }
+
+        self.check_round_event(line)
+    }
+}
+
+/// A change in the state of a round, reported by the coordinator
+/// along with the round's number, see
+/// [CoordinatorStateReporter::check_round_event()].
+#[derive(Debug, Clone, Copy)]
+enum RoundEvent {
+    /// See [LogPatterns::round_started].
+    Started,
+    /// See [LogPatterns::round_started_aggregation].
+    StartedAggregation,
+    /// See [LogPatterns::round_aggregated].
+    Aggregated,
+    /// See [LogPatterns::round_finished].
+    Finished,
+}
+
+/// Options for how the [CoordinatorStateReporter] used by
+/// [monitor_coordinator()] responds to the coordinator's state.
+#[derive(Debug, Clone)]
+struct ReporterOptions {
+    /// See [CoordinatorConfig::max_rounds].
+    max_rounds: Option<u64>,
+    /// See [CoordinatorConfig::shutdown_on_rollback].
+    shutdown_on_rollback: bool,
+    /// See [CoordinatorConfig::startup_retries].
+    startup_retries: u32,
+    /// See [CoordinatorConfig::log_pattern_overrides].
+    log_pattern_overrides: LogPatternOverrides,
+    /// If `Some`, each round is archived with [archive_round()] before
+    /// [CeremonyMessage::RoundFinished] is broadcast for it, see
+    /// [CoordinatorConfig::per_round_out_dir].
+    round_archive: Option<CoordinatorConfig>,
+}
+
+impl Default for ReporterOptions {
+    fn default() -> Self {
+        Self {
+            max_rounds: None,
+            shutdown_on_rollback: true,
+            startup_retries: 0,
+            log_pattern_overrides: LogPatternOverrides::default(),
+            round_archive: None,
+        }
+    }
+}
+
+/// A line of the `coordinator.jsonl` log file, see
+/// [CoordinatorConfig::json_log].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoordinatorLogRecord {
+    /// The time that the line was received from the coordinator, in
+    /// RFC 3339 format.
+    pub ts: String,
+    /// The line, as written to the plain text log.
+    pub raw: String,
+    /// The message broadcast to the ceremony because of this line (the
+    /// first, if there were several), or `None` if the line didn't
+    /// cause any.
+    pub event: Option<CeremonyMessage>,
+}
+
+/// Options for the log files written by [monitor_coordinator()].
+#[derive(Debug, Clone)]
+struct CoordinatorLogOptions {
+    /// Path to the plain text log file.
+    log_file_path: PathBuf,
+    /// Path to the plain text log file for the coordinator's
+    /// `stderr`, if it is separated from its `stdout` (see
+    /// [ProcessOutput::stderr]).
+    stderr_log_file_path: PathBuf,
+    /// See [CoordinatorConfig::max_log_size].
+    max_log_size: Option<u64>,
+    /// Path to the JSON lines log file, if enabled (see
+    /// [CoordinatorConfig::json_log]).
+    json_log_path: Option<PathBuf>,
+    /// See [CoordinatorConfig::log_timestamps].
+    timestamps: bool,
+}
+
+impl CoordinatorLogOptions {
+    /// Only write the plain text log file at `log_file_path` (and
+    /// the `stderr` log file alongside it, with the extension
+    /// `.stderr.log`), with no maximum size or timestamps.
+    fn new(log_file_path: PathBuf) -> Self {
+        Self {
+            stderr_log_file_path: log_file_path.with_extension("stderr.log"),
+            log_file_path,
+            max_log_size: None,
+            json_log_path: None,
+            timestamps: false,
+        }
+    }
+}
+
+/// Prefix `line` with an RFC3339 timestamp for `time`, see
+/// [CoordinatorConfig::log_timestamps].
+fn timestamp_line(time: SystemTime, line: &str) -> String {
+    format!("{} {}", humantime::format_rfc3339_millis(time), line)
+}
+
+/// A log file which is rolled over to a file of the same name with
+/// `.1` appended (replacing any previous one) once it would exceed a
+/// maximum size, see [CoordinatorConfig::max_log_size].
+struct RotatingLogFile {
+    path: PathBuf,
+    file: File,
+    /// The current size of the `file` in bytes.
+    size: u64,
+    /// The maximum size of the `file` in bytes, or `None` for no
+    /// limit.
+    max_size: Option<u64>,
+}
+
+impl RotatingLogFile {
+    /// Open the log file at `path` for appending, creating it if it
+    /// doesn't yet exist.
+    fn open(path: impl AsRef<Path>, max_size: Option<u64>) -> eyre::Result<Self> {
+        let path = path.as_ref().to_owned();
+        let file = Self::open_file(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+        })
+    }
+
+    fn open_file(path: &Path) -> eyre::Result<File> {
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .wrap_err("unable to open log file")
+    }
+
+    /// The path that the log file is rolled over to.
+    fn rolled_path(&self) -> PathBuf {
+        rolled_log_path(&self.path)
+    }
+
+    /// Write a `line` to the log file. If the line would take the file
+    /// over the maximum size, the file is rolled over first, so that
+    /// every line is written whole to a single file.
+    ///
+    /// The line is written (and flushed) with a single write, so that
+    /// it is on disk even if the harness stops abruptly immediately
+    /// afterwards, e.g. because of a panic.
+    fn write_line(&mut self, line: &str) -> eyre::Result<()> {
+        let line_size = line.len() as u64 + 1;
+
+        if let Some(max_size) = self.max_size {
+            if self.size > 0 && self.size + line_size > max_size {
+                self.roll_over()?;
+            }
+        }
+
+        write_log_record(&mut self.file, line.as_bytes())?;
+        self.size += line_size;
+
+        Ok(())
+    }
+
+    fn roll_over(&mut self) -> eyre::Result<()> {
+        self.file.flush()?;
+
+        let rolled_path = self.rolled_path();
+        std::fs::rename(&self.path, &rolled_path).wrap_err_with(|| {
+            eyre::eyre!(
+                "Unable to roll over log file {:?} to {:?}",
+                self.path,
+                rolled_path
+            )
+        })?;
+
+        self.file = Self::open_file(&self.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+/// The path which the log file at `path` is moved to when it is
+/// rolled over by [RotatingLogFile], with `.1` appended.
+fn rolled_log_path(path: &Path) -> PathBuf {
+    let mut rolled_path = path.to_path_buf().into_os_string();
+    rolled_path.push(".1");
+    PathBuf::from(rolled_path)
+}
+
+/// Write the `record` followed by a newline to the log `file` in a
+/// single write, and flush it, so that the log is complete up to the
+/// last record if the harness stops abruptly.
+fn write_log_record(file: &mut File, record: &[u8]) -> eyre::Result<()> {
+    let mut buffer = Vec::with_capacity(record.len() + 1);
+    buffer.extend_from_slice(record);
+    buffer.push(b'\n');
+    file.write_all(&buffer)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// The stream of the coordinator's output that a line was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line read from the coordinator's output by
+/// [spawn_line_reader()].
+type OutputLine = (OutputStream, std::io::Result<String>);
+
+/// Read lines from the `stream` of the coordinator's output in a new
+/// thread, sending them to `line_tx` until the stream closes.
+fn spawn_line_reader(
+    file: File,
+    stream: OutputStream,
+    line_tx: mpsc::Sender<OutputLine>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for line_result in BufReader::new(file).lines() {
+            if line_tx.send((stream, line_result)).is_err() {
+                // The monitor has stopped.
+                break;
+            }
+        }
+    })
+}
+
+/// Monitor the setup coordinator. Parses the `stderr`/`stdout` and
+/// emits messages/alters state when certain events occur, and also
+/// pipes the output to the [tracing::debug!()], and
+/// `coordinator_log.txt` log file (or the `stderr` log file if the
+/// line was read from [ProcessOutput::stderr]). Sends
+/// [MonitorProcessMessage::Ready] to `monitor_tx` (if specified) once
+/// the coordinator has booted.
+fn monitor_coordinator(
+    output: ProcessOutput,
+    ceremony_tx: Sender<CeremonyMessage>,
+    monitor_tx: Option<Sender<MonitorProcessMessage>>,
+    log_options: CoordinatorLogOptions,
+    reporter_options: ReporterOptions,
+    state_history: CoordinatorStateHistory,
+    broadcast_retry: BroadcastRetryOptions,
+) -> eyre::Result<()> {
+    let mut state_reporter = CoordinatorStateReporter::process_started(
+        ceremony_tx,
+        reporter_options.max_rounds,
+        state_history,
+        broadcast_retry,
+    );
+    state_reporter.shutdown_on_rollback = reporter_options.shutdown_on_rollback;
+    state_reporter.startup_retries = reporter_options.startup_retries;
+    state_reporter.round_archive = reporter_options.round_archive;
+    state_reporter.set_log_pattern_overrides(reporter_options.log_pattern_overrides);
+
+    let mut log_file = RotatingLogFile::open(&log_options.log_file_path, log_options.max_log_size)?;
+    let mut json_log_file = log_options
+        .json_log_path
+        .as_ref()
+        .map(|json_log_path| {
+            OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(json_log_path)
+                .wrap_err("unable to open json log file")
+        })
+        .transpose()?;
+
+    // Both streams are read concurrently, so that the coordinator
+    // can't block writing to a full pipe which isn't being read.
+    let (line_tx, line_rx) = mpsc::channel::<OutputLine>();
+    let mut reader_joins = vec![spawn_line_reader(
+        output.stdout,
+        OutputStream::Stdout,
+        line_tx.clone(),
+    )];
+    let mut stderr_log_file = match output.stderr {
+        Some(stderr) => {
+            reader_joins.push(spawn_line_reader(
+                stderr,
+                OutputStream::Stderr,
+                line_tx.clone(),
+            ));
+            Some(RotatingLogFile::open(
+                &log_options.stderr_log_file_path,
+                log_options.max_log_size,
+            )?)
+        }
+        None => None,
+    };
+    drop(line_tx);
+
+    // It's expected that if the process closes, its output will also
+    // close and this iterator will complete gracefully.
+    for (stream, line_result) in line_rx {
+        match line_result {
+            Ok(line) => {
+                // Write to log file before the line is parsed, so that
+                // a line which stops the coordinator (e.g. an error
+                // while booting) is in the log.
+                let read_time = SystemTime::now();
+                let log_line = if log_options.timestamps {
+                    Cow::Owned(timestamp_line(read_time, &line))
+                } else {
+                    Cow::Borrowed(line.as_str())
+                };
+                match (stream, &mut stderr_log_file) {
+                    (OutputStream::Stderr, Some(stderr_log_file)) => {
+                        stderr_log_file.write_line(&log_line)?
+                    }
+                    _ => log_file.write_line(&log_line)?,
+                }
+
+                // The lines of the two streams are received in no
+                // particular order relative to each other, so when
+                // they are read separately only `stdout` is used to
+                // track the state of the rounds.
+                match (stream, stderr_log_file.is_some()) {
+                    (OutputStream::Stdout, true) => state_reporter.parse_stdout_line(&line)?,
+                    (OutputStream::Stderr, _) => state_reporter.parse_stderr_line(&line)?,
+                    (OutputStream::Stdout, false) => state_reporter.parse_output_line(&line)?,
+                }
+
+                let messages = state_reporter.take_broadcast_messages();
+
+                if let Some(monitor_tx) = &monitor_tx {
+                    if messages
+                        .iter()
+                        .any(|message| matches!(message, CeremonyMessage::CoordinatorReady))
+                    {
+                        monitor_tx
+                            .broadcast(MonitorProcessMessage::Ready)
+                            .map_err(|error| {
+                                eyre::eyre!("Error sending message to process monitor: {}", error)
+                            })?;
+                    }
+                }
+
+                if let Some(json_log_file) = &mut json_log_file {
+                    let record = CoordinatorLogRecord {
+                        ts: humantime::format_rfc3339_millis(read_time).to_string(),
+                        raw: line,
+                        event: messages.into_iter().next(),
+                    };
+                    write_log_record(json_log_file, &serde_json::to_vec(&record)?)?;
+                }
+            }
+            Err(error) => {
+                tracing::error!(
+                    "Error reading line from pipe to coordinator process: {}",
+                    error
+                )
+            }
+        }
+    }
+
+    for reader_join in reader_joins {
+        reader_join
+            .join()
+            .map_err(|_| eyre::eyre!("Coordinator output reader thread panicked"))?;
+    }
+
+    state_reporter.finish()?;
+
+    Ok(())
+}
+
+/// The state of a round, as recorded by the coordinator in the
+/// round's `state.json` file within the ceremony transcript.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RoundState {
+    /// The round number.
+    #[serde(default)]
+    pub height: Option<u64>,
+    /// The ids of the contributors in the round.
+    #[serde(rename = "contributorIds")]
+    pub contributor_ids: Vec<String>,
+    /// The ids of the verifiers in the round.
+    #[serde(rename = "verifierIds")]
+    pub verifier_ids: Vec<String>,
+    /// The state of each chunk in the round.
+    #[serde(default)]
+    pub chunks: Vec<ChunkState>,
+}
+
+/// The state of a chunk within a [RoundState].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChunkState {
+    #[serde(rename = "chunkId")]
+    pub chunk_id: u64,
+    /// The contributions made to this chunk, keyed by the
+    /// contribution number.
+    #[serde(default)]
+    pub contributions: BTreeMap<String, ContributionState>,
+}
+
+/// The state of a contribution to a chunk within a [ChunkState].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ContributionState {
+    /// The id of the contributor which made this contribution (see
+    /// [Contributor::id_on_coordinator()]), `None` for the initial
+    /// challenge.
+    #[serde(rename = "contributorId", default)]
+    pub contributor_id: Option<String>,
+    /// Whether this contribution has been verified.
+    #[serde(default)]
+    pub verified: bool,
+}
+
+/// The size of the chunks in which [tail_log()] reads the log file,
+/// from the end.
+const TAIL_LOG_CHUNK_SIZE: u64 = 8 * 1024;
+
+/// Read the last `n` lines of the coordinator's log file (see
+/// [CoordinatorConfig::log_file()]), e.g. to print when a test fails.
+/// If the file has fewer than `n` lines because it has just been
+/// rolled over (see [CoordinatorConfig::max_log_size]), the remaining
+/// lines are read from the end of the rolled over file. Returns all
+/// the lines if there are fewer than `n`. The files are read
+/// backwards from the end, so only the last `n` lines are held in
+/// memory.
+pub fn tail_log(config: &CoordinatorConfig, n: usize) -> eyre::Result<Vec<String>> {
+    tail_log_file(&config.log_file(), n)
+}
+
+/// Read the last `n` lines of the coordinator's `stderr` log file
+/// (see [CoordinatorConfig::stderr_log_file()]), in the same way as
+/// [tail_log()]. Returns no lines if the file does not exist, because
+/// the `stderr` is only logged separately on some platforms.
+pub fn tail_stderr_log(config: &CoordinatorConfig, n: usize) -> eyre::Result<Vec<String>> {
+    let stderr_log_file_path = config.stderr_log_file();
+    if !stderr_log_file_path.exists() {
+        return Ok(Vec::new());
+    }
+    tail_log_file(&stderr_log_file_path, n)
+}
+
+/// Read the last `n` lines of the log file at `log_file_path`,
+/// continuing into its rolled over file if required, see
+/// [tail_log()].
+fn tail_log_file(log_file_path: &Path, n: usize) -> eyre::Result<Vec<String>> {
+    let mut file = File::open(log_file_path)
+        .wrap_err_with(|| eyre::eyre!("Unable to open log file: {:?}", log_file_path))?;
+    let mut lines = tail_lines(&mut file, n)
+        .wrap_err_with(|| eyre::eyre!("Unable to read log file: {:?}", log_file_path))?;
+
+    let rolled_path = rolled_log_path(log_file_path);
+    if lines.len() < n && rolled_path.exists() {
+        let mut rolled_file = File::open(&rolled_path)
+            .wrap_err_with(|| eyre::eyre!("Unable to open log file: {:?}", rolled_path))?;
+        let mut rolled_lines = tail_lines(&mut rolled_file, n - lines.len())
+            .wrap_err_with(|| eyre::eyre!("Unable to read log file: {:?}", rolled_path))?;
+        rolled_lines.append(&mut lines);
+        lines = rolled_lines;
+    }
+
+    Ok(lines)
+}
+
+/// Read the last `n` lines of `file`, see [tail_log()].
+fn tail_lines(file: &mut File, n: usize) -> std::io::Result<Vec<String>> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    let len = file.seek(SeekFrom::End(0))?;
+    let mut start = len;
+    let mut tail: Vec<u8> = Vec::new();
+    let mut separators = 0;
+
+    // Once there are `n` newlines separating the lines (i.e. not
+    // counting a newline at the very end of the file), the last `n`
+    // lines are complete.
+    while start > 0 && separators < n {
+        let chunk_len = TAIL_LOG_CHUNK_SIZE.min(start);
+        start -= chunk_len;
+        file.seek(SeekFrom::Start(start))?;
+
+        let mut chunk = vec![0; chunk_len as usize];
+        file.read_exact(&mut chunk)?;
+        let chunk_end = if start + chunk_len == len {
+            chunk.strip_suffix(b"\n").unwrap_or(&chunk)
+        } else {
+            &chunk[..]
+        };
+        separators += chunk_end.iter().filter(|byte| **byte == b'\n').count();
+
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+    }
+
+    let tail = String::from_utf8_lossy(&tail);
+    let lines: Vec<&str> = tail.lines().collect();
+    Ok(lines[lines.len().saturating_sub(n)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Copy the transcript for the specified `round` from the
+/// [CoordinatorConfig::live_round_dir()] into the
+/// [CoordinatorConfig::round_dir()] if
+/// [CoordinatorConfig::per_round_out_dir] is enabled. The transcript
+/// is copied rather than moved, because the coordinator derives the
+/// next round from it.
+pub fn archive_round(config: &CoordinatorConfig, round: u64) -> eyre::Result<()> {
+    if !config.per_round_out_dir {
+        return Ok(());
+    }
+
+    let live_round_dir = config.live_round_dir(round);
+    let round_dir = config.round_dir(round);
+    copy_dir_all(&live_round_dir, &round_dir).wrap_err_with(|| {
+        eyre::eyre!(
+            "Unable to archive the transcript for round {} from {:?} to {:?}",
+            round,
+            live_round_dir,
+            round_dir
+        )
+    })
+}
+
+/// Read and deserialize the `state.json` file for the specified round
+/// of the ceremony transcript.
+pub fn read_round_state(config: &CoordinatorConfig, round: u64) -> eyre::Result<RoundState> {
+    let state_file = config.round_state_file(round);
 
     let state_file_str = std::fs::read_to_string(&state_file)
         .wrap_err_with(|| eyre::eyre!("Unable to read state file: {:?}", &state_file))?;
 
-    let state: RoundState = serde_json::from_str(&state_file_str)
-        .wrap_err_with(|| eyre::eyre!("Unable to deserialize state file: {:?}", state_file))?;
+    serde_json::from_str(&state_file_str)
+        .wrap_err_with(|| eyre::eyre!("Unable to deserialize state file: {:?}", state_file))
+}
+
+/// Check that the specified participants are in the specified round
+/// transcript. All the verifiers missing from the round are reported
+/// in the error. Returns the [RoundState] which was checked, see
+/// [read_round_state()].
+pub fn check_participants_in_round(
+    config: &CoordinatorConfig,
+    round: u64,
+    contributors: &[Contributor],
+    verifiers: &[Verifier],
+) -> eyre::Result<RoundState> {
+    let state = read_round_state(config, round)?;
+
+    for contributor in contributors {
+        check_contributor_id_in_round(&state, &contributor.id_on_coordinator())?;
+    }
+
+    let mut missing_verifier_ids: Vec<String> = Vec::new();
+    for verifier in verifiers {
+        let verifier_id = verifier.id_on_coordinator().ok_or_else(|| {
+            eyre::eyre!(
+                "The address of verifier {:?} is unknown, unable to check that it is in round {}",
+                verifier.id,
+                round
+            )
+        })?;
+
+        if !state.verifier_ids.contains(&verifier_id) {
+            missing_verifier_ids.push(verifier_id);
+        }
+    }
+
+    if !missing_verifier_ids.is_empty() {
+        return Err(eyre::eyre!(
+            "Unable to find verifiers {:?} in round state file",
+            missing_verifier_ids
+        ));
+    }
+
+    Ok(state)
+}
+
+/// Check that the specified participants are in the specified round
+/// transcript, as a contributor or as a verifier depending on the
+/// variant of each [ParticipantRef]. Returns the [RoundState] which
+/// was checked, see [read_round_state()].
+pub fn check_participant_refs_in_round(
+    config: &CoordinatorConfig,
+    round: u64,
+    participants: &[ParticipantRef],
+) -> eyre::Result<RoundState> {
+    let state = read_round_state(config, round)?;
+
+    for participant in participants {
+        match participant {
+            ParticipantRef::Contributor(contributor) => {
+                check_contributor_id_in_round(&state, &contributor.id_on_coordinator())?
+            }
+            ParticipantRef::Verifier(verifier) => {
+                check_verifier_id_in_round(&state, &verifier.id_on_coordinator())?
+            }
+        }
+    }
+
+    Ok(state)
+}
+
+/// Check that the contributor with the specified id (see
+/// [Contributor::id_on_coordinator()]) is in the round `state`.
+fn check_contributor_id_in_round(state: &RoundState, contributor_id: &str) -> eyre::Result<()> {
+    if state.contributor_ids.iter().any(|id| id == contributor_id) {
+        Ok(())
+    } else {
+        Err(eyre::eyre!(
+            "Unable to find contributor {} in round state file",
+            contributor_id
+        ))
+    }
+}
+
+/// Check that the verifier with the specified id (see
+/// [Verifier::id_on_coordinator()]) is in the round `state`.
+fn check_verifier_id_in_round(state: &RoundState, verifier_id: &str) -> eyre::Result<()> {
+    if state.verifier_ids.iter().any(|id| id == verifier_id) {
+        Ok(())
+    } else {
+        Err(eyre::eyre!(
+            "Unable to find verifier {} in round state file",
+            verifier_id
+        ))
+    }
+}
+
+/// Check that each of the specified `contributors` has made a
+/// contribution to every chunk in the specified round transcript, as
+/// recorded in [RoundState::chunks]. Returns an error listing the
+/// chunks that each contributor is missing a contribution to, and
+/// otherwise the [RoundState] which was checked, see
+/// [read_round_state()].
+pub fn check_contributions_complete(
+    config: &CoordinatorConfig,
+    round: u64,
+    contributors: &[Contributor],
+) -> eyre::Result<RoundState> {
+    let state = read_round_state(config, round)?;
+
+    if state.chunks.is_empty() {
+        return Err(eyre::eyre!(
+            "No chunks found in the state file for round {}",
+            round
+        ));
+    }
+
+    let gaps: Vec<String> = contributors
+        .iter()
+        .filter_map(|contributor| {
+            let contributor_id = contributor.id_on_coordinator();
+            let missing_chunks: Vec<u64> = state
+                .chunks
+                .iter()
+                .filter(|chunk| {
+                    !chunk.contributions.values().any(|contribution| {
+                        contribution.contributor_id.as_ref() == Some(&contributor_id)
+                    })
+                })
+                .map(|chunk| chunk.chunk_id)
+                .collect();
+
+            if missing_chunks.is_empty() {
+                None
+            } else {
+                Some(format!("{} (chunks {:?})", contributor_id, missing_chunks))
+            }
+        })
+        .collect();
+
+    if !gaps.is_empty() {
+        return Err(eyre::eyre!(
+            "Contributions missing from the state file for round {}: {}",
+            round,
+            gaps.join(", ")
+        ));
+    }
+
+    Ok(state)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        convert::TryFrom,
+        net::SocketAddr,
+        num::{NonZeroU16, NonZeroU64, NonZeroU8, NonZeroUsize},
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use mpmc_bus::{Bus, TryRecvError};
+
+    use super::{
+        archive_round, check_contributions_complete, check_participant_refs_in_round,
+        check_participants_in_round, classify_log_line, default_listen_address, launch_coordinator,
+        parse_successful_contribution, rolled_log_path, run_heartbeat, run_round_state_watcher,
+        run_stall_monitor, tail_log, tail_stderr_log, wait_for_transcript_dir, BacktraceCollector,
+        ConfigParseError, CoordinatorConfig, CoordinatorConfigProfile, CoordinatorLaunch,
+        CoordinatorStateHistory, CoordinatorStateReporter, CoordinatorTomlConfiguration,
+        CoordinatorVersion, EnvironmentParameters, LaunchMode, LogPatterns, PublicCoordinatorState,
+        ReliabilityCheckSettings, RotatingLogFile, RuntimeParameters, SqliteJournalMode,
+        TwitterSettings, VerifierSettings, DEFAULT_COORDINATOR_LOG_LEVEL,
+        DEFAULT_TRANSCRIPT_DIR_TIMEOUT, NUM_POWERS_RANGE, TWITTER_CONSUMER_SECRET_ENV,
+        TWITTER_CONSUMER_TOKEN_ENV,
+    };
+    use crate::{
+        bus::BroadcastRetryOptions, contributor::Contributor, util::TEST_ADDRESS,
+        verifier::Verifier, waiter::wait_for_message, AleoPublicKey, CeremonyMessage,
+        ContributorRef, Environment, ParticipantRef, ShutdownReason, VerifierRef,
+    };
+
+    // Used by the tests which run the coordinator (or a stand in for
+    // it) as a shell script.
+    #[cfg(unix)]
+    use std::{fs::File, os::unix::fs::PermissionsExt};
+
+    #[cfg(unix)]
+    use regex::Regex;
+    #[cfg(unix)]
+    use subprocess::{Exec, Popen, Redirection};
+
+    #[cfg(unix)]
+    use super::{
+        check_coordinator_version, coordinator_exec, monitor_coordinator,
+        parse_limited_exit_status, run_coordinator, validate_config, with_config_section,
+        CoordinatorBacktrace, CoordinatorJoin, CoordinatorLogOptions, CoordinatorLogRecord,
+        LogPatternOverrides, ReporterOptions, ResourceLimits, BOOTED_RE,
+    };
+    #[cfg(unix)]
+    use crate::{process::ProcessOutput, util::fake_bin};
+
+    /// A [CoordinatorConfig] for use in tests, with all the artifacts
+    /// stored in `out_dir`.
+    fn test_config(out_dir: &Path) -> CoordinatorConfig {
+        let mut config = CoordinatorConfig::builder()
+            .crate_dir(out_dir)
+            .setup_coordinator_bin(out_dir.join("aleo-setup-coordinator"))
+            .environment(Environment::Development)
+            .out_dir(out_dir)
+            .build()
+            .unwrap();
+        // Don't depend on the environment the tests are run in.
+        config.twitter_settings = None;
+        config
+    }
+
+    /// Serialize the [CoordinatorTomlConfiguration] for the `config`,
+    /// and parse it back as a [toml::Value], so that the tests can
+    /// index into its sections.
+    fn toml_config_value(config: &CoordinatorConfig) -> toml::Value {
+        let toml_config =
+            toml::to_string_pretty(&CoordinatorTomlConfiguration::try_from(config).unwrap())
+                .unwrap();
+        toml::from_str(&toml_config).unwrap()
+    }
+
+    /// Test building a [CoordinatorConfig] with only the required
+    /// fields, and that each of them is required.
+    #[test]
+    fn test_builder_minimal() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let builder = CoordinatorConfig::builder()
+            .crate_dir(out_dir.path())
+            .setup_coordinator_bin(out_dir.path().join("aleo-setup-coordinator"))
+            .environment(Environment::Inner)
+            .out_dir(out_dir.path().join("coordinator"));
+
+        let config = builder.clone().build().unwrap();
+        assert_eq!(out_dir.path(), config.crate_dir);
+        assert_eq!(Environment::Inner, config.environment);
+        assert_eq!(out_dir.path().join("coordinator"), config.out_dir);
+        assert_eq!(config.out_dir, config.working_dir());
+        assert_eq!(
+            DEFAULT_TRANSCRIPT_DIR_TIMEOUT,
+            config.transcript_dir_timeout
+        );
+        assert_eq!(default_listen_address(), config.listen_address);
+        assert_eq!(DEFAULT_COORDINATOR_LOG_LEVEL, config.log_level);
+        assert_eq!(None, config.max_rounds);
+        assert!(config.replacement_contributors.is_empty());
+        assert!(config.shutdown_on_rollback);
+
+        let error = CoordinatorConfig::builder()
+            .crate_dir(out_dir.path())
+            .environment(Environment::Inner)
+            .out_dir(out_dir.path())
+            .build()
+            .unwrap_err();
+        assert!(error.to_string().contains("setup_coordinator_bin"));
+
+        assert!(CoordinatorConfig::builder().build().is_err());
+    }
+
+    /// Test building a [CoordinatorConfig] with every field specified.
+    #[test]
+    fn test_builder_fully_specified() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let listen_address: SocketAddr = "127.0.0.1:9100".parse().unwrap();
+        let config = CoordinatorConfig::builder()
+            .crate_dir(out_dir.path())
+            .setup_coordinator_bin(out_dir.path().join("aleo-setup-coordinator"))
+            .environment(Environment::Universal)
+            .out_dir(out_dir.path())
+            .working_dir(out_dir.path().join("working"))
+            .replacement_contributors(vec![ContributorRef {
+                address: TEST_ADDRESS.parse().unwrap(),
+            }])
+            .transcript_dir_timeout(Duration::from_secs(1))
+            .environment_parameters(EnvironmentParameters::default())
+            .max_rounds(2)
+            .launch_mode(LaunchMode::Stdin)
+            .eof_grace(Duration::from_secs(2))
+            .terminate_grace(Duration::from_secs(3))
+            .startup_retries(4)
+            .startup_backoff(Duration::from_secs(5))
+            .num_powers(12)
+            .check_version(true)
+            .broadcast_retry(BroadcastRetryOptions::default())
+            .per_round_out_dir(true)
+            .sqlite_journal_mode(SqliteJournalMode::Wal)
+            .listen_address(listen_address)
+            .sqlite_file(PathBuf::from("custom.db3"))
+            .runtime_parameters(RuntimeParameters::default())
+            .reliability_check(ReliabilityCheckSettings::default())
+            .verifier_settings(VerifierSettings::default())
+            .log_level("trace")
+            .max_log_size(1024)
+            .json_log(true)
+            .log_timestamps(true)
+            .stall_timeout(Duration::from_secs(6))
+            .round_state_poll_interval(Duration::from_millis(50))
+            .heartbeat_interval(Duration::from_secs(7))
+            .twitter_settings(TwitterSettings {
+                consumer_token: "token".to_string(),
+                consumer_secret: "secret".to_string(),
+            })
+            .shutdown_on_rollback(false)
+            .dry_run(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(Environment::Universal, config.environment);
+        assert_eq!(out_dir.path().join("working"), config.working_dir());
+        assert_eq!(1, config.replacement_contributors.len());
+        assert_eq!(Duration::from_secs(1), config.transcript_dir_timeout);
+        assert_eq!(
+            Some(EnvironmentParameters::default()),
+            config.environment_parameters
+        );
+        assert_eq!(Some(2), config.max_rounds);
+        assert!(matches!(config.launch_mode, LaunchMode::Stdin));
+        assert_eq!(Duration::from_secs(2), config.eof_grace);
+        assert_eq!(Duration::from_secs(3), config.terminate_grace);
+        assert_eq!(4, config.startup_retries);
+        assert_eq!(Duration::from_secs(5), config.startup_backoff);
+        assert_eq!(Some(12), config.num_powers);
+        assert!(config.check_version);
+        assert!(config.per_round_out_dir);
+        assert_eq!(Some(SqliteJournalMode::Wal), config.sqlite_journal_mode);
+        assert_eq!(listen_address, config.listen_address);
+        assert_eq!(Some(PathBuf::from("custom.db3")), config.sqlite_file);
+        assert_eq!(
+            Some(RuntimeParameters::default()),
+            config.runtime_parameters
+        );
+        assert_eq!(
+            Some(ReliabilityCheckSettings::default()),
+            config.reliability_check
+        );
+        assert_eq!(Some(VerifierSettings::default()), config.verifier_settings);
+        assert_eq!("trace", config.log_level);
+        assert_eq!(Some(1024), config.max_log_size);
+        assert!(config.json_log);
+        assert!(config.log_timestamps);
+        assert_eq!(Some(Duration::from_secs(6)), config.stall_timeout);
+        assert_eq!(
+            Some(Duration::from_millis(50)),
+            config.round_state_poll_interval
+        );
+        assert_eq!(Some(Duration::from_secs(7)), config.heartbeat_interval);
+        assert!(config.twitter_settings.is_some());
+        assert!(!config.shutdown_on_rollback);
+        assert!(config.dry_run);
+    }
+
+    /// Start a fake coordinator process which runs the specified
+    /// shell `script`, returning the process and its `stdout`.
+    #[cfg(unix)]
+    fn fake_coordinator(script: &str) -> (Popen, File) {
+        let mut process = Exec::cmd("sh")
+            .arg("-c")
+            .arg(script)
+            .stdout(Redirection::Pipe)
+            .stderr(Redirection::Merge)
+            .popen()
+            .unwrap();
+        let stdout = process.stdout.take().unwrap();
+        (process, stdout)
+    }
+
+    /// Test that a coordinator which refuses to start because of an
+    /// existing lock produces an error explaining the problem.
+    #[cfg(unix)]
+    #[test]
+    fn test_startup_lock_detected() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Error: SqliteFailure(DatabaseBusy, \"database is locked\")'; exit 1",
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let error = monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap_err();
+        process.wait().unwrap();
+
+        assert!(error.to_string().contains("existing lock"));
+
+        let log = std::fs::read_to_string(out_dir.path().join("coordinator.log")).unwrap();
+        assert_eq!(
+            vec!["Error: SqliteFailure(DatabaseBusy, \"database is locked\")"],
+            log.lines().collect::<Vec<_>>()
+        );
+    }
+
+    /// Test that a coordinator which rejects its configuration at
+    /// startup is reported as a [ConfigParseError], including the
+    /// relevant section of the configuration.
+    #[cfg(unix)]
+    #[test]
+    fn test_config_parse_error_detected() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Error: unknown field `is_enabled`, expected one of `accept_threshold`'; exit 1",
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let error = monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap_err();
+        process.wait().unwrap();
+
+        let toml_config = toml::to_string_pretty(
+            &CoordinatorTomlConfiguration::try_from(&test_config(out_dir.path())).unwrap(),
+        )
+        .unwrap();
+        let error = with_config_section(error, &out_dir.path().join("config.toml"), &toml_config);
+
+        let parse_error = error.downcast_ref::<ConfigParseError>().unwrap();
+        let section = parse_error.relevant_section(&toml_config).unwrap();
+        assert!(section.starts_with("[reliability_check]"));
+        assert!(section.contains("is_enabled = false"));
+        assert!(!section.contains("[twitter_settings]"));
+
+        let message = error.to_string();
+        assert!(message.contains("Invalid coordinator configuration"));
+        assert!(message.contains("[reliability_check]"));
+
+        let log = std::fs::read_to_string(out_dir.path().join("coordinator.log")).unwrap();
+        assert_eq!(
+            vec!["Error: unknown field `is_enabled`, expected one of `accept_threshold`"],
+            log.lines().collect::<Vec<_>>()
+        );
+    }
+
+    /// Test that a config parse error before the coordinator has
+    /// booted is broadcast as a
+    /// [ShutdownReason::CoordinatorConfigRejected], while the same
+    /// output at runtime is not treated as a config error.
+    #[test]
+    fn test_config_parse_error_shutdown() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+
+        let line = "Error: failed to parse config: unknown variant `production`, \
+            expected one of `development`, `inner`, `outer`, `universal`";
+        let error = reporter.parse_output_line(line).unwrap_err();
+        let expected_error = ConfigParseError {
+            line: line.to_string(),
+        };
+        assert_eq!(
+            Some(&expected_error),
+            error.downcast_ref::<ConfigParseError>()
+        );
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![CeremonyMessage::Shutdown(
+                ShutdownReason::CoordinatorConfigRejected(expected_error)
+            )],
+            messages
+        );
+
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+        reporter
+            .parse_output_line("Coordinator has booted up")
+            .unwrap();
+        reporter.parse_output_line(line).unwrap();
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(!messages
+            .iter()
+            .any(|message| matches!(message, CeremonyMessage::Shutdown(_))));
+    }
+
+    /// Test finding the relevant section of the configuration from a
+    /// line number in the error.
+    #[test]
+    fn test_config_parse_error_line_number() {
+        let toml_config = "setup = \"development\"\n\n[runtime_parameters]\na = 1\nb = 2\n\n[verifier_settings]\nc = 3\n";
+        let error = ConfigParseError {
+            line: "TOML parse error at line 5, column 5".to_string(),
+        };
+        assert_eq!(
+            Some("[runtime_parameters]\na = 1\nb = 2".to_string()),
+            error.relevant_section(toml_config)
+        );
+
+        let error = ConfigParseError {
+            line: "TOML parse error at line 50, column 5".to_string(),
+        };
+        assert_eq!(None, error.relevant_section(toml_config));
+    }
+
+    /// Test that the test is told to shut down as soon as the last
+    /// expected round has finished, instead of waiting for
+    /// participants in the next round.
+    #[cfg(unix)]
+    #[test]
+    fn test_max_rounds_shutdown() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Coordinator has booted up'; \
+            for round in 1 2; do \
+                echo \"Advanced ceremony to round $round\"; \
+                echo \"Starting aggregation on round $round\"; \
+                echo \"Round $round is aggregated\"; \
+                echo \"Round $round is finished\"; \
+                echo 'Waiting for participants'; \
+            done",
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions {
+                max_rounds: Some(2),
+                ..ReporterOptions::default()
+            },
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let mut messages = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => panic!("`rx` disconnected"),
+            }
+        }
+
+        assert_eq!(
+            CeremonyMessage::RoundFinished(2),
+            messages[messages.len() - 3]
+        );
+        assert!(matches!(
+            messages[messages.len() - 2],
+            CeremonyMessage::RoundTimings { round: 2, .. }
+        ));
+        assert_eq!(
+            CeremonyMessage::Shutdown(ShutdownReason::TestFinished),
+            messages[messages.len() - 1]
+        );
+        assert!(messages.contains(&CeremonyMessage::RoundWaitingForParticipants(2)));
+        assert!(!messages.contains(&CeremonyMessage::RoundWaitingForParticipants(3)));
+
+        state_history
+            .assert_state_sequence(&[
+                PublicCoordinatorState::ProcessStarted,
+                PublicCoordinatorState::RoundWaitingForParticipants(1),
+                PublicCoordinatorState::RoundRunning(1),
+                PublicCoordinatorState::RoundAggregating(1),
+                PublicCoordinatorState::RoundWaitingForFinish(1),
+                PublicCoordinatorState::RoundFinished(1),
+                PublicCoordinatorState::RoundWaitingForParticipants(2),
+                PublicCoordinatorState::RoundRunning(2),
+                PublicCoordinatorState::RoundAggregating(2),
+                PublicCoordinatorState::RoundWaitingForFinish(2),
+                PublicCoordinatorState::LastRoundFinished(2),
+            ])
+            .unwrap();
+    }
+
+    /// Test that the monitor does not deadlock when the ceremony bus
+    /// is full because a receiver is not consuming its messages.
+    #[cfg(unix)]
+    #[test]
+    fn test_full_bus_does_not_deadlock() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Coordinator has booted up'; \
+            for round in 1 2 3 4 5; do \
+                echo \"Advanced ceremony to round $round\"; \
+                echo \"Starting aggregation on round $round\"; \
+                echo \"Round $round is aggregated\"; \
+                echo \"Round $round is finished\"; \
+                echo 'Waiting for participants'; \
+            done",
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(2);
+        // Subscribed, but never consumed.
+        let _rx = bus.subscribe();
+        let ceremony_tx = bus.broadcaster();
+        let log_file_path = out_dir.path().join("coordinator.log");
+        let broadcast_retry = BroadcastRetryOptions {
+            timeout: Duration::from_millis(50),
+            retry_interval: Duration::from_millis(5),
+            drop_on_timeout: true,
+        };
+
+        let (result_tx, result_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let result = monitor_coordinator(
+                ProcessOutput {
+                    stdout,
+                    stderr: None,
+                },
+                ceremony_tx,
+                None,
+                CoordinatorLogOptions::new(log_file_path),
+                ReporterOptions::default(),
+                CoordinatorStateHistory::default(),
+                broadcast_retry,
+            );
+            result_tx.send(result).unwrap();
+        });
+
+        result_rx
+            .recv_timeout(Duration::from_secs(10))
+            .expect("monitor deadlocked on a full bus")
+            .unwrap();
+        process.wait().unwrap();
+    }
+
+    /// Test the round progress calculated from a partially complete
+    /// round.
+    #[test]
+    fn test_round_progress() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+
+        reporter
+            .parse_output_line("Coordinator has booted up")
+            .unwrap();
+        reporter
+            .parse_output_line("Advanced ceremony to round 1")
+            .unwrap();
+        assert_eq!(0.0, state_history.round_progress());
+
+        // 2 chunks, 2 contributors.
+        state_history.set_expected_contributions(1, 4);
+
+        for chunk in 0..3 {
+            reporter
+                .parse_output_line(&format!(
+                    "{}.contributor added a contribution to chunk {}",
+                    TEST_ADDRESS, chunk
+                ))
+                .unwrap();
+        }
+        reporter
+            .parse_output_line(&format!("{}.verifier verified chunk 0", TEST_ADDRESS))
+            .unwrap();
+        reporter
+            .parse_output_line(&format!("{}.verifier verified chunk 1", TEST_ADDRESS))
+            .unwrap();
+
+        let progress = state_history.progress();
+        assert_eq!(3, progress.contributions);
+        assert_eq!(2, progress.verifications);
+        assert!((state_history.round_progress() - 62.5).abs() < f64::EPSILON);
+    }
+
+    /// Test that the coordinator entering and leaving maintenance mode
+    /// is reported, and that the round continues from where it was
+    /// when the coordinator resumes.
+    #[test]
+    fn test_coordinator_pause_resume() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            "INFO aleo_setup_coordinator: Entering maintenance mode",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert!(state_history.is_paused());
+
+        for line in &[
+            "INFO aleo_setup_coordinator: Leaving maintenance mode",
+            "Starting aggregation on round 1",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert!(!state_history.is_paused());
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+                CeremonyMessage::RoundStarted(1),
+                CeremonyMessage::CoordinatorPaused,
+                CeremonyMessage::CoordinatorResumed,
+                CeremonyMessage::RoundStartedAggregation(1),
+            ],
+            messages
+        );
+    }
+
+    /// Test that a custom pattern overrides the built-in one, while
+    /// the patterns which aren't overridden are still used, including
+    /// after the coordinator's version has been detected.
+    #[cfg(unix)]
+    #[test]
+    fn test_log_pattern_overrides() {
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Starting aleo-setup-coordinator v0.2.1'; \
+            echo 'Coordinator has booted up'; \
+            echo 'Coordinator v2 is ready'; \
+            echo 'Advanced ceremony to round 1'",
+        );
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions {
+                log_pattern_overrides: LogPatternOverrides {
+                    booted: Some(Regex::new(".*Coordinator v2 is ready.*").unwrap()),
+                    ..LogPatternOverrides::default()
+                },
+                ..ReporterOptions::default()
+            },
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+                CeremonyMessage::RoundStarted(1),
+            ],
+            messages
+        );
+    }
+
+    /// Test that the log patterns are selected from the version
+    /// reported by the coordinator, and used to parse its
+    /// contribution lines.
+    #[test]
+    fn test_log_patterns_for_version() {
+        let line = format!(
+            "Contributor {}.contributor contributed to chunk 3",
+            TEST_ADDRESS
+        );
+
+        let version: CoordinatorVersion = "0.2.1".parse().unwrap();
+        let (contributor, chunk) =
+            parse_successful_contribution(&LogPatterns::for_version(&version), &line)
+                .unwrap()
+                .unwrap();
+        assert_eq!(TEST_ADDRESS, contributor.address.to_string());
+        assert_eq!(3, chunk);
+        assert!(
+            parse_successful_contribution(&LogPatterns::default(), &line)
+                .unwrap()
+                .is_none()
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+        for output_line in &[
+            "Starting aleo-setup-coordinator v0.2.1",
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            &line,
+        ] {
+            reporter.parse_output_line(output_line).unwrap();
+        }
+
+        let expected = CeremonyMessage::SuccessfulContribution {
+            contributor,
+            chunk: 3,
+            seq: 0,
+        };
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(messages.contains(&expected));
+        assert_eq!(Some(version), state_history.coordinator_version());
+    }
+
+    /// Test that round events reported for the tracked round advance
+    /// the state, and that an event for a different round is
+    /// rejected.
+    #[test]
+    fn test_round_number_checked() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            "Starting aggregation on round 1",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundAggregating(1)),
+            state_history.current_state()
+        );
+
+        let error = reporter
+            .parse_output_line("Round 2 is aggregated")
+            .unwrap_err();
+        assert!(error.to_string().contains("round 2"));
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundAggregating(1)),
+            state_history.current_state()
+        );
+    }
+
+    /// Test that with [CoordinatorConfig::per_round_out_dir] enabled,
+    /// the transcript for a round is copied into its own directory
+    /// before [CeremonyMessage::RoundFinished] is broadcast, and that
+    /// [archive_round()] does nothing otherwise.
+    #[test]
+    fn test_round_archived_on_finish() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(out_dir.path());
+        let live_chunk_dir = config.live_round_dir(1).join("chunk_0");
+        std::fs::create_dir_all(&live_chunk_dir).unwrap();
+        std::fs::write(live_chunk_dir.join("contribution_0.verified"), "challenge").unwrap();
+        std::fs::write(
+            config.live_round_dir(1).join("round_1.verified"),
+            "aggregated",
+        )
+        .unwrap();
+
+        archive_round(&config, 1).unwrap();
+        assert!(!config.round_out_dir(1).join("round_1").exists());
+
+        config.per_round_out_dir = true;
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+        reporter.round_archive = Some(config.clone());
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            "Starting aggregation on round 1",
+            "Round 1 is aggregated",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert!(!config.round_dir(1).exists());
+
+        reporter.parse_output_line("Round 1 is finished").unwrap();
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(messages.contains(&CeremonyMessage::RoundFinished(1)));
+        assert_eq!(
+            "challenge",
+            std::fs::read_to_string(config.round_dir(1).join("chunk_0/contribution_0.verified"))
+                .unwrap()
+        );
+        assert_eq!(
+            "aggregated",
+            std::fs::read_to_string(config.round_dir(1).join("round_1.verified")).unwrap()
+        );
+        // The coordinator's own transcript is left in place.
+        assert!(config.live_round_dir(1).join("round_1.verified").is_file());
+    }
+
+    /// Test that the time spent in each phase of a round is measured
+    /// with the reporter's clock, and broadcast once the round has
+    /// finished.
+    #[test]
+    fn test_round_timings() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let clock_now = now.clone();
+        reporter.clock = Arc::new(move || *clock_now.lock().unwrap());
+
+        reporter
+            .parse_output_line("Coordinator has booted up")
+            .unwrap();
+        for (seconds, line) in &[
+            (1, "Advanced ceremony to round 1"),
+            (2, "Starting aggregation on round 1"),
+            (3, "Round 1 is aggregated"),
+            (4, "Round 1 is finished"),
+        ] {
+            *now.lock().unwrap() += Duration::from_secs(*seconds);
+            reporter.parse_output_line(line).unwrap();
+        }
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            &[
+                CeremonyMessage::RoundFinished(1),
+                CeremonyMessage::RoundTimings {
+                    round: 1,
+                    waiting: Duration::from_secs(1),
+                    running: Duration::from_secs(2),
+                    aggregating: Duration::from_secs(3),
+                    finishing: Duration::from_secs(4),
+                },
+            ],
+            &messages[messages.len() - 2..]
+        );
+    }
+
+    /// Test that an event reported late for a round which has already
+    /// completed is ignored, but that an event for a round which was
+    /// never started is an error.
+    #[test]
+    fn test_round_event_for_untracked_round() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            "Starting aggregation on round 1",
+            "Round 1 is aggregated",
+            "Round 1 is finished",
+            "Waiting for participants",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundWaitingForParticipants(2)),
+            state_history.current_state()
+        );
+        // Drain the messages up to the end of round 1.
+        std::iter::from_fn(|| rx.try_recv().ok()).for_each(drop);
+
+        reporter.parse_output_line("Round 1 is aggregated").unwrap();
+        reporter.parse_output_line("Round 1 is finished").unwrap();
+        assert!(std::iter::from_fn(|| rx.try_recv().ok()).next().is_none());
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundWaitingForParticipants(2)),
+            state_history.current_state()
+        );
+
+        assert!(reporter.parse_output_line("Round 5 is aggregated").is_err());
+    }
+
+    /// Test that the events of a round which starts while the
+    /// previous round is still aggregating are routed to the round
+    /// they are for.
+    #[test]
+    fn test_interleaved_rounds() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+        // A stopped clock, so that the round timings are predictable.
+        let now = Instant::now();
+        reporter.clock = Arc::new(move || now);
+        let zero_timings = |round| CeremonyMessage::RoundTimings {
+            round,
+            waiting: Duration::from_secs(0),
+            running: Duration::from_secs(0),
+            aggregating: Duration::from_secs(0),
+            finishing: Duration::from_secs(0),
+        };
+
+        let contribution = format!(
+            "INFO phase1_coordinator: {}.contributor added a contribution to chunk 0",
+            TEST_ADDRESS
+        );
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            &contribution,
+            "Starting aggregation on round 1",
+            "Advanced ceremony to round 2",
+            &contribution,
+            "Aggregated chunk 1 of 1",
+            "Round 1 is aggregated",
+            "Round 1 is finished",
+            "Starting aggregation on round 2",
+            "Round 2 is aggregated",
+            "Round 2 is finished",
+            "Waiting for participants",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+
+        let contributor = ContributorRef {
+            address: TEST_ADDRESS.parse().unwrap(),
+        };
+        let successful_contribution = CeremonyMessage::SuccessfulContribution {
+            contributor,
+            chunk: 0,
+            seq: 0,
+        };
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+                CeremonyMessage::RoundStarted(1),
+                successful_contribution.clone(),
+                CeremonyMessage::RoundStartedAggregation(1),
+                CeremonyMessage::RoundWaitingForParticipants(2),
+                CeremonyMessage::RoundStarted(2),
+                successful_contribution,
+                CeremonyMessage::AggregationProgress {
+                    round: 1,
+                    done: 1,
+                    total: 1
+                },
+                CeremonyMessage::RoundAggregated(1),
+                CeremonyMessage::RoundFinished(1),
+                zero_timings(1),
+                CeremonyMessage::RoundStartedAggregation(2),
+                CeremonyMessage::RoundAggregated(2),
+                CeremonyMessage::RoundFinished(2),
+                zero_timings(2),
+                CeremonyMessage::RoundWaitingForParticipants(3),
+            ],
+            messages
+        );
+
+        state_history
+            .assert_state_sequence(&[
+                PublicCoordinatorState::ProcessStarted,
+                PublicCoordinatorState::RoundWaitingForParticipants(1),
+                PublicCoordinatorState::RoundRunning(1),
+                PublicCoordinatorState::RoundAggregating(1),
+                PublicCoordinatorState::RoundWaitingForParticipants(2),
+                PublicCoordinatorState::RoundRunning(2),
+                PublicCoordinatorState::RoundWaitingForFinish(1),
+                PublicCoordinatorState::RoundFinished(1),
+                PublicCoordinatorState::RoundAggregating(2),
+                PublicCoordinatorState::RoundWaitingForFinish(2),
+                PublicCoordinatorState::RoundFinished(2),
+                PublicCoordinatorState::RoundWaitingForParticipants(3),
+            ])
+            .unwrap();
+    }
+
+    /// Test that a round can't start while the previous round is
+    /// still running, because its events could not be told apart.
+    #[test]
+    fn test_round_started_while_previous_running() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+
+        for line in &["Coordinator has booted up", "Advanced ceremony to round 1"] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        let error = reporter
+            .parse_output_line("Advanced ceremony to round 2")
+            .unwrap_err();
+        assert!(error.to_string().contains("round 2"));
+    }
+
+    /// Test that a snapshot of the ceremony's state can be read from
+    /// another thread while the output is being parsed.
+    #[test]
+    fn test_state_snapshot() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+        assert!(state_history.round_states().is_empty());
+
+        for line in &["Coordinator has booted up", "Advanced ceremony to round 1"] {
+            reporter.parse_output_line(line).unwrap();
+        }
+
+        let snapshot_history = state_history.clone();
+        let (current_state, round_states) = std::thread::spawn(move || {
+            (
+                snapshot_history.current_state(),
+                snapshot_history.round_states(),
+            )
+        })
+        .join()
+        .unwrap();
+
+        let state = current_state.unwrap();
+        assert_eq!(PublicCoordinatorState::RoundRunning(1), state);
+        assert_eq!("round 1 running", state.to_string());
+        assert_eq!(
+            vec![(1, PublicCoordinatorState::RoundRunning(1))],
+            round_states.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    /// Test that a round being rolled back is reported as
+    /// [CeremonyMessage::RoundRolledBack], and only shuts down the
+    /// ceremony if [CoordinatorConfig::shutdown_on_rollback] is set.
+    #[test]
+    fn test_round_rolled_back() {
+        let rollback_line = "WARN phase1_coordinator: No contributors remaining to reset and \
+            complete the current round. Rolling back to round 0 to wait and accept new \
+            participants";
+
+        for shutdown_on_rollback in &[true, false] {
+            let bus = Bus::<CeremonyMessage>::new(100);
+            let mut rx = bus.subscribe();
+            let state_history = CoordinatorStateHistory::default();
+            let mut reporter = CoordinatorStateReporter::process_started(
+                bus.broadcaster(),
+                None,
+                state_history.clone(),
+                BroadcastRetryOptions::default(),
+            );
+            reporter.shutdown_on_rollback = *shutdown_on_rollback;
+
+            for line in &[
+                "Coordinator has booted up",
+                "Advanced ceremony to round 1",
+                rollback_line,
+            ] {
+                reporter.parse_output_line(line).unwrap();
+            }
+            assert_eq!(
+                Some(PublicCoordinatorState::RoundRolledBack(1)),
+                state_history.current_state()
+            );
+
+            let messages: Vec<CeremonyMessage> =
+                std::iter::from_fn(|| rx.try_recv().ok()).collect();
+            assert!(messages.contains(&CeremonyMessage::RoundRolledBack(1)));
+            assert_eq!(
+                *shutdown_on_rollback,
+                messages.contains(&CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            );
+
+            reporter
+                .parse_output_line("Waiting for participants")
+                .unwrap();
+            assert_eq!(
+                Some(PublicCoordinatorState::RoundWaitingForParticipants(1)),
+                state_history.current_state()
+            );
+        }
+    }
+
+    /// Test that after round 1 is rolled back, round 1 is tracked
+    /// again as waiting for participants, and can then be restarted
+    /// and run.
+    #[test]
+    fn test_round_1_rolled_back() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+        reporter.shutdown_on_rollback = false;
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            "WARN phase1_coordinator: No contributors remaining to reset and complete the \
+                current round. Rolling back to round 0 to wait and accept new participants",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        // Drain the messages up to the rollback.
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(Some(&CeremonyMessage::RoundRolledBack(1)), messages.last());
+
+        for line in &["Waiting for participants", "Advanced ceremony to round 1"] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::RoundWaitingForParticipants(1),
+                CeremonyMessage::RoundStarted(1),
+            ],
+            messages
+        );
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundRunning(1)),
+            state_history.current_state()
+        );
+    }
+
+    /// Test that a round restarted mid-round with contributors
+    /// remaining is reported as [CeremonyMessage::RoundRestarted],
+    /// discarding the contributions made so far, and that the round
+    /// continues running.
+    #[test]
+    fn test_round_restarted() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+
+        let contribution = format!(
+            "INFO phase1_coordinator: {}.contributor added a contribution to chunk 0",
+            TEST_ADDRESS
+        );
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            &contribution,
+            "INFO phase1_coordinator: Resetting current round 1 with 2 contributors remaining",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundRunning(1)),
+            state_history.current_state()
+        );
+        assert!(state_history.chunk_contributions().is_empty());
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(Some(&CeremonyMessage::RoundRestarted(1)), messages.last());
+        assert!(!messages
+            .iter()
+            .any(|message| matches!(message, CeremonyMessage::Shutdown(_))));
+
+        // The contribution is made again after the restart, and is not
+        // a duplicate.
+        reporter.parse_output_line(&contribution).unwrap();
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![CeremonyMessage::SuccessfulContribution {
+                contributor: ContributorRef {
+                    address: TEST_ADDRESS.parse().unwrap(),
+                },
+                chunk: 0,
+                seq: 0,
+            }],
+            messages
+        );
+
+        // A restart of a round other than the running round is
+        // ignored.
+        reporter
+            .parse_output_line("INFO phase1_coordinator: Resetting current round 2")
+            .unwrap();
+        assert!(std::iter::from_fn(|| rx.try_recv().ok()).next().is_none());
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundRunning(1)),
+            state_history.current_state()
+        );
+
+        // Other lines which mention restarting a round are not
+        // treated as the coordinator restarting the round.
+        assert_eq!(
+            None,
+            classify_log_line("INFO phase1_coordinator: Contributor asked to restart round 1")
+        );
+    }
+
+    /// Test that the contributions to each chunk are counted, and
+    /// that the counts are reset when the next round begins.
+    #[test]
+    fn test_chunk_contributions() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let _rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+
+        let contribution = |chunk: u64| {
+            format!(
+                "INFO phase1_coordinator: {}.contributor added a contribution to chunk {}",
+                TEST_ADDRESS, chunk
+            )
+        };
+        for line in &[
+            "Coordinator has booted up".to_string(),
+            "Advanced ceremony to round 1".to_string(),
+            contribution(0),
+            contribution(1),
+            contribution(0),
+            contribution(1),
+            contribution(0),
+            "Starting aggregation on round 1".to_string(),
+            "Round 1 is aggregated".to_string(),
+            "Round 1 is finished".to_string(),
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+
+        let chunk_contributions = state_history.chunk_contributions();
+        assert_eq!(2, chunk_contributions.len());
+        assert_eq!(Some(&3), chunk_contributions.get(&0));
+        assert_eq!(Some(&2), chunk_contributions.get(&1));
+
+        reporter
+            .parse_output_line("Waiting for participants")
+            .unwrap();
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundWaitingForParticipants(2)),
+            state_history.current_state()
+        );
+        assert!(state_history.chunk_contributions().is_empty());
+    }
+
+    /// Test that after a round has finished, the next round is not
+    /// reported as waiting for participants until the coordinator
+    /// confirms it.
+    #[test]
+    fn test_next_round_waits_for_confirmation() {
+        let waiting_messages = |rx: &mut mpmc_bus::Receiver<CeremonyMessage>| -> Vec<u64> {
+            std::iter::from_fn(|| rx.try_recv().ok())
+                .filter_map(|message| match message {
+                    CeremonyMessage::RoundWaitingForParticipants(round) => Some(round),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            "Starting aggregation on round 1",
+            "Round 1 is aggregated",
+            "Round 1 is finished",
+            "DEBUG rocket: GET /v1/queue/contributor/heartbeat",
+            "INFO phase1_coordinator: Waiting for participants for round 3",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert_eq!(vec![1], waiting_messages(&mut rx));
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundFinished(1)),
+            state_history.current_state()
+        );
+
+        reporter
+            .parse_output_line("INFO phase1_coordinator: Waiting for participants for round 2")
+            .unwrap();
+        assert_eq!(vec![2], waiting_messages(&mut rx));
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundWaitingForParticipants(2)),
+            state_history.current_state()
+        );
+
+        // The next round starting also confirms that it was waiting
+        // for participants.
+        for line in &[
+            "Advanced ceremony to round 2",
+            "Starting aggregation on round 2",
+            "Round 2 is aggregated",
+            "Round 2 is finished",
+            "Advanced ceremony to round 3",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(messages.ends_with(&[
+            CeremonyMessage::RoundWaitingForParticipants(3),
+            CeremonyMessage::RoundStarted(3),
+        ]));
+    }
+
+    /// Test that a contributor contributing to the same chunk twice
+    /// in a round is reported, and that this is reset for the next
+    /// round.
+    #[test]
+    fn test_duplicate_contribution() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+
+        let contribution = format!(
+            "INFO phase1_coordinator: {}.contributor added a contribution to chunk 0",
+            TEST_ADDRESS
+        );
+        let contributor = ContributorRef {
+            address: TEST_ADDRESS.parse().unwrap(),
+        };
+        let duplicate = CeremonyMessage::DuplicateContribution {
+            contributor,
+            chunk: 0,
+        };
+        let count_duplicates = |messages: &[CeremonyMessage]| {
+            messages
+                .iter()
+                .filter(|message| *message == &duplicate)
+                .count()
+        };
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            &contribution,
+            &contribution,
+            "Starting aggregation on round 1",
+            "Round 1 is aggregated",
+            "Round 1 is finished",
+            "Waiting for participants",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(1, count_duplicates(&messages));
+
+        for line in &["Advanced ceremony to round 2", &contribution] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(0, count_duplicates(&messages));
+    }
+
+    /// Test that each contribution in a round is broadcast with the
+    /// next `seq`, and that this is reset for the next round.
+    #[test]
+    fn test_contribution_seq() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+
+        let contribution = |chunk: u64| {
+            format!(
+                "INFO phase1_coordinator: {}.contributor added a contribution to chunk {}",
+                TEST_ADDRESS, chunk
+            )
+        };
+        let seqs = |rx: &mut mpmc_bus::Receiver<CeremonyMessage>| -> Vec<u64> {
+            std::iter::from_fn(|| rx.try_recv().ok())
+                .filter_map(|message| match message {
+                    CeremonyMessage::SuccessfulContribution { seq, .. } => Some(seq),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            &contribution(0),
+            &contribution(1),
+            &contribution(2),
+            "Starting aggregation on round 1",
+            "Round 1 is aggregated",
+            "Round 1 is finished",
+            "Waiting for participants",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert_eq!(vec![0, 1, 2], seqs(&mut rx));
+
+        for line in &["Advanced ceremony to round 2", &contribution(0)] {
+            reporter.parse_output_line(line).unwrap();
+        }
+        assert_eq!(vec![0], seqs(&mut rx));
+    }
+
+    /// Test that the progress of a round's aggregation is broadcast
+    /// for each aggregated chunk.
+    #[test]
+    fn test_aggregation_progress() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+
+        for line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            "Starting aggregation on round 1",
+            "INFO phase1_coordinator: Aggregated chunk 1 of 3",
+            "INFO phase1_coordinator: Aggregated chunk 2 of 3",
+            "INFO phase1_coordinator: Aggregated chunk 3 of 3",
+            "Round 1 is aggregated",
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+
+        let progress: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter(|message| matches!(message, CeremonyMessage::AggregationProgress { .. }))
+            .collect();
+        assert_eq!(
+            (1..=3)
+                .map(|done| CeremonyMessage::AggregationProgress {
+                    round: 1,
+                    done,
+                    total: 3
+                })
+                .collect::<Vec<_>>(),
+            progress
+        );
+    }
+
+    /// Test that [CeremonyMessage::CoordinatorReady] is broadcast when
+    /// the coordinator boots, before the first round is waiting for
+    /// participants.
+    #[test]
+    fn test_coordinator_ready() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+
+        reporter
+            .parse_output_line("Starting aleo-setup-coordinator")
+            .unwrap();
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        reporter
+            .parse_output_line("Coordinator has booted up")
+            .unwrap();
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+            ],
+            messages
+        );
+    }
+
+    /// Test that a verification reported by the coordinator while the
+    /// round is running is broadcast as a
+    /// [CeremonyMessage::SuccessfulVerification].
+    #[test]
+    fn test_successful_verification() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+        for output_line in &[
+            "Coordinator has booted up",
+            "Advanced ceremony to round 1",
+            &format!(
+                "INFO phase1_coordinator: {}.verifier verified contribution to chunk 5",
+                TEST_ADDRESS
+            ),
+        ] {
+            reporter.parse_output_line(output_line).unwrap();
+        }
+
+        let expected = CeremonyMessage::SuccessfulVerification {
+            verifier: VerifierRef {
+                address: TEST_ADDRESS.parse::<AleoPublicKey>().unwrap(),
+            },
+            chunk: 5,
+        };
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(messages.contains(&expected));
+        assert_eq!(1, state_history.progress().verifications);
+    }
+
+    /// Test that [CoordinatorStateHistory::assert_state_sequence()]
+    /// rejects a history containing an unexpected extra transition,
+    /// or missing an expected one.
+    #[test]
+    fn test_assert_state_sequence() {
+        let expected = [
+            PublicCoordinatorState::ProcessStarted,
+            PublicCoordinatorState::RoundWaitingForParticipants(1),
+            PublicCoordinatorState::RoundRunning(1),
+            PublicCoordinatorState::RoundAggregating(1),
+        ];
+
+        let history = CoordinatorStateHistory::default();
+        for state in &expected {
+            history.push(*state);
+        }
+        history.assert_state_sequence(&expected).unwrap();
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundAggregating(1)),
+            history.current_state()
+        );
+
+        let extra = CoordinatorStateHistory::default();
+        extra.push(PublicCoordinatorState::ProcessStarted);
+        extra.push(PublicCoordinatorState::RoundWaitingForParticipants(1));
+        extra.push(PublicCoordinatorState::RoundFinished(1));
+        extra.push(PublicCoordinatorState::RoundRunning(1));
+        extra.push(PublicCoordinatorState::RoundAggregating(1));
+        let error = extra.assert_state_sequence(&expected).unwrap_err();
+        assert!(error.to_string().contains("transition 2"));
+
+        assert!(history.assert_state_sequence(&expected[..3]).is_err());
+        assert!(history
+            .assert_state_sequence(&[
+                PublicCoordinatorState::ProcessStarted,
+                PublicCoordinatorState::RoundRunning(1),
+                PublicCoordinatorState::RoundAggregating(1),
+            ])
+            .is_err());
+    }
+
+    #[test]
+    fn test_classify_log_line() {
+        let address: AleoPublicKey = TEST_ADDRESS.parse().unwrap();
+
+        assert_eq!(
+            Some(CeremonyMessage::RoundWaitingForParticipants(1)),
+            classify_log_line("INFO aleo_setup_coordinator: Coordinator has booted up")
+        );
+        assert_eq!(
+            Some(CeremonyMessage::RoundStarted(3)),
+            classify_log_line("INFO phase1_coordinator: Advanced ceremony to round 3")
+        );
+        assert_eq!(
+            Some(CeremonyMessage::RoundStartedAggregation(3)),
+            classify_log_line("INFO phase1_coordinator: Starting aggregation on round 3")
+        );
+        assert_eq!(
+            Some(CeremonyMessage::RoundAggregated(3)),
+            classify_log_line("INFO phase1_coordinator: Round 3 is aggregated")
+        );
+        assert_eq!(
+            Some(CeremonyMessage::RoundFinished(3)),
+            classify_log_line("INFO phase1_coordinator: Round 3 is finished")
+        );
+        assert_eq!(
+            Some(CeremonyMessage::RoundRestarted(3)),
+            classify_log_line("INFO phase1_coordinator: Resetting current round 3")
+        );
+        assert_eq!(
+            Some(CeremonyMessage::Shutdown(ShutdownReason::TestFinished)),
+            classify_log_line(
+                "WARN phase1_coordinator: No contributors remaining to reset and complete the \
+                    current round. Rolling back to round 0 to wait and accept new participants"
+            )
+        );
+        assert_eq!(
+            Some(CeremonyMessage::ParticipantDropped(
+                ParticipantRef::Contributor(ContributorRef {
+                    address: address.clone()
+                })
+            )),
+            classify_log_line(&format!(
+                "INFO phase1_coordinator: Dropping {}.contributor from the ceremony",
+                TEST_ADDRESS
+            ))
+        );
+        assert_eq!(
+            Some(CeremonyMessage::ParticipantDropped(
+                ParticipantRef::Verifier(VerifierRef {
+                    address: address.clone()
+                })
+            )),
+            classify_log_line(&format!(
+                "INFO phase1_coordinator: Dropping {}.verifier from the ceremony",
+                TEST_ADDRESS
+            ))
+        );
+        let replacement_address = format!("aleo1{:0>58}", 1);
+        assert_eq!(
+            Some(CeremonyMessage::ReplacementActivated {
+                dropped: ContributorRef {
+                    address: address.clone()
+                },
+                replacement: ContributorRef {
+                    address: replacement_address.parse().unwrap()
+                },
+            }),
+            classify_log_line(&format!(
+                "INFO phase1_coordinator: Adding replacement contributor {}.contributor to {}.contributor",
+                replacement_address, TEST_ADDRESS
+            ))
+        );
+        assert_eq!(
+            None,
+            classify_log_line(&format!(
+                "INFO phase1_coordinator: Adding replacement contributor {}.contributor",
+                replacement_address
+            ))
+        );
+        assert_eq!(
+            Some(CeremonyMessage::SuccessfulContribution {
+                contributor: ContributorRef { address },
+                chunk: 7,
+                seq: 0,
+            }),
+            classify_log_line(&format!(
+                "INFO phase1_coordinator: {}.contributor added a contribution to chunk 7",
+                TEST_ADDRESS
+            ))
+        );
+    }
+
+    /// Test that a replacement contributor taking over the slot of a
+    /// dropped contributor is reported, and that a replacement without
+    /// a dropped contributor is ignored.
+    #[test]
+    fn test_replacement_activated() {
+        let dropped = ContributorRef {
+            address: TEST_ADDRESS.parse().unwrap(),
+        };
+        let replacement_address = format!("aleo1{:0>58}", 1);
+        let replacement = ContributorRef {
+            address: replacement_address.parse().unwrap(),
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+        for line in &[
+            "Coordinator has booted up".to_string(),
+            "Advanced ceremony to round 1".to_string(),
+            format!(
+                "INFO phase1_coordinator: Adding replacement contributor {}.contributor",
+                replacement_address
+            ),
+            format!(
+                "INFO phase1_coordinator: Dropping {}.contributor from the ceremony",
+                TEST_ADDRESS
+            ),
+            format!(
+                "INFO phase1_coordinator: Adding replacement contributor {}.contributor",
+                replacement_address
+            ),
+        ] {
+            reporter.parse_output_line(line).unwrap();
+        }
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+                CeremonyMessage::RoundStarted(1),
+                CeremonyMessage::ParticipantDropped(ParticipantRef::Contributor(dropped.clone())),
+                CeremonyMessage::ReplacementActivated {
+                    dropped,
+                    replacement,
+                },
+            ],
+            messages
+        );
+    }
+
+    /// Test that a verifier disconnecting and reconnecting is
+    /// detected, and that a reconnection without a previous
+    /// disconnection is ignored.
+    #[cfg(unix)]
+    #[test]
+    fn test_verifier_disconnect_reconnect() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let verifier = VerifierRef {
+            address: TEST_ADDRESS.parse().unwrap(),
+        };
+        let (mut process, stdout) = fake_coordinator(&format!(
+            "echo 'Coordinator has booted up'; \
+            echo 'INFO phase1_coordinator: {address}.verifier reconnected'; \
+            echo 'WARN phase1_coordinator: {address}.verifier has disconnected'; \
+            echo 'INFO phase1_coordinator: {address}.verifier has reconnected'",
+            address = TEST_ADDRESS
+        ));
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let mut messages = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => panic!("`rx` disconnected"),
+            }
+        }
+
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+                CeremonyMessage::VerifierDisconnected(verifier.clone()),
+                CeremonyMessage::VerifierReconnected(verifier),
+            ],
+            messages
+        );
+    }
+
+    /// Test that contributors and verifiers joining the queue while
+    /// the round is waiting for participants are broadcast.
+    #[cfg(unix)]
+    #[test]
+    fn test_participant_joined_queue() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let verifier_address = format!("aleo1{:0>58}", 1);
+        let (mut process, stdout) = fake_coordinator(&format!(
+            "echo 'Coordinator has booted up'; \
+            echo 'INFO phase1_coordinator: {contributor}.contributor joined the queue'; \
+            echo 'INFO phase1_coordinator: {verifier}.verifier has joined the queue'",
+            contributor = TEST_ADDRESS,
+            verifier = verifier_address,
+        ));
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+                CeremonyMessage::ParticipantJoinedQueue(ParticipantRef::Contributor(
+                    ContributorRef {
+                        address: TEST_ADDRESS.parse().unwrap(),
+                    }
+                )),
+                CeremonyMessage::ParticipantJoinedQueue(ParticipantRef::Verifier(VerifierRef {
+                    address: verifier_address.parse().unwrap(),
+                })),
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn test_classify_participant_joined_queue() {
+        assert_eq!(
+            Some(CeremonyMessage::ParticipantJoinedQueue(
+                ParticipantRef::Contributor(ContributorRef {
+                    address: TEST_ADDRESS.parse().unwrap(),
+                })
+            )),
+            classify_log_line(&format!(
+                "INFO phase1_coordinator: {}.contributor joined the queue",
+                TEST_ADDRESS
+            ))
+        );
+    }
+
+    #[test]
+    fn test_classify_verifier_connection_lines() {
+        let verifier = VerifierRef {
+            address: TEST_ADDRESS.parse().unwrap(),
+        };
+
+        assert_eq!(
+            Some(CeremonyMessage::VerifierDisconnected(verifier.clone())),
+            classify_log_line(&format!(
+                "WARN phase1_coordinator: {}.verifier disconnected",
+                TEST_ADDRESS
+            ))
+        );
+        assert_eq!(
+            Some(CeremonyMessage::VerifierReconnected(verifier)),
+            classify_log_line(&format!(
+                "INFO phase1_coordinator: {}.verifier has reconnected",
+                TEST_ADDRESS
+            ))
+        );
+    }
+
+    #[test]
+    fn test_classify_log_line_no_match() {
+        assert_eq!(
+            None,
+            classify_log_line("DEBUG rocket: GET /v1/queue/contributor/heartbeat")
+        );
+    }
+
+    /// Test that a panic and its backtrace printed over many lines are
+    /// grouped into a single [CoordinatorBacktrace] attached to the
+    /// shutdown.
+    #[cfg(unix)]
+    #[test]
+    fn test_backtrace_grouped() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Coordinator has booted up'; \
+            echo \"thread 'main' panicked at 'called \\`Option::unwrap()\\` on a \\`None\\` value', src/main.rs:10:5\"; \
+            echo 'stack backtrace:'; \
+            echo '   0: rust_begin_unwind'; \
+            echo '             at /rustc/library/std/src/panicking.rs:493:5'; \
+            echo '   1: aleo_setup_coordinator::main'; \
+            echo '             at ./src/main.rs:10:5'; \
+            echo 'note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.'; \
+            exit 101",
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let mut backtraces = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(CeremonyMessage::Shutdown(ShutdownReason::CoordinatorPanicked(backtrace))) => {
+                    backtraces.push(backtrace)
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => panic!("`rx` disconnected"),
+            }
+        }
+
+        assert_eq!(
+            vec![CoordinatorBacktrace {
+                message: "called `Option::unwrap()` on a `None` value".to_string(),
+                location: "src/main.rs:10:5".to_string(),
+                frames: vec![
+                    "0: rust_begin_unwind\nat /rustc/library/std/src/panicking.rs:493:5"
+                        .to_string(),
+                    "1: aleo_setup_coordinator::main\nat ./src/main.rs:10:5".to_string(),
+                ],
+            }],
+            backtraces
+        );
+    }
+
+    /// Test that a panic printed in the format used by newer versions
+    /// of Rust (location first, with the message on the following
+    /// line) is reported as a [ShutdownReason::CoordinatorPanicked].
+    #[cfg(unix)]
+    #[test]
+    fn test_backtrace_location_first() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Coordinator has booted up'; \
+            echo \"thread 'main' panicked at src/main.rs:10:5:\"; \
+            echo 'called `Option::unwrap()` on a `None` value'; \
+            echo 'note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace'; \
+            exit 101",
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(out_dir.path().join("coordinator.log")),
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(messages.contains(&CeremonyMessage::Shutdown(
+            ShutdownReason::CoordinatorPanicked(CoordinatorBacktrace {
+                message: "called `Option::unwrap()` on a `None` value".to_string(),
+                location: "src/main.rs:10:5".to_string(),
+                frames: Vec::new(),
+            })
+        )));
+    }
+
+    /// Test that lines written to the coordinator's `stderr` are
+    /// logged to their own log file, and are also parsed.
+    #[cfg(unix)]
+    #[test]
+    fn test_stderr_separated() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut process = Exec::cmd("sh")
+            .arg("-c")
+            .arg(
+                "echo 'Coordinator has booted up'; \
+                echo \"thread 'main' panicked at src/main.rs:10:5:\" >&2; \
+                echo 'called `Option::unwrap()` on a `None` value' >&2; \
+                exit 101",
+            )
+            .stdout(Redirection::Pipe)
+            .stderr(Redirection::Pipe)
+            .popen()
+            .unwrap();
+        let output = ProcessOutput {
+            stdout: process.stdout.take().unwrap(),
+            stderr: process.stderr.take(),
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let log_file_path = out_dir.path().join("coordinator.log");
+        monitor_coordinator(
+            output,
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions::new(log_file_path.clone()),
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let log = std::fs::read_to_string(&log_file_path).unwrap();
+        assert_eq!(
+            vec!["Coordinator has booted up"],
+            log.lines().collect::<Vec<_>>()
+        );
+        let stderr_log =
+            std::fs::read_to_string(out_dir.path().join("coordinator.stderr.log")).unwrap();
+        assert_eq!(
+            vec![
+                "thread 'main' panicked at src/main.rs:10:5:",
+                "called `Option::unwrap()` on a `None` value",
+            ],
+            stderr_log.lines().collect::<Vec<_>>()
+        );
+
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(messages.contains(&CeremonyMessage::Shutdown(
+            ShutdownReason::CoordinatorPanicked(CoordinatorBacktrace {
+                message: "called `Option::unwrap()` on a `None` value".to_string(),
+                location: "src/main.rs:10:5".to_string(),
+                frames: Vec::new(),
+            })
+        )));
+    }
+
+    /// Test that, when the coordinator's `stderr` is read separately,
+    /// the round state is only parsed from `stdout`, and `stderr`
+    /// only reports errors.
+    #[test]
+    fn test_stderr_not_parsed_for_state() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+
+        reporter
+            .parse_stderr_line("Coordinator has booted up")
+            .unwrap();
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert!(messages.is_empty());
+
+        reporter
+            .parse_stdout_line("Coordinator has booted up")
+            .unwrap();
+        reporter
+            .parse_stderr_line("Advanced ceremony to round 1")
+            .unwrap();
+        let messages: Vec<CeremonyMessage> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(
+            vec![
+                CeremonyMessage::CoordinatorReady,
+                CeremonyMessage::RoundWaitingForParticipants(1),
+            ],
+            messages
+        );
+
+        let line = "Error: database is locked";
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        );
+        reporter.parse_stderr_line(line).unwrap_err();
+    }
+
+    /// Test that a backtrace which is still being collected when the
+    /// output ends is reported.
+    #[test]
+    fn test_backtrace_flushed_on_eof() {
+        let mut collector = BacktraceCollector::default();
+        assert_eq!(
+            None,
+            collector.push_line("thread 'main' panicked at 'boom', src/main.rs:1:1")
+        );
+        assert_eq!(None, collector.push_line("stack backtrace:"));
+        assert_eq!(None, collector.push_line("   0: main"));
+
+        let backtrace = collector.finish().unwrap();
+        assert_eq!("boom", backtrace.message);
+        assert_eq!(vec!["0: main".to_string()], backtrace.frames);
+        assert_eq!(None, collector.finish());
+    }
+
+    /// Test the round directory paths for the shared layout, and
+    /// with [CoordinatorConfig::per_round_out_dir] enabled.
+    #[test]
+    fn test_round_dir_layouts() {
+        let out_dir = Path::new("/tmp/out");
+        let mut config = test_config(out_dir);
+        config.environment = Environment::Universal;
+
+        assert_eq!(out_dir, config.round_out_dir(2));
+        assert_eq!(
+            Path::new("/tmp/out/transcript/round_2"),
+            config.round_dir(2)
+        );
+        assert_eq!(config.transcript_dir().join("round_2"), config.round_dir(2));
+
+        config.per_round_out_dir = true;
+        assert_eq!(Path::new("/tmp/out/round_2"), config.round_out_dir(2));
+        assert_eq!(
+            Path::new("/tmp/out/round_2/transcript/round_2"),
+            config.round_dir(2)
+        );
+        // The coordinator still writes to the shared layout.
+        assert_eq!(
+            Path::new("/tmp/out/transcript/round_2"),
+            config.live_round_dir(2)
+        );
+        assert_eq!(
+            Path::new("/tmp/out/transcript/round_2/state.json"),
+            config.round_state_file(2)
+        );
+
+        config.environment = Environment::Development;
+        assert_eq!(
+            Path::new("/tmp/out/round_3/transcript/development/round_3"),
+            config.round_dir(3)
+        );
+    }
+
+    /// Test the transcript directory for each [Environment].
+    #[test]
+    fn test_transcript_dir_per_environment() {
+        let out_dir = tempfile::tempdir().unwrap();
+        for (environment, expected) in &[
+            (Environment::Development, "transcript/development"),
+            (Environment::Inner, "transcript"),
+            (Environment::Outer, "transcript"),
+            (Environment::Universal, "transcript"),
+        ] {
+            let config = CoordinatorConfig {
+                environment: *environment,
+                ..test_config(out_dir.path())
+            };
+            assert_eq!(out_dir.path().join(expected), config.transcript_dir());
+        }
+    }
+
+    /// Test the round directory and state file paths for development
+    /// and non-development environments.
+    #[test]
+    fn test_round_state_file_per_environment() {
+        let out_dir = tempfile::tempdir().unwrap();
+        for (environment, expected_round_dir) in &[
+            (Environment::Development, "transcript/development/round_3"),
+            (Environment::Inner, "transcript/round_3"),
+            (Environment::Outer, "transcript/round_3"),
+            (Environment::Universal, "transcript/round_3"),
+        ] {
+            let config = CoordinatorConfig {
+                environment: *environment,
+                ..test_config(out_dir.path())
+            };
+            let expected_round_dir = out_dir.path().join(expected_round_dir);
+            assert_eq!(expected_round_dir, config.round_dir(3));
+            assert_eq!(
+                expected_round_dir.join("state.json"),
+                config.round_state_file(3)
+            );
+        }
+    }
+
+    #[test]
+    fn test_wait_for_transcript_dir() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+        std::fs::create_dir_all(config.transcript_dir()).unwrap();
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let rx = bus.subscribe();
+        bus.broadcast(CeremonyMessage::CoordinatorReady).unwrap();
+        wait_for_transcript_dir(&config, rx).unwrap();
+    }
+
+    /// Test that the timeout for the transcript directory to appear
+    /// only starts once the coordinator has booted.
+    #[test]
+    fn test_wait_for_transcript_dir_after_boot() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            transcript_dir_timeout: Duration::from_millis(200),
+            ..test_config(out_dir.path())
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let rx = bus.subscribe();
+        let tx = bus.broadcaster();
+        let transcript_dir = config.transcript_dir();
+        let boot_join = std::thread::spawn(move || {
+            // Booting takes longer than the timeout.
+            std::thread::sleep(Duration::from_millis(400));
+            tx.broadcast(CeremonyMessage::CoordinatorReady).unwrap();
+            std::fs::create_dir_all(transcript_dir).unwrap();
+        });
+
+        wait_for_transcript_dir(&config, rx).unwrap();
+        boot_join.join().unwrap();
+    }
+
+    /// Test that an error is returned if the transcript directory
+    /// never appears, or if the ceremony shuts down before the
+    /// coordinator boots.
+    #[test]
+    fn test_wait_for_transcript_dir_timeout() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            transcript_dir_timeout: Duration::from_millis(200),
+            ..test_config(out_dir.path())
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let rx = bus.subscribe();
+        bus.broadcast(CeremonyMessage::CoordinatorReady).unwrap();
+        let error = wait_for_transcript_dir(&config, rx).unwrap_err();
+        assert!(error.to_string().contains("transcript directory"));
+
+        let rx = bus.subscribe();
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))
+            .unwrap();
+        let error = wait_for_transcript_dir(&config, rx).unwrap_err();
+        assert!(error.to_string().contains("before the coordinator booted"));
+    }
+
+    /// Create a fake coordinator binary in `out_dir` which runs the
+    /// specified shell `script`, and a coordinator config file. Returns
+    /// a [CoordinatorConfig] using the specified `launch_mode`, and
+    /// the path to the config file.
+    #[cfg(unix)]
+    fn launch_mode_config(
+        out_dir: &Path,
+        script: &str,
+        launch_mode: LaunchMode,
+    ) -> (CoordinatorConfig, PathBuf) {
+        let config = CoordinatorConfig {
+            launch_mode,
+            ..test_config(out_dir)
+        };
+
+        fake_bin(out_dir, "aleo-setup-coordinator", script);
+
+        let toml_config_path = out_dir.join("config.toml");
+        std::fs::write(&toml_config_path, "setup = \"development\"\n").unwrap();
+
+        (config, toml_config_path)
+    }
+
+    /// Test that a coordinator which exits before booting is
+    /// relaunched when [CoordinatorConfig::startup_retries] is set.
+    #[cfg(unix)]
+    #[test]
+    fn test_startup_retried() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut config, _) = launch_mode_config(
+            out_dir.path(),
+            "echo launched >> launches; \
+            if [ $(wc -l < launches) -lt 2 ]; then echo 'failed to bind port'; exit 1; fi; \
+            echo 'Coordinator has booted up'; \
+            while true; do sleep 0.1; done",
+            LaunchMode::Arg,
+        );
+        config.startup_retries = 1;
+        config.startup_backoff = Duration::from_millis(100);
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let join = run_coordinator(&config, bus.broadcaster(), bus.subscribe()).unwrap();
+
+        wait_for_message(
+            &mut rx,
+            |message| matches!(message, CeremonyMessage::CoordinatorReady),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+
+        let state_history = join.state_history().clone();
+        join.join().unwrap();
+
+        let launches = std::fs::read_to_string(out_dir.path().join("launches")).unwrap();
+        assert_eq!(2, launches.lines().count());
+        assert_eq!(
+            vec![
+                PublicCoordinatorState::ProcessStarted,
+                PublicCoordinatorState::ProcessStarted,
+                PublicCoordinatorState::RoundWaitingForParticipants(1),
+            ],
+            state_history.states()
+        );
+    }
+
+    /// Test that the coordinator is launched under the configured
+    /// resource limits, and that exceeding them shuts the ceremony
+    /// down with an error.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_resource_limits() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut config, toml_config_path) = launch_mode_config(
+            out_dir.path(),
+            "echo \"limits $(ulimit -v) $(ulimit -t) $$ $@\"; \
+            echo 'Coordinator has booted up'; \
+            data=$(head -c 268435456 /dev/zero | tr '\\0' a); \
+            echo 'allocated'",
+            LaunchMode::Arg,
+        );
+        config.resource_limits = ResourceLimits {
+            max_memory_bytes: Some(64 * 1024 * 1024),
+            max_cpu_seconds: Some(60),
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let join = run_coordinator(&config, bus.broadcaster(), bus.subscribe()).unwrap();
+        let pid = join.pid().unwrap();
+        wait_for_message(
+            &mut rx,
+            |message| {
+                matches!(
+                    message,
+                    CeremonyMessage::Shutdown(ShutdownReason::CoordinatorDied)
+                )
+            },
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert!(join.join().is_err());
+
+        // The limits are in place as soon as the coordinator starts,
+        // and `prlimit` executes the coordinator in its own process.
+        let log = std::fs::read_to_string(config.log_file()).unwrap();
+        assert!(log.starts_with(&format!(
+            "limits 65536 60 {} --config {}",
+            pid,
+            toml_config_path.canonicalize().unwrap().display()
+        )));
+        assert!(!log.contains("allocated"));
+
+        assert!(parse_limited_exit_status(subprocess::ExitStatus::Exited(2))
+            .unwrap_err()
+            .to_string()
+            .contains("resource limits"));
+    }
+
+    /// Test that the exit code of the coordinator process is
+    /// broadcast when it exits.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_coordinator_exited() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (config, _) = launch_mode_config(out_dir.path(), "exit 3", LaunchMode::Arg);
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let join = run_coordinator(&config, bus.broadcaster(), bus.subscribe()).unwrap();
+        let message = wait_for_message(
+            &mut rx,
+            |message| matches!(message, CeremonyMessage::CoordinatorExited { .. }),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(
+            CeremonyMessage::CoordinatorExited {
+                code: Some(3),
+                signal: None
+            },
+            message
+        );
+        assert!(join.join().is_err());
+    }
+
+    /// Test that the ceremony is shut down with
+    /// [ShutdownReason::CoordinatorDied] when the coordinator exits
+    /// with an error after it has booted.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_coordinator_died() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (config, _) = launch_mode_config(
+            out_dir.path(),
+            "echo 'Coordinator has booted up'; sleep 0.2; exit 1",
+            LaunchMode::Arg,
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let join = run_coordinator(&config, bus.broadcaster(), bus.subscribe()).unwrap();
+        let message = wait_for_message(
+            &mut rx,
+            |message| matches!(message, CeremonyMessage::Shutdown(_)),
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(
+            CeremonyMessage::Shutdown(ShutdownReason::CoordinatorDied),
+            message
+        );
+        assert!(join.join().is_err());
+    }
+
+    /// Test that with [CoordinatorConfig::dry_run] set, the
+    /// configuration file is written without the coordinator being
+    /// executed.
+    #[cfg(unix)]
+    #[test]
+    fn test_dry_run() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut config, _) = launch_mode_config(
+            out_dir.path(),
+            "touch \"$(dirname \"$0\")/launched\"",
+            LaunchMode::Arg,
+        );
+        config.dry_run = true;
+        config.check_version = true;
+        std::fs::remove_file(out_dir.path().join("config.toml")).unwrap();
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let toml_config_path =
+            match launch_coordinator(&config, bus.broadcaster(), bus.subscribe()).unwrap() {
+                CoordinatorLaunch::DryRun { toml_config_path } => toml_config_path,
+                CoordinatorLaunch::Started(_) => panic!("expected a dry run"),
+            };
+
+        assert_eq!(out_dir.path().join("config.toml"), toml_config_path);
+        let expected_toml_config =
+            toml::to_string_pretty(&CoordinatorTomlConfiguration::try_from(&config).unwrap())
+                .unwrap();
+        assert_eq!(
+            expected_toml_config,
+            std::fs::read_to_string(&toml_config_path).unwrap()
+        );
+        assert!(!out_dir.path().join("launched").exists());
+
+        assert!(run_coordinator(&config, bus.broadcaster(), bus.subscribe()).is_err());
+        assert!(!out_dir.path().join("launched").exists());
+    }
+
+    /// Test that a dry run creates the [CoordinatorConfig::out_dir]
+    /// and the directory of the SQLite file when they do not exist
+    /// yet.
+    #[test]
+    fn test_dry_run_creates_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("out");
+        let sqlite_dir = out_dir.join("db");
+        let mut config = test_config(&out_dir);
+        config.dry_run = true;
+        config.sqlite_file = Some(sqlite_dir.join("setup.db3"));
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let toml_config_path =
+            match launch_coordinator(&config, bus.broadcaster(), bus.subscribe()).unwrap() {
+                CoordinatorLaunch::DryRun { toml_config_path } => toml_config_path,
+                CoordinatorLaunch::Started(_) => panic!("expected a dry run"),
+            };
+
+        assert_eq!(out_dir.join("config.toml"), toml_config_path);
+        assert!(toml_config_path.exists());
+        assert!(sqlite_dir.is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_launch_mode_arg() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (config, toml_config_path) =
+            launch_mode_config(out_dir.path(), "echo \"$@\"", LaunchMode::Arg);
+
+        let output = coordinator_exec(&config, &toml_config_path)
+            .unwrap()
+            .stdout(Redirection::Pipe)
+            .capture()
+            .unwrap()
+            .stdout_str();
+
+        let expected = format!(
+            "--config {}",
+            toml_config_path.canonicalize().unwrap().display()
+        );
+        assert_eq!(expected, output.trim());
+    }
 
-    for contributor in contributors {
-        state
-            .contributor_ids
+    #[cfg(unix)]
+    #[test]
+    fn test_launch_mode_env_var() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (config, toml_config_path) = launch_mode_config(
+            out_dir.path(),
+            "echo \"$@\"; echo \"$COORDINATOR_CONFIG\"",
+            LaunchMode::EnvVar("COORDINATOR_CONFIG".to_string()),
+        );
+
+        let output = coordinator_exec(&config, &toml_config_path)
+            .unwrap()
+            .stdout(Redirection::Pipe)
+            .capture()
+            .unwrap()
+            .stdout_str();
+
+        let expected = format!("\n{}", toml_config_path.canonicalize().unwrap().display());
+        assert_eq!(expected, output.trim_end());
+    }
+
+    /// Test that the coordinator is run in the
+    /// [CoordinatorConfig::working_dir] when it is specified, and
+    /// otherwise in the [CoordinatorConfig::out_dir].
+    #[cfg(unix)]
+    #[test]
+    fn test_working_dir() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let working_dir = tempfile::tempdir().unwrap();
+        let (mut config, toml_config_path) =
+            launch_mode_config(out_dir.path(), "pwd", LaunchMode::Arg);
+
+        let cwd = |config: &CoordinatorConfig| {
+            let output = coordinator_exec(config, &toml_config_path)
+                .unwrap()
+                .stdout(Redirection::Pipe)
+                .capture()
+                .unwrap()
+                .stdout_str();
+            PathBuf::from(output.trim()).canonicalize().unwrap()
+        };
+
+        assert_eq!(out_dir.path().canonicalize().unwrap(), cwd(&config));
+
+        config.working_dir = Some(working_dir.path().to_owned());
+        assert_eq!(working_dir.path(), config.working_dir());
+        assert_eq!(working_dir.path().canonicalize().unwrap(), cwd(&config));
+
+        // The transcript, which the coordinator writes relative to its
+        // working directory, is checked there. The other artifacts
+        // remain in the out dir.
+        assert!(config.transcript_dir().starts_with(working_dir.path()));
+        assert!(config.round_state_file(1).starts_with(working_dir.path()));
+        assert!(config.log_file().starts_with(out_dir.path()));
+        assert!(config.sqlite_file().starts_with(out_dir.path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_launch_mode_stdin() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (config, toml_config_path) =
+            launch_mode_config(out_dir.path(), "echo \"$@\"; cat", LaunchMode::Stdin);
+
+        let output = coordinator_exec(&config, &toml_config_path)
+            .unwrap()
+            .stdout(Redirection::Pipe)
+            .capture()
+            .unwrap()
+            .stdout_str();
+
+        assert_eq!("\nsetup = \"development\"", output.trim_end());
+    }
+
+    /// Test that a fake coordinator binary reporting a supported
+    /// version passes the check, and one reporting an incompatible
+    /// version fails it.
+    #[cfg(unix)]
+    #[test]
+    fn test_check_coordinator_version() {
+        let out_dir = tempfile::tempdir().unwrap();
+
+        let (config, _) = launch_mode_config(
+            out_dir.path(),
+            "echo 'aleo-setup-coordinator 0.3.1'",
+            LaunchMode::Arg,
+        );
+        let version = check_coordinator_version(&config).unwrap();
+        assert_eq!("0.3.1", version.to_string());
+
+        let (config, _) = launch_mode_config(
+            out_dir.path(),
+            "echo 'aleo-setup-coordinator 1.0.0'",
+            LaunchMode::Arg,
+        );
+        let error = check_coordinator_version(&config).unwrap_err();
+        assert!(error.to_string().contains("not supported"));
+    }
+
+    fn profile_config(profile: CoordinatorConfigProfile) -> CoordinatorConfig {
+        CoordinatorConfig::from_profile(
+            profile,
+            "aleo-setup-coordinator".into(),
+            "aleo-setup-coordinator/target/release/aleo-setup-coordinator".into(),
+            "out".into(),
+        )
+    }
+
+    #[test]
+    fn test_smoke_profile() {
+        let config = profile_config(CoordinatorConfigProfile::Smoke);
+        assert!(matches!(config.environment, Environment::Development));
+
+        let toml_config = CoordinatorTomlConfiguration::try_from(&config).unwrap();
+        let parameters = toml_config.environment_parameters.parameters;
+        assert_eq!(1, parameters.maximum_contributors_per_round.get());
+        assert_eq!(300, parameters.participant_lock_timeout);
+    }
+
+    #[test]
+    fn test_full_profile() {
+        let config = profile_config(CoordinatorConfigProfile::Full);
+        assert!(matches!(config.environment, Environment::Universal));
+        assert_eq!(Duration::from_secs(120), config.transcript_dir_timeout);
+
+        let toml_config = CoordinatorTomlConfiguration::try_from(&config).unwrap();
+        let parameters = toml_config.environment_parameters.parameters;
+        assert_eq!(5, parameters.maximum_contributors_per_round.get());
+        assert_eq!(900, parameters.participant_lock_timeout);
+    }
+
+    #[test]
+    fn test_stress_profile() {
+        let config = profile_config(CoordinatorConfigProfile::Stress);
+        assert!(matches!(config.environment, Environment::Development));
+
+        let toml_config = CoordinatorTomlConfiguration::try_from(&config).unwrap();
+        let parameters = toml_config.environment_parameters.parameters;
+        assert_eq!(20, parameters.maximum_contributors_per_round.get());
+        assert_eq!(1800, parameters.participant_lock_timeout);
+    }
+
+    #[test]
+    fn test_num_powers_serialization() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            num_powers: Some(8),
+            ..test_config(out_dir.path())
+        };
+
+        let toml_value = toml_config_value(&config);
+
+        assert_eq!(
+            Some(8),
+            toml_value["environment_parameters"]["num_powers"].as_integer()
+        );
+        assert_eq!(
+            Some(5),
+            toml_value["environment_parameters"]["maximum_contributors_per_round"].as_integer()
+        );
+
+        assert!(
+            toml_config_value(&test_config(out_dir.path()))["environment_parameters"]
+                .get("num_powers")
+                .is_none()
+        );
+    }
+
+    /// Test that the journal mode is serialized in the
+    /// `sqlite_pragmas` section only when it is specified.
+    #[test]
+    fn test_sqlite_pragmas_serialization() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            sqlite_journal_mode: Some("wal".parse().unwrap()),
+            ..test_config(out_dir.path())
+        };
+
+        let toml_value = toml_config_value(&config);
+        assert_eq!(
+            Some("WAL"),
+            toml_value["sqlite_pragmas"]["journal_mode"].as_str()
+        );
+
+        assert!(toml_config_value(&test_config(out_dir.path()))
+            .get("sqlite_pragmas")
+            .is_none());
+
+        assert_eq!(
+            SqliteJournalMode::Memory,
+            "MEMORY".parse::<SqliteJournalMode>().unwrap()
+        );
+        let error = "FAST".parse::<SqliteJournalMode>().unwrap_err();
+        assert!(error.to_string().contains("WAL"));
+    }
+
+    /// Test that a [CoordinatorTomlConfiguration] written to a
+    /// `config.toml` file can be read back unchanged.
+    #[test]
+    fn test_toml_config_round_trip() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let configs = vec![
+            test_config(out_dir.path()),
+            CoordinatorConfig {
+                replacement_contributors: vec![ContributorRef {
+                    address: TEST_ADDRESS.parse().unwrap(),
+                }],
+                environment_parameters: Some(EnvironmentParameters::default()),
+                num_powers: Some(12),
+                sqlite_journal_mode: Some(SqliteJournalMode::Wal),
+                reliability_check: Some(ReliabilityCheckSettings {
+                    is_enabled: true,
+                    ..ReliabilityCheckSettings::default()
+                }),
+                twitter_settings: Some(TwitterSettings {
+                    consumer_token: "token".to_string(),
+                    consumer_secret: "secret".to_string(),
+                }),
+                ..test_config(out_dir.path())
+            },
+        ];
+
+        let toml_config_path = out_dir.path().join("config.toml");
+        for config in &configs {
+            let toml_config = CoordinatorTomlConfiguration::try_from(config).unwrap();
+            std::fs::write(
+                &toml_config_path,
+                toml::to_string_pretty(&toml_config).unwrap(),
+            )
+            .unwrap();
+
+            let read_toml_config: CoordinatorTomlConfiguration =
+                toml::from_str(&std::fs::read_to_string(&toml_config_path).unwrap()).unwrap();
+            assert_eq!(toml_config, read_toml_config);
+        }
+    }
+
+    /// Test that [tail_log()] returns the last lines of the log,
+    /// reading across multiple chunks, and all of the lines when there
+    /// are fewer than requested.
+    #[test]
+    fn test_tail_log() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+
+        // Long enough lines for the file to span multiple chunks.
+        let lines: Vec<String> = (0..100)
+            .map(|i| format!("line {} {}", i, "x".repeat(200)))
+            .collect();
+        std::fs::write(config.log_file(), format!("{}\n", lines.join("\n"))).unwrap();
+
+        assert_eq!(lines[90..].to_vec(), tail_log(&config, 10).unwrap());
+        assert_eq!(lines[99..].to_vec(), tail_log(&config, 1).unwrap());
+        assert_eq!(lines, tail_log(&config, 1000).unwrap());
+        assert!(tail_log(&config, 0).unwrap().is_empty());
+
+        std::fs::write(config.log_file(), "first\nsecond").unwrap();
+        assert_eq!(vec!["second".to_string()], tail_log(&config, 1).unwrap());
+        assert_eq!(
+            vec!["first".to_string(), "second".to_string()],
+            tail_log(&config, 10).unwrap()
+        );
+
+        std::fs::remove_file(config.log_file()).unwrap();
+        assert!(tail_log(&config, 10).is_err());
+    }
+
+    /// Test that [tail_log()] continues into the rolled over log file
+    /// when the current one has fewer lines than requested.
+    #[test]
+    fn test_tail_log_rolled_over() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+
+        std::fs::write(
+            rolled_log_path(&config.log_file()),
+            "line 0\nline 1\nline 2\n",
+        )
+        .unwrap();
+        std::fs::write(config.log_file(), "line 3\nline 4\n").unwrap();
+
+        assert_eq!(vec!["line 4".to_string()], tail_log(&config, 1).unwrap());
+        assert_eq!(
+            vec![
+                "line 1".to_string(),
+                "line 2".to_string(),
+                "line 3".to_string(),
+                "line 4".to_string(),
+            ],
+            tail_log(&config, 4).unwrap()
+        );
+        assert_eq!(5, tail_log(&config, 10).unwrap().len());
+    }
+
+    /// Test that [tail_stderr_log()] returns the last lines of the
+    /// `stderr` log, and no lines when it does not exist.
+    #[test]
+    fn test_tail_stderr_log() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+        assert!(tail_stderr_log(&config, 10).unwrap().is_empty());
+
+        std::fs::write(config.log_file(), "stdout\n").unwrap();
+        std::fs::write(config.stderr_log_file(), "first\nsecond\n").unwrap();
+        assert_eq!(
+            vec!["second".to_string()],
+            tail_stderr_log(&config, 1).unwrap()
+        );
+        assert_eq!(vec!["stdout".to_string()], tail_log(&config, 10).unwrap());
+    }
+
+    /// Test that [validate_config()] accepts a valid configuration,
+    /// and reports a [ConfigParseError] for an invalid one, both for a
+    /// coordinator which supports `--check-config` and for one which
+    /// has to be launched.
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_config() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let valid_path = out_dir.path().join("valid.toml");
+        std::fs::write(&valid_path, "[settings]\nenvironment = \"development\"\n").unwrap();
+        let invalid_path = out_dir.path().join("invalid.toml");
+        std::fs::write(&invalid_path, "[settings]\nbad = true\n").unwrap();
+
+        let check_config_bin = fake_bin(
+            out_dir.path(),
+            "aleo-setup-coordinator",
+            r#"[ "$1" = "--check-config" ] || exit 1
+if grep -q bad "$3"; then
+    echo "Unable to parse config: unknown field \`bad\`" >&2
+    exit 1
+fi
+echo "Configuration is valid"
+"#,
+        );
+        validate_config(&check_config_bin, &valid_path).unwrap();
+        let error = validate_config(&check_config_bin, &invalid_path).unwrap_err();
+        assert!(error.downcast_ref::<ConfigParseError>().is_some());
+        assert!(format!("{:?}", error).contains("bad = true"));
+
+        let launch_bin = fake_bin(
+            out_dir.path(),
+            "aleo-setup-coordinator",
+            r#"if [ "$1" = "--check-config" ]; then
+    echo "error: Found argument '--check-config' which wasn't expected" >&2
+    exit 2
+fi
+if grep -q bad "$2"; then
+    echo "Unable to parse config: unknown field \`bad\`"
+else
+    echo "Coordinator has booted up"
+fi
+while true; do sleep 0.05; done
+"#,
+        );
+        validate_config(&launch_bin, &valid_path).unwrap();
+        let error = validate_config(&launch_bin, &invalid_path).unwrap_err();
+        assert!(error.downcast_ref::<ConfigParseError>().is_some());
+
+        let crashing_bin = fake_bin(
+            out_dir.path(),
+            "aleo-setup-coordinator",
+            r#"if [ "$1" = "--check-config" ]; then
+    echo "error: unrecognized option '--check-config'" >&2
+    exit 2
+fi
+exit 3
+"#,
+        );
+        let error = validate_config(&crashing_bin, &valid_path).unwrap_err();
+        assert!(error.downcast_ref::<ConfigParseError>().is_none());
+
+        // A coordinator which ignores `--check-config` and starts
+        // serving is stopped, and validated by launching it.
+        let serving_bin = fake_bin(
+            out_dir.path(),
+            "aleo-setup-coordinator",
+            r#"echo "Coordinator has booted up"
+while true; do sleep 0.05; done
+"#,
+        );
+        validate_config(&serving_bin, &valid_path).unwrap();
+    }
+
+    /// Test that duplicate or malformed
+    /// [CoordinatorConfig::replacement_contributors] are rejected when
+    /// creating the [CoordinatorTomlConfiguration].
+    #[test]
+    fn test_toml_config_invalid_replacement_contributors() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let contributor = |address: &str| ContributorRef {
+            address: address.parse().unwrap(),
+        };
+        let other_address = format!("aleo1{:0>58}", 1);
+
+        let config = CoordinatorConfig {
+            replacement_contributors: vec![contributor(TEST_ADDRESS), contributor(&other_address)],
+            ..test_config(out_dir.path())
+        };
+        let toml_config = CoordinatorTomlConfiguration::try_from(&config).unwrap();
+        assert_eq!(2, toml_config.replacement_contributors.len());
+
+        let config = CoordinatorConfig {
+            replacement_contributors: vec![
+                contributor(TEST_ADDRESS),
+                contributor(&other_address),
+                contributor(TEST_ADDRESS),
+            ],
+            ..test_config(out_dir.path())
+        };
+        let error = CoordinatorTomlConfiguration::try_from(&config).unwrap_err();
+        assert_eq!(
+            format!(
+                "Replacement contributor {} is specified more than once",
+                TEST_ADDRESS
+            ),
+            error.to_string()
+        );
+
+        let config = CoordinatorConfig {
+            replacement_contributors: vec![ContributorRef {
+                address: serde_json::from_str(r#""notanaleoaddress""#).unwrap(),
+            }],
+            ..test_config(out_dir.path())
+        };
+        let error = CoordinatorTomlConfiguration::try_from(&config).unwrap_err();
+        assert_eq!("Invalid replacement contributor address", error.to_string());
+    }
+
+    /// Test that custom [CoordinatorConfig::environment_parameters]
+    /// round-trip through the toml config exactly, including the `i64`
+    /// timeouts.
+    #[test]
+    fn test_environment_parameters_round_trip() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let parameters = EnvironmentParameters {
+            minimum_contributors_per_round: NonZeroUsize::new(1).unwrap(),
+            maximum_contributors_per_round: NonZeroUsize::new(10).unwrap(),
+            contributor_seen_timeout: 7_776_000_001,
+            participant_lock_timeout: -1,
+            queue_seen_timeout: i64::MAX,
+        };
+        let config = CoordinatorConfig {
+            environment_parameters: Some(parameters.clone()),
+            ..test_config(out_dir.path())
+        };
+
+        let toml_value = toml_config_value(&config);
+        let round_trip: EnvironmentParameters = toml_value["environment_parameters"]
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(parameters, round_trip);
+    }
+
+    /// Test that an enabled [CoordinatorConfig::reliability_check] is
+    /// written to the toml config, including its threshold and score.
+    #[test]
+    fn test_reliability_check_enabled() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig {
+            reliability_check: Some(ReliabilityCheckSettings {
+                is_enabled: true,
+                accept_threshold: NonZeroU8::new(5).unwrap(),
+                maximum_score: 10,
+                ..ReliabilityCheckSettings::default()
+            }),
+            ..test_config(out_dir.path())
+        };
+
+        let toml_value = toml_config_value(&config);
+        let reliability_check = &toml_value["reliability_check"];
+        assert_eq!(Some(true), reliability_check["is_enabled"].as_bool());
+        assert_eq!(Some(5), reliability_check["accept_threshold"].as_integer());
+        assert_eq!(Some(10), reliability_check["maximum_score"].as_integer());
+
+        let toml_value = toml_config_value(&test_config(out_dir.path()));
+        assert_eq!(
+            Some(false),
+            toml_value["reliability_check"]["is_enabled"].as_bool()
+        );
+    }
+
+    /// Test that the [CoordinatorConfig::runtime_parameters] override
+    /// appears verbatim in the toml config, and that the defaults are
+    /// used otherwise.
+    #[test]
+    fn test_runtime_parameters_override() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let runtime_parameters = |config: &CoordinatorConfig| -> RuntimeParameters {
+            let toml_value = toml_config_value(config);
+            toml_value["runtime_parameters"].clone().try_into().unwrap()
+        };
+
+        let parameters = RuntimeParameters {
+            operator_update_loop_delay: NonZeroU64::new(250).unwrap(),
+            rayon_global_pool_threads: NonZeroU16::new(4).unwrap(),
+        };
+        let config = CoordinatorConfig {
+            runtime_parameters: Some(parameters),
+            ..test_config(out_dir.path())
+        };
+        assert_eq!(parameters, runtime_parameters(&config));
+
+        assert_eq!(
+            RuntimeParameters::default(),
+            runtime_parameters(&test_config(out_dir.path()))
+        );
+    }
+
+    /// Test that [CoordinatorConfig::verifier_settings] overrides the
+    /// default verifier settings in the toml config.
+    #[test]
+    fn test_verifier_settings_override() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let verifier_settings = |config: &CoordinatorConfig| -> VerifierSettings {
+            let toml_value = toml_config_value(config);
+            assert!(toml_value["verifier_settings"]["assigned_tasks_cache_ttl"].is_integer());
+            toml_value["verifier_settings"].clone().try_into().unwrap()
+        };
+
+        let settings = VerifierSettings {
+            assigned_tasks_cache_ttl: NonZeroU64::new(5).unwrap(),
+            assigned_tasks_cache_records_cap: NonZeroUsize::new(100_000).unwrap(),
+        };
+        let config = CoordinatorConfig {
+            verifier_settings: Some(settings),
+            ..test_config(out_dir.path())
+        };
+        assert_eq!(settings, verifier_settings(&config));
+
+        assert_eq!(
+            VerifierSettings::default(),
+            verifier_settings(&test_config(out_dir.path()))
+        );
+    }
+
+    /// Test that the SQLite db path is written to the toml config as
+    /// an absolute path, both by default and when overridden.
+    #[test]
+    fn test_sqlite_file_absolute_path() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let canonical_out_dir = out_dir.path().canonicalize().unwrap();
+
+        let sqlite_file_value = |config: &CoordinatorConfig| -> PathBuf {
+            let toml_value = toml_config_value(config);
+            PathBuf::from(toml_value["sqlite_file"].as_str().unwrap())
+        };
+
+        assert_eq!(
+            canonical_out_dir.join("setup.db3"),
+            sqlite_file_value(&test_config(out_dir.path()))
+        );
+
+        std::fs::create_dir(out_dir.path().join("db")).unwrap();
+        let config = CoordinatorConfig {
+            sqlite_file: Some(PathBuf::from("db/coordinator1.db3")),
+            ..test_config(out_dir.path())
+        };
+        let sqlite_file = sqlite_file_value(&config);
+        assert!(sqlite_file.is_absolute());
+        assert_eq!(canonical_out_dir.join("db/coordinator1.db3"), sqlite_file);
+    }
+
+    /// Test that coordinators configured with distinct
+    /// [CoordinatorConfig::listen_address]es can run at the same time,
+    /// each bound to its own port. The fake coordinators bind the
+    /// `listen_address` from their configuration file (requires
+    /// `python3`).
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_listen_address_per_coordinator() {
+        // Find two free ports.
+        let addresses: Vec<SocketAddr> = (0..2)
+            .map(|_| {
+                std::net::TcpListener::bind("127.0.0.1:0")
+                    .unwrap()
+                    .local_addr()
+                    .unwrap()
+            })
+            .collect();
+        assert_ne!(addresses[0].port(), addresses[1].port());
+
+        let out_dirs: Vec<tempfile::TempDir> =
+            (0..2).map(|_| tempfile::tempdir().unwrap()).collect();
+        let coordinators: Vec<(Bus<CeremonyMessage>, CoordinatorJoin)> = addresses
             .iter()
-            .find(|round_contributor_id| round_contributor_id == &&contributor.id_on_coordinator())
-            .ok_or_else(|| {
-                eyre::eyre!(
-                    "Unable to find contributor {} in round state file",
-                    contributor.id_on_coordinator()
+            .zip(&out_dirs)
+            .map(|(address, out_dir)| {
+                let (mut config, _) = launch_mode_config(
+                    out_dir.path(),
+                    "address=$(sed -n 's/^listen_address = \"\\(.*\\)\"$/\\1/p' \"$2\"); \
+                    exec python3 -c \"\
+import socket, sys, time
+host, port = sys.argv[1].rsplit(':', 1)
+listener = socket.socket()
+listener.bind((host, int(port)))
+listener.listen()
+print('Coordinator has booted up', flush=True)
+time.sleep(60)
+\" \"$address\"",
+                    LaunchMode::Arg,
+                );
+                config.listen_address = *address;
+
+                let bus = Bus::<CeremonyMessage>::new(100);
+                let mut rx = bus.subscribe();
+                let join = run_coordinator(&config, bus.broadcaster(), bus.subscribe()).unwrap();
+                wait_for_message(
+                    &mut rx,
+                    |message| matches!(message, CeremonyMessage::CoordinatorReady),
+                    Duration::from_secs(10),
                 )
-            })?;
+                .unwrap();
+                (bus, join)
+            })
+            .collect();
+
+        // Each coordinator is listening on its own address, which is
+        // no longer free.
+        for address in &addresses {
+            std::net::TcpStream::connect(address).unwrap();
+            assert!(std::net::TcpListener::bind(address).is_err());
+        }
+
+        for (bus, join) in coordinators {
+            bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+                .unwrap();
+            join.join().unwrap();
+        }
+
+        let out_dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            default_listen_address(),
+            test_config(out_dir.path()).listen_address
+        );
     }
 
-    Ok(())
+    #[test]
+    fn test_num_powers_range_validation() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = |num_powers| CoordinatorConfig {
+            num_powers,
+            ..test_config(out_dir.path())
+        };
+
+        config(None).validate_num_powers().unwrap();
+        config(Some(*NUM_POWERS_RANGE.start()))
+            .validate_num_powers()
+            .unwrap();
+        config(Some(*NUM_POWERS_RANGE.end()))
+            .validate_num_powers()
+            .unwrap();
+        assert!(config(Some(0)).validate_num_powers().is_err());
+        assert!(config(Some(64)).validate_num_powers().is_err());
+    }
+
+    /// Test that [check_participants_in_round()] fails when a
+    /// verifier is missing from the round state file.
+    #[test]
+    fn test_check_participants_in_round_missing_verifier() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+        let address = TEST_ADDRESS.parse::<AleoPublicKey>().unwrap();
+        let contributor = Contributor {
+            id: "contributor1".to_string(),
+            key_file: out_dir.path().join("contributor1.keys"),
+            address: address.clone(),
+        };
+        let verifier = Verifier {
+            id: "verifier1".to_string(),
+            view_key_path: out_dir.path().join("verifier1.key"),
+            address: Some(address),
+        };
+
+        let round_dir = config.round_dir(1);
+        std::fs::create_dir_all(&round_dir).unwrap();
+        let write_state = |verifier_ids: &str| {
+            std::fs::write(
+                round_dir.join("state.json"),
+                format!(
+                    r#"{{"contributorIds": ["{}.contributor"], "verifierIds": [{}]}}"#,
+                    TEST_ADDRESS, verifier_ids
+                ),
+            )
+            .unwrap();
+        };
+
+        write_state(&format!(r#""{}.verifier""#, TEST_ADDRESS));
+        check_participants_in_round(&config, 1, &[contributor.clone()], &[verifier.clone()])
+            .unwrap();
+
+        write_state("");
+        let error = check_participants_in_round(&config, 1, &[contributor.clone()], &[verifier])
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains(&format!("{}.verifier", TEST_ADDRESS)));
+
+        // A verifier with an unknown address can't be checked.
+        write_state(&format!(r#""{}.verifier""#, TEST_ADDRESS));
+        let unknown_verifier = Verifier {
+            id: "verifier2".to_string(),
+            view_key_path: out_dir.path().join("verifier2.key"),
+            address: None,
+        };
+        let error = check_participants_in_round(&config, 1, &[contributor], &[unknown_verifier])
+            .unwrap_err();
+        assert!(error.to_string().contains("is unknown"));
+    }
+
+    /// Test that [check_participants_in_round()] returns the parsed
+    /// round state.
+    #[test]
+    fn test_check_participants_in_round_returns_state() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+        let contributors: Vec<Contributor> = (0..2)
+            .map(|i| Contributor {
+                id: format!("contributor{}", i),
+                key_file: out_dir.path().join(format!("contributor{}.keys", i)),
+                address: format!("aleo1{:0>58}", i).parse::<AleoPublicKey>().unwrap(),
+            })
+            .collect();
+
+        let round_dir = config.round_dir(1);
+        std::fs::create_dir_all(&round_dir).unwrap();
+        std::fs::write(
+            round_dir.join("state.json"),
+            format!(
+                r#"{{"contributorIds": ["{}", "{}"], "verifierIds": []}}"#,
+                contributors[0].id_on_coordinator(),
+                contributors[1].id_on_coordinator()
+            ),
+        )
+        .unwrap();
+
+        let state = check_participants_in_round(&config, 1, &contributors, &[]).unwrap();
+        assert_eq!(2, state.contributor_ids.len());
+        assert!(state.verifier_ids.is_empty());
+    }
+
+    /// Test that [check_participant_refs_in_round()] checks each
+    /// ref against the contributor or verifier ids depending on its
+    /// variant.
+    #[test]
+    fn test_check_participant_refs_in_round() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+        let contributor_address = TEST_ADDRESS.parse::<AleoPublicKey>().unwrap();
+        let verifier_address = format!("aleo1{:0>58}", 1).parse::<AleoPublicKey>().unwrap();
+        let contributor = ParticipantRef::Contributor(ContributorRef {
+            address: contributor_address.clone(),
+        });
+        let verifier = ParticipantRef::Verifier(VerifierRef {
+            address: verifier_address.clone(),
+        });
+
+        let round_dir = config.round_dir(1);
+        std::fs::create_dir_all(&round_dir).unwrap();
+        std::fs::write(
+            round_dir.join("state.json"),
+            format!(
+                r#"{{"contributorIds": ["{}.contributor"], "verifierIds": ["{}.verifier"]}}"#,
+                contributor_address, verifier_address
+            ),
+        )
+        .unwrap();
+
+        let state =
+            check_participant_refs_in_round(&config, 1, &[contributor.clone(), verifier.clone()])
+                .unwrap();
+        assert_eq!(1, state.contributor_ids.len());
+        assert_eq!(1, state.verifier_ids.len());
+
+        // The refs are only matched against the ids for their variant.
+        let contributor_as_verifier = ParticipantRef::Verifier(VerifierRef {
+            address: contributor_address,
+        });
+        let error =
+            check_participant_refs_in_round(&config, 1, &[contributor, contributor_as_verifier])
+                .unwrap_err();
+        assert!(error.to_string().contains("verifier"));
+
+        let verifier_as_contributor = ParticipantRef::Contributor(ContributorRef {
+            address: verifier_address,
+        });
+        let error =
+            check_participant_refs_in_round(&config, 1, &[verifier, verifier_as_contributor])
+                .unwrap_err();
+        assert!(error.to_string().contains("contributor"));
+    }
+
+    /// Test that [check_contributions_complete()] reports the chunks
+    /// which a contributor has not contributed to, and passes once
+    /// every chunk has a contribution from every contributor.
+    #[test]
+    fn test_check_contributions_complete() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+        let contributors: Vec<Contributor> = (0..2)
+            .map(|i| Contributor {
+                id: format!("contributor{}", i),
+                key_file: out_dir.path().join(format!("contributor{}.keys", i)),
+                address: format!("aleo1{:0>58}", i).parse::<AleoPublicKey>().unwrap(),
+            })
+            .collect();
+        let ids: Vec<String> = contributors
+            .iter()
+            .map(Contributor::id_on_coordinator)
+            .collect();
+
+        let round_dir = config.round_dir(1);
+        std::fs::create_dir_all(&round_dir).unwrap();
+        let write_state = |chunk_1_contributors: &[&str]| {
+            let chunk_1_contributions: Vec<String> = chunk_1_contributors
+                .iter()
+                .enumerate()
+                .map(|(i, id)| format!(r#""{}": {{"contributorId": "{}"}}"#, i + 1, id))
+                .collect();
+            std::fs::write(
+                round_dir.join("state.json"),
+                format!(
+                    r#"{{
+                        "contributorIds": ["{0}", "{1}"],
+                        "verifierIds": [],
+                        "chunks": [
+                            {{"chunkId": 0, "contributions": {{
+                                "0": {{"contributorId": null}},
+                                "1": {{"contributorId": "{0}"}},
+                                "2": {{"contributorId": "{1}"}}
+                            }}}},
+                            {{"chunkId": 1, "contributions": {{
+                                "0": {{"contributorId": null}}{2}{3}
+                            }}}}
+                        ]
+                    }}"#,
+                    ids[0],
+                    ids[1],
+                    if chunk_1_contributions.is_empty() {
+                        ""
+                    } else {
+                        ", "
+                    },
+                    chunk_1_contributions.join(", ")
+                ),
+            )
+            .unwrap();
+        };
+
+        write_state(&[&ids[0]]);
+        let error = check_contributions_complete(&config, 1, &contributors).unwrap_err();
+        let error_message = error.to_string();
+        assert!(error_message.contains(&format!("{} (chunks [1])", ids[1])));
+        assert!(!error_message.contains(&ids[0]));
+
+        write_state(&[&ids[0], &ids[1]]);
+        let state = check_contributions_complete(&config, 1, &contributors).unwrap();
+        assert_eq!(2, state.chunks.len());
+    }
+
+    /// Test that the coordinator is launched with `RUST_LOG` set from
+    /// [CoordinatorConfig::log_level].
+    #[cfg(unix)]
+    #[test]
+    fn test_log_level_env_var() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut config, toml_config_path) =
+            launch_mode_config(out_dir.path(), "echo \"$RUST_LOG\"", LaunchMode::Arg);
+        assert_eq!(DEFAULT_COORDINATOR_LOG_LEVEL, config.log_level);
+
+        config.log_level = "trace,hyper=warn".to_string();
+        config.validate_log_level().unwrap();
+        let output = coordinator_exec(&config, &toml_config_path)
+            .unwrap()
+            .stdout(Redirection::Pipe)
+            .capture()
+            .unwrap()
+            .stdout_str();
+        assert_eq!("trace,hyper=warn", output.trim());
+
+        config.log_level = "debug,hyper=loud".to_string();
+        assert!(config.validate_log_level().is_err());
+    }
+
+    /// Test that a missing coordinator binary is reported clearly.
+    #[test]
+    fn test_coordinator_bin_missing() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+
+        let error = config.validate_coordinator_bin().unwrap_err();
+        assert!(format!("{}", error).contains("does not exist"));
+        assert!(format!("{}", error).contains("aleo-setup-coordinator"));
+
+        std::fs::create_dir(&config.setup_coordinator_bin).unwrap();
+        let error = config.validate_coordinator_bin().unwrap_err();
+        assert!(format!("{}", error).contains("is not a file"));
+    }
+
+    /// Test that a coordinator binary without the executable bit set
+    /// is rejected before it is launched.
+    #[cfg(unix)]
+    #[test]
+    fn test_coordinator_bin_not_executable() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (config, _) = launch_mode_config(out_dir.path(), "exit 0", LaunchMode::Arg);
+        config.validate_coordinator_bin().unwrap();
+
+        std::fs::set_permissions(
+            &config.setup_coordinator_bin,
+            std::fs::Permissions::from_mode(0o644),
+        )
+        .unwrap();
+        let error = config.validate_coordinator_bin().unwrap_err();
+        assert!(format!("{}", error).contains("is not executable"));
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        assert!(run_coordinator(&config, bus.broadcaster(), bus.subscribe()).is_err());
+    }
+
+    /// Test that `coordinator.log` is rolled over to
+    /// `coordinator.log.1` when it exceeds the maximum size, without
+    /// losing or splitting lines.
+    #[cfg(unix)]
+    #[test]
+    fn test_log_file_rolled_over() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) =
+            fake_coordinator("for i in 1 2 3 4 5 6 7 8 9; do echo \"log line $i\"; done");
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let log_file_path = out_dir.path().join("coordinator.log");
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions {
+                max_log_size: Some(40),
+                ..CoordinatorLogOptions::new(log_file_path.clone())
+            },
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        // Each line is 11 bytes, so 3 lines fit in each file.
+        let rolled_log = std::fs::read_to_string(out_dir.path().join("coordinator.log.1")).unwrap();
+        let log = std::fs::read_to_string(&log_file_path).unwrap();
+        assert_eq!("log line 4\nlog line 5\nlog line 6\n", rolled_log);
+        assert_eq!("log line 7\nlog line 8\nlog line 9\n", log);
+    }
+
+    /// Test that a line written to the log file is on disk
+    /// immediately, even if the harness stops abruptly without
+    /// dropping (and so flushing) the file.
+    #[test]
+    fn test_log_line_on_disk_after_abrupt_stop() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let log_file_path = out_dir.path().join("coordinator.log");
+
+        let mut log_file = RotatingLogFile::open(&log_file_path, None).unwrap();
+        log_file.write_line("Coordinator has booted up").unwrap();
+        std::mem::forget(log_file);
+
+        assert_eq!(
+            "Coordinator has booted up\n",
+            std::fs::read_to_string(&log_file_path).unwrap()
+        );
+    }
+
+    /// Test that with timestamps enabled, each line in
+    /// `coordinator.log` is prefixed with an RFC3339 timestamp
+    /// followed by the coordinator's unchanged output.
+    #[cfg(unix)]
+    #[test]
+    fn test_log_timestamps() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator("echo 'Coordinator has booted up'");
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let log_file_path = out_dir.path().join("coordinator.log");
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions {
+                timestamps: true,
+                ..CoordinatorLogOptions::new(log_file_path.clone())
+            },
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let log = std::fs::read_to_string(&log_file_path).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(1, lines.len());
+
+        let timestamp_re =
+            Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}Z Coordinator has booted up$")
+                .unwrap();
+        assert!(timestamp_re.is_match(lines[0]), "{:?}", lines[0]);
+
+        let (timestamp, raw) = lines[0].split_at(lines[0].find(' ').unwrap());
+        humantime::parse_rfc3339(timestamp).unwrap();
+        assert_eq!(" Coordinator has booted up", raw);
+        assert!(BOOTED_RE.is_match(raw));
+    }
+
+    /// Test that with a JSON log enabled, each line is recorded along
+    /// with the message it caused (if any).
+    #[cfg(unix)]
+    #[test]
+    fn test_json_log_records_events() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(&format!(
+            "echo 'Coordinator has booted up'; \
+            echo 'Advanced ceremony to round 1'; \
+            echo 'Some other output'; \
+            echo '{}.contributor added a contribution to chunk 2'",
+            TEST_ADDRESS
+        ));
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let _rx = bus.subscribe();
+        let json_log_path = out_dir.path().join("coordinator.jsonl");
+        monitor_coordinator(
+            ProcessOutput {
+                stdout,
+                stderr: None,
+            },
+            bus.broadcaster(),
+            None,
+            CoordinatorLogOptions {
+                json_log_path: Some(json_log_path.clone()),
+                ..CoordinatorLogOptions::new(out_dir.path().join("coordinator.log"))
+            },
+            ReporterOptions::default(),
+            CoordinatorStateHistory::default(),
+            BroadcastRetryOptions::default(),
+        )
+        .unwrap();
+        process.wait().unwrap();
+
+        let records: Vec<CoordinatorLogRecord> = std::fs::read_to_string(&json_log_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(4, records.len());
+        assert_eq!(Some(CeremonyMessage::CoordinatorReady), records[0].event);
+        assert_eq!(Some(CeremonyMessage::RoundStarted(1)), records[1].event);
+        assert_eq!("Some other output", records[2].raw);
+        assert_eq!(None, records[2].event);
+        assert_eq!(
+            Some(CeremonyMessage::SuccessfulContribution {
+                contributor: ContributorRef {
+                    address: TEST_ADDRESS.parse().unwrap(),
+                },
+                chunk: 2,
+                seq: 0,
+            }),
+            records[3].event
+        );
+
+        let raw_record: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&records[2]).unwrap()).unwrap();
+        assert!(raw_record["event"].is_null());
+        assert!(raw_record["ts"].is_string());
+    }
+
+    /// Test that the ceremony is shut down when the coordinator's
+    /// state stops changing for longer than the stall timeout.
+    #[cfg(unix)]
+    #[test]
+    fn test_stall_monitor_shutdown() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let (mut process, stdout) = fake_coordinator(
+            "echo 'Coordinator has booted up'; \
+            echo 'Advanced ceremony to round 1'; \
+            sleep 10",
+        );
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let stall_join = run_stall_monitor(
+            Duration::from_millis(500),
+            state_history.clone(),
+            bus.broadcaster(),
+            bus.subscribe(),
+            BroadcastRetryOptions::default(),
+        );
+
+        let ceremony_tx = bus.broadcaster();
+        let log_file_path = out_dir.path().join("coordinator.log");
+        let monitor_state_history = state_history.clone();
+        std::thread::spawn(move || {
+            monitor_coordinator(
+                ProcessOutput {
+                    stdout,
+                    stderr: None,
+                },
+                ceremony_tx,
+                None,
+                CoordinatorLogOptions::new(log_file_path),
+                ReporterOptions::default(),
+                monitor_state_history,
+                BroadcastRetryOptions::default(),
+            )
+        });
+
+        let start = std::time::Instant::now();
+        let shutdown = loop {
+            match rx.try_recv() {
+                Ok(CeremonyMessage::Shutdown(reason)) => break reason,
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => {
+                    assert!(start.elapsed() < Duration::from_secs(5));
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(TryRecvError::Disconnected) => panic!("`rx` disconnected"),
+            }
+        };
+        assert_eq!(ShutdownReason::Error, shutdown);
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert_eq!(
+            Some(PublicCoordinatorState::RoundRunning(1)),
+            state_history.current_state()
+        );
+
+        assert!(stall_join.join().unwrap().is_err());
+        process.kill().unwrap();
+    }
+
+    /// Test that the time the coordinator takes to boot is not
+    /// counted as a stall.
+    #[test]
+    fn test_stall_monitor_waits_for_boot() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let stall_join = run_stall_monitor(
+            Duration::from_millis(200),
+            CoordinatorStateHistory::default(),
+            bus.broadcaster(),
+            bus.subscribe(),
+            BroadcastRetryOptions::default(),
+        );
+
+        std::thread::sleep(Duration::from_millis(600));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        let ready = std::time::Instant::now();
+        bus.broadcast(CeremonyMessage::CoordinatorReady).unwrap();
+        let shutdown = wait_for_message(
+            &mut rx,
+            |message| matches!(message, CeremonyMessage::Shutdown(_)),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        assert_eq!(CeremonyMessage::Shutdown(ShutdownReason::Error), shutdown);
+        assert!(ready.elapsed() >= Duration::from_millis(200));
+        assert!(stall_join.join().unwrap().is_err());
+    }
+
+    /// Test that [CeremonyMessage::RoundStateWritten] is broadcast once
+    /// the coordinator writes the `state.json` file for a round.
+    #[test]
+    fn test_round_state_watcher() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = test_config(out_dir.path());
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let watcher_join = run_round_state_watcher(
+            config.clone(),
+            Duration::from_millis(50),
+            bus.broadcaster(),
+            bus.subscribe(),
+        );
+
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(matches!(rx.try_recv(), Err(TryRecvError::Empty)));
+
+        std::fs::create_dir_all(config.round_dir(1)).unwrap();
+        std::fs::write(config.round_state_file(1), "{}").unwrap();
+
+        let start = std::time::Instant::now();
+        let message = loop {
+            match rx.try_recv() {
+                Ok(message) => break message,
+                Err(TryRecvError::Empty) => {
+                    assert!(start.elapsed() < Duration::from_secs(5));
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(TryRecvError::Disconnected) => panic!("`rx` disconnected"),
+            }
+        };
+        assert_eq!(CeremonyMessage::RoundStateWritten(1), message);
+
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        watcher_join.join().unwrap().unwrap();
+    }
+
+    /// Test that heartbeats are broadcast with the current state until
+    /// the ceremony shuts down.
+    #[test]
+    fn test_heartbeat() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let state_history = CoordinatorStateHistory::default();
+        let mut reporter = CoordinatorStateReporter::process_started(
+            bus.broadcaster(),
+            None,
+            state_history.clone(),
+            BroadcastRetryOptions::default(),
+        );
+        for line in &["Coordinator has booted up", "Advanced ceremony to round 1"] {
+            reporter.parse_output_line(line).unwrap();
+        }
+
+        let heartbeat_join = run_heartbeat(
+            Duration::from_millis(50),
+            state_history,
+            bus.broadcaster(),
+            bus.subscribe(),
+            BroadcastRetryOptions::default(),
+        );
+
+        let start = std::time::Instant::now();
+        let mut heartbeats = Vec::new();
+        while heartbeats.len() < 2 {
+            match rx.try_recv() {
+                Ok(message @ CeremonyMessage::Heartbeat { .. }) => heartbeats.push(message),
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => {
+                    assert!(start.elapsed() < Duration::from_secs(5));
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(TryRecvError::Disconnected) => panic!("`rx` disconnected"),
+            }
+        }
+        for heartbeat in heartbeats {
+            assert_eq!(
+                CeremonyMessage::Heartbeat {
+                    round: Some(1),
+                    state_name: "RoundRunning".to_string(),
+                },
+                heartbeat
+            );
+        }
+
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        heartbeat_join.join().unwrap().unwrap();
+    }
+
+    /// Test that the `[twitter_settings]` section is only included in
+    /// the coordinator's configuration when it has been set.
+    #[test]
+    fn test_twitter_settings_optional() {
+        let out_dir = tempfile::tempdir().unwrap();
+
+        let config = test_config(out_dir.path());
+        assert!(toml_config_value(&config).get("twitter_settings").is_none());
+
+        let config = CoordinatorConfig {
+            twitter_settings: Some(TwitterSettings {
+                consumer_token: "token".to_string(),
+                consumer_secret: "secret".to_string(),
+            }),
+            ..test_config(out_dir.path())
+        };
+        let toml_value = toml_config_value(&config);
+        assert_eq!(
+            "token",
+            toml_value["twitter_settings"]["consumer_token"]
+                .as_str()
+                .unwrap()
+        );
+        assert_eq!(
+            "secret",
+            toml_value["twitter_settings"]["consumer_secret"]
+                .as_str()
+                .unwrap()
+        );
+    }
+
+    /// Test that the [TwitterSettings] are read from the environment
+    /// into the coordinator's configuration, falling back to the
+    /// placeholders for the variables which are not set.
+    #[test]
+    fn test_twitter_settings_from_env() {
+        let env = |token: Option<&str>, secret: Option<&str>| {
+            let token = token.map(str::to_string);
+            let secret = secret.map(str::to_string);
+            move |name: &str| match name {
+                TWITTER_CONSUMER_TOKEN_ENV => token.clone(),
+                TWITTER_CONSUMER_SECRET_ENV => secret.clone(),
+                _ => None,
+            }
+        };
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut config = test_config(out_dir.path());
+        config.twitter_settings = Some(TwitterSettings::from_lookup(env(
+            Some("env_token"),
+            Some("env_secret"),
+        )));
+        let toml_value = toml_config_value(&config);
+        assert_eq!(
+            "env_token",
+            toml_value["twitter_settings"]["consumer_token"]
+                .as_str()
+                .unwrap()
+        );
+        assert_eq!(
+            "env_secret",
+            toml_value["twitter_settings"]["consumer_secret"]
+                .as_str()
+                .unwrap()
+        );
+
+        assert_eq!(
+            TwitterSettings {
+                consumer_token: "env_token".to_string(),
+                consumer_secret: "some_secret".to_string(),
+            },
+            TwitterSettings::from_lookup(env(Some("env_token"), None))
+        );
+        assert_eq!(
+            TwitterSettings {
+                consumer_token: "some_token".to_string(),
+                consumer_secret: "some_secret".to_string(),
+            },
+            TwitterSettings::from_lookup(env(None, None))
+        );
+    }
 }
@@ -0,0 +1,167 @@
+//! Read-only access to the coordinator's SQLite database (see
+//! [CoordinatorConfig::sqlite_file()]), for making assertions about
+//! the coordinator's internal state which are not visible in its log
+//! output or transcript. Requires the `sqlite` feature.
+
+use std::{collections::BTreeMap, path::Path};
+
+use eyre::Context;
+use rusqlite::{types::Value, Connection, OpenFlags};
+
+use crate::coordinator::CoordinatorConfig;
+
+/// A row read from a table in the coordinator's database, mapping
+/// column names to their values.
+pub type DbRow = BTreeMap<String, Value>;
+
+/// The tables extracted from the coordinator's database by
+/// [read_coordinator_db()].
+#[derive(Debug, Clone, Default)]
+pub struct CoordinatorDbSnapshot {
+    /// Rows of the `rounds` table.
+    pub rounds: Vec<DbRow>,
+    /// Rows of the `participants` table.
+    pub participants: Vec<DbRow>,
+    /// Rows of the `tasks` table.
+    pub tasks: Vec<DbRow>,
+}
+
+impl CoordinatorDbSnapshot {
+    pub fn round_count(&self) -> usize {
+        self.rounds.len()
+    }
+
+    pub fn participant_count(&self) -> usize {
+        self.participants.len()
+    }
+
+    pub fn task_count(&self) -> usize {
+        self.tasks.len()
+    }
+}
+
+/// Read a [CoordinatorDbSnapshot] from the coordinator's database.
+///
+/// To avoid interfering with a coordinator which is still running,
+/// the database (and its write-ahead log, if there is one) is first
+/// copied into a temporary snapshot file in
+/// [CoordinatorConfig::out_dir], and the copy is opened read-only.
+/// Tables which don't exist are read as empty.
+pub fn read_coordinator_db(config: &CoordinatorConfig) -> eyre::Result<CoordinatorDbSnapshot> {
+    let db_file = config.sqlite_file();
+    let snapshot_file = config.out_dir.join("setup.snapshot.db3");
+
+    std::fs::copy(&db_file, &snapshot_file).wrap_err_with(|| {
+        eyre::eyre!(
+            "Unable to copy coordinator database {:?} to {:?}",
+            db_file,
+            snapshot_file
+        )
+    })?;
+
+    let wal_file = db_file.with_extension("db3-wal");
+    let snapshot_wal_file = snapshot_file.with_extension("db3-wal");
+    if wal_file.exists() {
+        std::fs::copy(&wal_file, &snapshot_wal_file).wrap_err_with(|| {
+            eyre::eyre!("Unable to copy coordinator database WAL {:?}", wal_file)
+        })?;
+    }
+
+    let result = read_db_file(&snapshot_file);
+
+    std::fs::remove_file(&snapshot_file)?;
+    if snapshot_wal_file.exists() {
+        std::fs::remove_file(&snapshot_wal_file)?;
+    }
+
+    result.wrap_err_with(|| eyre::eyre!("Unable to read coordinator database {:?}", db_file))
+}
+
+fn read_db_file(path: impl AsRef<Path>) -> eyre::Result<CoordinatorDbSnapshot> {
+    let connection = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+
+    Ok(CoordinatorDbSnapshot {
+        rounds: read_table(&connection, "rounds")?,
+        participants: read_table(&connection, "participants")?,
+        tasks: read_table(&connection, "tasks")?,
+    })
+}
+
+/// Read all the rows of the `table`, or no rows if it does not exist.
+fn read_table(connection: &Connection, table: &str) -> eyre::Result<Vec<DbRow>> {
+    let exists: bool = connection.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get(0),
+    )?;
+
+    if !exists {
+        tracing::warn!("Table {:?} not found in coordinator database.", table);
+        return Ok(Vec::new());
+    }
+
+    let mut statement = connection.prepare(&format!("SELECT * FROM \"{}\"", table))?;
+    let column_names: Vec<String> = statement
+        .column_names()
+        .into_iter()
+        .map(ToString::to_string)
+        .collect();
+
+    let rows = statement.query_map([], |row| {
+        column_names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Ok((name.clone(), row.get::<_, Value>(i)?)))
+            .collect::<rusqlite::Result<DbRow>>()
+    })?;
+
+    rows.collect::<rusqlite::Result<Vec<DbRow>>>()
+        .wrap_err_with(|| eyre::eyre!("Unable to read rows of table {:?}", table))
+}
+
+#[cfg(test)]
+mod test {
+    use rusqlite::{types::Value, Connection};
+
+    use super::read_coordinator_db;
+    use crate::coordinator::{CoordinatorConfig, CoordinatorConfigProfile};
+
+    /// Test reading the round and participant tables from a fixture
+    /// database.
+    #[test]
+    fn test_read_coordinator_db() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig::from_profile(
+            CoordinatorConfigProfile::Smoke,
+            out_dir.path().to_owned(),
+            out_dir.path().join("aleo-setup-coordinator"),
+            out_dir.path().to_owned(),
+        );
+
+        let connection = Connection::open(config.sqlite_file()).unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE rounds (height INTEGER PRIMARY KEY, finished INTEGER);
+                CREATE TABLE participants (address TEXT PRIMARY KEY, round INTEGER);
+                INSERT INTO rounds VALUES (1, 1), (2, 0);
+                INSERT INTO participants VALUES
+                    ('aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689', 1),
+                    ('aleo1qgqkzmv9jzq3y2ykl4vdm2mcaa7wh8kl3jzjy2cm8tg63k9xcsqqyvsw5m', 2),
+                    ('verifier1', 2);",
+            )
+            .unwrap();
+
+        let snapshot = read_coordinator_db(&config).unwrap();
+        assert_eq!(2, snapshot.round_count());
+        assert_eq!(3, snapshot.participant_count());
+        assert_eq!(0, snapshot.task_count());
+        assert_eq!(Some(&Value::Integer(2)), snapshot.rounds[1].get("height"));
+
+        // The live database is left in place, the snapshot is removed.
+        assert!(config.sqlite_file().exists());
+        assert!(!out_dir.path().join("setup.snapshot.db3").exists());
+    }
+}
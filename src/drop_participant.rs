@@ -3,7 +3,10 @@ use crate::{CeremonyMessage, ContributorRef, ParticipantRef, ShutdownReason};
 use mpmc_bus::{Receiver, Sender};
 use serde::{Deserialize, Serialize};
 
-use std::{collections::HashMap, thread::JoinHandle};
+use std::{
+    collections::{HashMap, HashSet},
+    thread::JoinHandle,
+};
 
 /// The configuration for dropping a contributor from the ceremony.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,13 +27,17 @@ pub struct MonitorDropsConfig {
 
 /// Monitor the ceremony for dropped contributors. Returns an error if
 /// an unexpected drop occurs or if not all expected drops have
-/// occurred.
+/// occurred. Contributors which have been restarted by the watchdog
+/// (see [CeremonyMessage::ContributorRestarted]) are allowed to be
+/// dropped, because the coordinator may drop them while they are
+/// being relaunched.
 pub fn monitor_drops(
     config: MonitorDropsConfig,
     mut ceremony_rx: Receiver<CeremonyMessage>,
     ceremony_tx: Sender<CeremonyMessage>,
 ) -> JoinHandle<eyre::Result<()>> {
     let mut contributor_drops = config.contributor_drops;
+    let mut restarted_contributors: HashSet<ContributorRef> = HashSet::new();
     let span = tracing::error_span!("monitor_drops");
     std::thread::spawn(move || {
         let _guard = span.enter();
@@ -43,7 +50,8 @@ pub fn monitor_drops(
                     break;
                 }
                 CeremonyMessage::Shutdown(reason) => {
-                    if let ShutdownReason::TestFinished = reason {
+                    if let ShutdownReason::TestFinished | ShutdownReason::HarnessRequested = reason
+                    {
                         check_drops(&contributor_drops)?;
                     }
 
@@ -59,6 +67,14 @@ pub fn monitor_drops(
                             continue;
                             // TODO: check that participant was dropped after the correct number of contributions.
                         }
+
+                        if restarted_contributors.contains(contributor) {
+                            tracing::info!(
+                                "Restarted participant {:?} dropped during the round.",
+                                &participant
+                            );
+                            continue;
+                        }
                     }
 
                     ceremony_tx.broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))?;
@@ -67,6 +83,9 @@ pub fn monitor_drops(
                         &participant
                     ));
                 }
+                CeremonyMessage::ContributorRestarted(contributor) => {
+                    restarted_contributors.insert(contributor);
+                }
                 _ => {}
             }
         }
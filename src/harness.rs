@@ -0,0 +1,339 @@
+//! A [TestHarness] which owns the ceremony bus and the handles of
+//! every process started during a test, so that their lifecycle is
+//! managed in one place.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use mpmc_bus::{Bus, Receiver, Sender};
+
+use crate::{
+    contributor::{run_contributor, ContributorConfig, ContributorJoin},
+    coordinator::{run_coordinator, CoordinatorConfig, CoordinatorJoin},
+    process::{wait_for_shutdown, MonitorProcessJoin},
+    verifier::run_verifier,
+    CeremonyMessage, ShutdownReason,
+};
+
+/// Capacity of the bus created by [TestHarness::new()].
+const HARNESS_BUS_CAPACITY: usize = 1000;
+
+/// Default value for [TestHarness::set_shutdown_timeout()].
+pub const DEFAULT_HARNESS_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Owns the ceremony [Bus] and the joins for the coordinator and
+/// participants started through it. When the harness is dropped
+/// without having been shut down (e.g. because a test panicked), it
+/// tells the ceremony to shut down with [ShutdownReason::Error] and
+/// waits for the processes to exit, so that they are not leaked.
+pub struct TestHarness {
+    bus: Bus<CeremonyMessage>,
+    coordinator: Option<CoordinatorJoin>,
+    contributors: Vec<ContributorJoin>,
+    verifiers: Vec<MonitorProcessJoin>,
+    shutdown_timeout: Duration,
+}
+
+impl std::fmt::Debug for TestHarness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestHarness")
+            .field("coordinator", &self.coordinator)
+            .field("contributors", &self.contributors)
+            .field("verifiers", &self.verifiers)
+            .field("shutdown_timeout", &self.shutdown_timeout)
+            .finish()
+    }
+}
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestHarness {
+    /// Create a new harness with an empty bus and no processes
+    /// running.
+    pub fn new() -> Self {
+        Self {
+            bus: Bus::new(HARNESS_BUS_CAPACITY),
+            coordinator: None,
+            contributors: Vec::new(),
+            verifiers: Vec::new(),
+            shutdown_timeout: DEFAULT_HARNESS_SHUTDOWN_TIMEOUT,
+        }
+    }
+
+    /// Set how long to wait for the processes to exit during
+    /// teardown. Default: [DEFAULT_HARNESS_SHUTDOWN_TIMEOUT].
+    pub fn set_shutdown_timeout(&mut self, timeout: Duration) {
+        self.shutdown_timeout = timeout;
+    }
+
+    /// A sender for broadcasting messages to the ceremony.
+    pub fn sender(&self) -> Sender<CeremonyMessage> {
+        self.bus.broadcaster()
+    }
+
+    /// Subscribe to the messages broadcast to the ceremony from this
+    /// point onwards.
+    pub fn subscribe(&self) -> Receiver<CeremonyMessage> {
+        self.bus.subscribe()
+    }
+
+    /// Run the coordinator, see [run_coordinator()]. Only one
+    /// coordinator may be started per harness.
+    pub fn start_coordinator(
+        &mut self,
+        config: &CoordinatorConfig,
+    ) -> eyre::Result<&CoordinatorJoin> {
+        if self.coordinator.is_some() {
+            return Err(eyre::eyre!(
+                "A coordinator has already been started by this harness."
+            ));
+        }
+
+        let join = run_coordinator(config, self.sender(), self.subscribe())?;
+        Ok(self.coordinator.get_or_insert(join))
+    }
+
+    /// Run a contributor, see [run_contributor()].
+    pub fn start_contributor(
+        &mut self,
+        config: ContributorConfig,
+    ) -> eyre::Result<&ContributorJoin> {
+        let join = run_contributor(config, self.sender(), self.subscribe())?;
+        self.contributors.push(join);
+        Ok(self
+            .contributors
+            .last()
+            .expect("contributor was just added"))
+    }
+
+    /// Run a verifier, see [run_verifier()].
+    pub fn start_verifier(
+        &mut self,
+        id: &str,
+        verifier_bin_path: impl AsRef<Path>,
+        coordinator_api_url: &str,
+        view_key_path: impl AsRef<Path>,
+        out_dir: PathBuf,
+    ) -> eyre::Result<&MonitorProcessJoin> {
+        let join = run_verifier(
+            id,
+            verifier_bin_path,
+            coordinator_api_url,
+            view_key_path,
+            self.sender(),
+            self.subscribe(),
+            out_dir,
+        )?;
+        self.verifiers.push(join);
+        Ok(self.verifiers.last().expect("verifier was just added"))
+    }
+
+    /// The coordinator, if it has been started.
+    pub fn coordinator(&self) -> Option<&CoordinatorJoin> {
+        self.coordinator.as_ref()
+    }
+
+    /// The contributors which have been started.
+    pub fn contributors(&self) -> &[ContributorJoin] {
+        &self.contributors
+    }
+
+    /// The verifiers which have been started.
+    pub fn verifiers(&self) -> &[MonitorProcessJoin] {
+        &self.verifiers
+    }
+
+    /// Tell the ceremony to shut down with
+    /// [ShutdownReason::HarnessRequested], and wait for all the
+    /// processes to exit.
+    pub fn shutdown(mut self) -> eyre::Result<()> {
+        self.bus
+            .broadcast(CeremonyMessage::Shutdown(ShutdownReason::HarnessRequested))?;
+        self.teardown()
+    }
+
+    /// Wait for all the processes to exit after the ceremony has shut
+    /// itself down (e.g. after [CoordinatorConfig::max_rounds]).
+    pub fn wait_for_shutdown(mut self) -> eyre::Result<()> {
+        self.teardown()
+    }
+
+    /// Whether any processes are still owned by this harness.
+    fn has_processes(&self) -> bool {
+        self.coordinator.is_some() || !self.contributors.is_empty() || !self.verifiers.is_empty()
+    }
+
+    /// Wait for every process owned by this harness to exit, within
+    /// a total of [TestHarness::set_shutdown_timeout()].
+    fn teardown(&mut self) -> eyre::Result<()> {
+        let deadline = Instant::now() + self.shutdown_timeout;
+        let remaining = || deadline.saturating_duration_since(Instant::now());
+
+        let mut errors: Vec<eyre::Error> = Vec::new();
+        let mut wait = |result: eyre::Result<()>| {
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        };
+
+        for join in std::mem::take(&mut self.contributors) {
+            wait(wait_for_shutdown(join, remaining()));
+        }
+        for join in std::mem::take(&mut self.verifiers) {
+            wait(wait_for_shutdown(join, remaining()));
+        }
+        if let Some(join) = self.coordinator.take() {
+            wait(wait_for_shutdown(join, remaining()));
+        }
+
+        match errors.pop() {
+            Some(error) => {
+                for other in &errors {
+                    tracing::error!("{:?}", other);
+                }
+                Err(error)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        if !self.has_processes() {
+            return;
+        }
+
+        tracing::warn!("Test harness dropped before shutdown, shutting down the ceremony.");
+        if let Err(error) = self
+            .bus
+            .broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))
+        {
+            tracing::error!("Error sending shutdown message: {}", error);
+        }
+
+        if let Err(error) = self.teardown() {
+            tracing::error!("Error during test harness teardown: {:?}", error);
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::time::Duration;
+
+    use super::TestHarness;
+    use crate::{
+        coordinator::{CoordinatorConfig, CoordinatorConfigProfile, PublicCoordinatorState},
+        util::{fake_bin, fake_contributor_config, TEST_ADDRESS},
+        CeremonyMessage, ShutdownReason,
+    };
+
+    /// Test running a full (fake) single round ceremony with a
+    /// coordinator, a contributor and a verifier through the harness.
+    #[test]
+    fn test_harness_fake_ceremony() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let heartbeat = "while true; do date +%s%N; sleep 0.05; done";
+
+        let coordinator_bin = fake_bin(
+            out_dir.path(),
+            "aleo-setup-coordinator",
+            &format!(
+                "sleep 0.5; \
+                echo 'Coordinator has booted up'; \
+                echo 'Advanced ceremony to round 1'; \
+                echo '{address}.contributor added a contribution to chunk 0'; \
+                echo '{address}.verifier verified chunk 0'; \
+                echo 'Starting aggregation on round 1'; \
+                echo 'Round 1 is aggregated'; \
+                echo 'Round 1 is finished'",
+                address = TEST_ADDRESS
+            ),
+        );
+        let mut coordinator_config = CoordinatorConfig::from_profile(
+            CoordinatorConfigProfile::Smoke,
+            out_dir.path().to_owned(),
+            coordinator_bin,
+            out_dir.path().to_owned(),
+        );
+        coordinator_config.max_rounds = Some(1);
+
+        let contributor_config = fake_contributor_config(
+            out_dir.path(),
+            fake_bin(out_dir.path(), "setup1-contributor", heartbeat),
+        );
+
+        let view_key_path = out_dir.path().join("verifier.key");
+        std::fs::write(&view_key_path, "view_key").unwrap();
+
+        let mut harness = TestHarness::new();
+        harness.set_shutdown_timeout(Duration::from_secs(20));
+        harness.start_coordinator(&coordinator_config).unwrap();
+        harness.start_contributor(contributor_config).unwrap();
+        harness
+            .start_verifier(
+                "verifier1",
+                fake_bin(out_dir.path(), "setup1-verifier", heartbeat),
+                "http://localhost:9000",
+                &view_key_path,
+                out_dir.path().to_owned(),
+            )
+            .unwrap();
+
+        assert_eq!(1, harness.contributors().len());
+        assert_eq!(1, harness.verifiers().len());
+
+        let state_history = harness.coordinator().unwrap().state_history().clone();
+        harness.wait_for_shutdown().unwrap();
+
+        state_history
+            .assert_state_sequence(&[
+                PublicCoordinatorState::ProcessStarted,
+                PublicCoordinatorState::RoundWaitingForParticipants(1),
+                PublicCoordinatorState::RoundRunning(1),
+                PublicCoordinatorState::RoundAggregating(1),
+                PublicCoordinatorState::RoundWaitingForFinish(1),
+                PublicCoordinatorState::LastRoundFinished(1),
+            ])
+            .unwrap();
+    }
+
+    /// Test that shutting down the harness tells the ceremony to
+    /// shut down with [ShutdownReason::HarnessRequested].
+    #[test]
+    fn test_harness_shutdown_reason() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let coordinator_bin = fake_bin(
+            out_dir.path(),
+            "aleo-setup-coordinator",
+            "echo 'Coordinator has booted up'; while true; do sleep 0.05; done",
+        );
+        let coordinator_config = CoordinatorConfig::from_profile(
+            CoordinatorConfigProfile::Smoke,
+            out_dir.path().to_owned(),
+            coordinator_bin,
+            out_dir.path().to_owned(),
+        );
+
+        let mut harness = TestHarness::new();
+        harness.set_shutdown_timeout(Duration::from_secs(20));
+        let mut rx = harness.subscribe();
+        harness.start_coordinator(&coordinator_config).unwrap();
+        harness.shutdown().unwrap();
+
+        let shutdowns: Vec<ShutdownReason> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|message| match message {
+                CeremonyMessage::Shutdown(reason) => Some(reason),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec![ShutdownReason::HarnessRequested], shutdowns);
+    }
+}
@@ -1,35 +1,57 @@
 use serde::{Deserialize, Serialize};
 use waiter::IsShutdownMessage;
 
-use std::{fmt::Display, str::FromStr};
+use std::{fmt::Display, str::FromStr, time::Duration};
 
+pub mod bus;
 pub mod ceremony_waiter;
 pub mod config;
 pub mod contributor;
 pub mod coordinator;
+#[cfg(feature = "sqlite")]
+pub mod db;
 pub mod drop_participant;
 pub mod git;
+pub mod harness;
 pub mod join;
+pub mod membership;
 pub mod npm;
 pub mod options;
 pub mod process;
+pub mod recorder;
+pub mod reload;
 pub mod reporting;
+pub mod resource_monitor;
 pub mod rust;
 pub mod specification;
 pub mod state_monitor;
+#[cfg(feature = "stream")]
+pub mod stream;
+pub mod summary;
 pub mod test;
 pub mod time_limit;
+pub mod timeline;
+pub mod transcript;
 pub mod util;
 pub mod verifier;
 pub mod waiter;
+pub mod watchdog;
 
 /// A reference to a contributor in the ceremony.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct ContributorRef {
     /// Public aleo address
     pub address: AleoPublicKey,
 }
 
+impl ContributorRef {
+    /// The id used to reference this contributor by the coordinator,
+    /// and within the ceremony transcript.
+    pub fn id_on_coordinator(&self) -> String {
+        format!("{}.contributor", self.address)
+    }
+}
+
 impl std::fmt::Display for ContributorRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.address.fmt(f)
@@ -37,31 +59,69 @@ impl std::fmt::Display for ContributorRef {
 }
 
 /// A reference to a verifier in the ceremony.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct VerifierRef {
     /// Public aleo address
     pub address: AleoPublicKey,
 }
 
+impl VerifierRef {
+    /// The id used to reference this verifier by the coordinator,
+    /// and within the ceremony transcript.
+    pub fn id_on_coordinator(&self) -> String {
+        format!("{}.verifier", self.address)
+    }
+}
+
+impl std::fmt::Display for VerifierRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.address.fmt(f)
+    }
+}
+
 /// A reference to a participant in the ceremony.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum ParticipantRef {
     Contributor(ContributorRef),
     Verifier(VerifierRef),
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum ShutdownReason {
     Error,
     TestFinished,
+    /// The coordinator panicked, see
+    /// [coordinator::CoordinatorBacktrace].
+    CoordinatorPanicked(coordinator::CoordinatorBacktrace),
+    /// The coordinator rejected its configuration file before it
+    /// booted, see [coordinator::ConfigParseError].
+    CoordinatorConfigRejected(coordinator::ConfigParseError),
+    /// The coordinator process exited unexpectedly, see
+    /// [coordinator::run_coordinator()].
+    CoordinatorDied,
+    /// The test harness was asked to shut the ceremony down, see
+    /// [harness::TestHarness::shutdown()].
+    HarnessRequested,
 }
 
 impl std::fmt::Display for ShutdownReason {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ShutdownReason::Error => f.write_str("there was an error"),
-            ShutdownReason::TestFinished => todo!("the test is finished"),
+            ShutdownReason::TestFinished => f.write_str("the test is finished"),
+            ShutdownReason::CoordinatorPanicked(backtrace) => write!(
+                f,
+                "the coordinator panicked at {}: {}",
+                backtrace.location, backtrace.message
+            ),
+            ShutdownReason::CoordinatorConfigRejected(error) => write!(
+                f,
+                "the coordinator rejected its configuration file: {:?}",
+                error.line
+            ),
+            ShutdownReason::CoordinatorDied => f.write_str("the coordinator died"),
+            ShutdownReason::HarnessRequested => f.write_str("the test harness requested it"),
         }
     }
 }
@@ -69,17 +129,34 @@ impl std::fmt::Display for ShutdownReason {
 /// Message sent between the various components running during the
 /// setup ceremony. Each component will have a process monitor running
 /// in its own thread which will listen to these messages.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[non_exhaustive]
 pub enum CeremonyMessage {
     /// Notify the receivers that the specified round has started.
     /// Data is the round number.
     RoundStarted(u64),
+    /// Notify the receivers that the coordinator has written the
+    /// `state.json` file for the specified round, so that it can be
+    /// read (see [coordinator::read_round_state()]). Only broadcast
+    /// when [coordinator::CoordinatorConfig::round_state_poll_interval]
+    /// is set.
+    /// Data is the round number.
+    RoundStateWritten(u64),
     /// Notify the receivers that the specified round has completed
     /// verification, and aggregation of the contributions by the
     /// coordinator has begun.
     /// Data is the round number.
     RoundStartedAggregation(u64),
+    /// Progress of the aggregation of the specified round, reported
+    /// after each chunk has been aggregated.
+    AggregationProgress {
+        /// The round number.
+        round: u64,
+        /// The number of chunks which have been aggregated so far.
+        done: u64,
+        /// The total number of chunks to aggregate.
+        total: u64,
+    },
     /// Notify the receivers that the specified round has successfully
     /// been aggregated.
     /// Data is the round number.
@@ -88,18 +165,130 @@ pub enum CeremonyMessage {
     /// sucessfully.
     /// Data is the round number.
     RoundFinished(u64),
+    /// Notify the receivers how long the specified round spent in
+    /// each phase, measured from the coordinator's log. Broadcast
+    /// immediately after the [CeremonyMessage::RoundFinished] for the
+    /// round (and before the [CeremonyMessage::Shutdown] if it was the
+    /// last round).
+    RoundTimings {
+        round: u64,
+        /// From waiting for participants until the round started.
+        waiting: Duration,
+        /// From the round starting until aggregation started.
+        running: Duration,
+        /// From aggregation starting until the round was aggregated.
+        aggregating: Duration,
+        /// From the round being aggregated until it finished.
+        finishing: Duration,
+    },
+    /// Notify the receivers that the specified round was rolled back
+    /// by the coordinator to round 0 (to wait for new participants),
+    /// because no contributors remained to complete it.
+    /// Data is the round number.
+    RoundRolledBack(u64),
+    /// Notify the receivers that the specified round was restarted by
+    /// the coordinator (e.g. after dropping a participant which was
+    /// not seen within the `contributor_seen_timeout`), with
+    /// contributors remaining to complete it. The contributions made
+    /// so far in the round are discarded.
+    /// Data is the round number.
+    RoundRestarted(u64),
+    /// Notify the receivers that the transcript for the specified
+    /// round has been verified after it finished, see
+    /// [transcript::run_incremental_verification()].
+    RoundVerified {
+        /// The round number.
+        round: u64,
+    },
+    /// Notify the receivers that the coordinator process has booted
+    /// up. Broadcast before any of the messages about rounds.
+    CoordinatorReady,
     /// Notify the receivers that the coordinator is ready and waiting
     /// for participants for the specified round before starting it.
     /// Data is the round number.
     RoundWaitingForParticipants(u64),
+    /// Notify the receivers that a participant has joined the
+    /// coordinator's queue, see [membership::MembershipTracker] for
+    /// determining which round it will participate in.
+    ParticipantJoinedQueue(ParticipantRef),
     /// Notify the receivers that the coordinator has just dropped a
     /// participant in the current round.
     ParticipantDropped(ParticipantRef),
+    /// Notify the receivers that a replacement contributor (see
+    /// [coordinator::CoordinatorConfig::replacement_contributors]) has
+    /// taken over the slot of a contributor which was dropped.
+    ReplacementActivated {
+        dropped: ContributorRef,
+        replacement: ContributorRef,
+    },
     /// The coordinator has successfully received a contribution from
     /// a contributor at a given chunk.
     SuccessfulContribution {
         contributor: ContributorRef,
         chunk: u64,
+        /// The index of this contribution among the contributions
+        /// reported during the current round, starting from 0. Can
+        /// be used to detect dropped or reordered messages.
+        seq: u64,
+    },
+    /// A verifier has successfully verified the contribution at a
+    /// given chunk.
+    SuccessfulVerification { verifier: VerifierRef, chunk: u64 },
+    /// The coordinator has reported a contribution from a contributor
+    /// to a chunk which it had already contributed to during the
+    /// current round. The contribution is also reported with
+    /// [CeremonyMessage::SuccessfulContribution].
+    DuplicateContribution {
+        contributor: ContributorRef,
+        chunk: u64,
+    },
+    /// A contributor which stopped making progress was killed and
+    /// relaunched, see [watchdog::run_contributor_watchdog()].
+    ContributorRestarted(ContributorRef),
+    /// The coordinator has lost its connection to the verifier, which
+    /// may reconnect (see [CeremonyMessage::VerifierReconnected])
+    /// without being dropped from the round.
+    VerifierDisconnected(VerifierRef),
+    /// A verifier which previously disconnected (see
+    /// [CeremonyMessage::VerifierDisconnected]) has reconnected to
+    /// the coordinator.
+    VerifierReconnected(VerifierRef),
+    /// The coordinator has entered a maintenance pause. Progress is
+    /// not expected until [CeremonyMessage::CoordinatorResumed], so
+    /// timeouts are suspended while paused.
+    CoordinatorPaused,
+    /// The coordinator has left a maintenance pause, see
+    /// [CeremonyMessage::CoordinatorPaused].
+    CoordinatorResumed,
+    /// Broadcast periodically while the coordinator is running, with
+    /// the state of the ceremony as tracked by the harness, see
+    /// [coordinator::CoordinatorConfig::heartbeat_interval].
+    Heartbeat {
+        /// The round of the current state, `None` before the
+        /// coordinator has booted.
+        round: Option<u64>,
+        /// The name of the current state, see
+        /// [coordinator::PublicCoordinatorState::name()].
+        state_name: String,
+    },
+    /// A sample of the coordinator process's resource usage, see
+    /// [resource_monitor::run_resource_monitor()].
+    ResourceSample {
+        /// Resident set size of the process in bytes.
+        rss_bytes: u64,
+        /// CPU usage since the previous sample, as a percentage of a
+        /// single core (rounded, may exceed 100 on multi-core
+        /// machines).
+        cpu_pct: u32,
+    },
+    /// The coordinator process has exited, see
+    /// [coordinator::coordinator_exited_message()].
+    CoordinatorExited {
+        /// The exit code, if the process exited normally.
+        code: Option<i32>,
+        /// The signal which terminated the process, if it was killed
+        /// by a signal.
+        signal: Option<i32>,
     },
     /// Tell all the recievers to shut down.
     Shutdown(ShutdownReason),
@@ -112,7 +301,7 @@ impl IsShutdownMessage for CeremonyMessage {
 }
 
 /// Which phase of the setup is to be run.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Environment {
     #[serde(rename = "development")]
     Development,
@@ -135,6 +324,20 @@ impl Environment {
     pub fn str_variants() -> &'static [&'static str] {
         &["development", "inner", "outer", "universal"]
     }
+
+    /// The number of output files (other than `state.json`) that the
+    /// coordinator produces in a round's transcript directory when
+    /// the round is aggregated in this environment. Every environment
+    /// currently aggregates all of the round's chunks into a single
+    /// `round_N.verified` file.
+    pub fn aggregation_file_count(&self) -> usize {
+        match self {
+            Environment::Development => 1,
+            Environment::Inner => 1,
+            Environment::Outer => 1,
+            Environment::Universal => 1,
+        }
+    }
 }
 
 impl FromStr for Environment {
@@ -146,11 +349,17 @@ impl FromStr for Environment {
             "inner" => Ok(Self::Inner),
             "outer" => Ok(Self::Outer),
             "universal" => Ok(Self::Universal),
-            _ => Err(eyre::eyre!("unable to parse {:?} as a SetupPhase", s)),
+            _ => Err(eyre::eyre!(
+                "Unable to parse {:?} as an Environment, expected one of: {}",
+                s,
+                Self::str_variants().join(", ")
+            )),
         }
     }
 }
 
+/// Formats the environment with the same name that is used in its
+/// serialized form (and accepted by [FromStr]).
 impl Display for Environment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -203,3 +412,60 @@ impl AsRef<str> for AleoPublicKey {
         self.0.as_ref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::Environment;
+
+    const ALL_ENVIRONMENTS: &[Environment] = &[
+        Environment::Development,
+        Environment::Inner,
+        Environment::Outer,
+        Environment::Universal,
+    ];
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct EnvironmentToml {
+        environment: Environment,
+    }
+
+    /// Test that every [Environment] round-trips through its string
+    /// form, and that the string form matches its toml serialization.
+    #[test]
+    fn test_environment_str_round_trip() {
+        assert_eq!(ALL_ENVIRONMENTS.len(), Environment::str_variants().len());
+
+        for (environment, variant) in ALL_ENVIRONMENTS.iter().zip(Environment::str_variants()) {
+            let s = environment.to_string();
+            assert_eq!(*variant, s);
+            assert_eq!(*environment, s.parse::<Environment>().unwrap());
+
+            let toml_str = toml::to_string(&EnvironmentToml {
+                environment: *environment,
+            })
+            .unwrap();
+            assert_eq!(format!("environment = \"{}\"\n", s), toml_str);
+            assert_eq!(
+                EnvironmentToml {
+                    environment: *environment,
+                },
+                toml::from_str(&toml_str).unwrap()
+            );
+        }
+    }
+
+    /// Test that parsing an unknown environment is an error which
+    /// lists the valid environments.
+    #[test]
+    fn test_environment_from_str_unknown() {
+        let error = "production".parse::<Environment>().unwrap_err();
+        assert_eq!(
+            "Unable to parse \"production\" as an Environment, \
+            expected one of: development, inner, outer, universal",
+            error.to_string()
+        );
+        assert!("Development".parse::<Environment>().is_err());
+    }
+}
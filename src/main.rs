@@ -4,6 +4,7 @@
 use aleo_setup_integration_test::{
     config::Config,
     options::CmdOptions,
+    reload::apply_config,
     reporting::{setup_reporting, LogFileWriter},
     specification::{Specification, TestId},
 };
@@ -16,7 +17,7 @@ use structopt::StructOpt;
 /// is run.
 fn main() -> eyre::Result<()> {
     let log_writer = LogFileWriter::new();
-    let _guard = setup_reporting(log_writer.clone())?;
+    let report_guard = setup_reporting(log_writer.clone())?;
 
     let options: CmdOptions = CmdOptions::from_args();
 
@@ -40,6 +41,15 @@ fn main() -> eyre::Result<()> {
     let config: Config = ron::from_str(&config_string)
         .wrap_err_with(|| eyre::eyre!("Error while parsing configuration"))?;
 
+    apply_config(&config, report_guard.log_filter())?;
+
+    if options.reload_on_sighup {
+        let config_file = options.config_file.clone().ok_or_else(|| {
+            eyre::eyre!("--reload-on-sighup requires a configuration file specified with --config")
+        })?;
+        reload_on_sighup(config_file, report_guard.log_filter().clone())?;
+    }
+
     tracing::info!(
         "Running integration test using specification {:?}",
         &options.specification_file
@@ -72,3 +82,20 @@ fn main() -> eyre::Result<()> {
 
     result
 }
+
+#[cfg(unix)]
+fn reload_on_sighup(
+    config_file: std::path::PathBuf,
+    log_filter: aleo_setup_integration_test::reporting::LogFilterHandle,
+) -> eyre::Result<()> {
+    aleo_setup_integration_test::reload::spawn_sighup_reloader(config_file, log_filter)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn reload_on_sighup(
+    _config_file: std::path::PathBuf,
+    _log_filter: aleo_setup_integration_test::reporting::LogFilterHandle,
+) -> eyre::Result<()> {
+    Err(eyre::eyre!("--reload-on-sighup is only supported on Unix"))
+}
@@ -0,0 +1,472 @@
+//! Tracks which rounds of the ceremony the participants actually took
+//! part in, based on the [CeremonyMessage]s broadcast during the
+//! ceremony (e.g. as recorded by a
+//! [MessageRecorder](crate::recorder::MessageRecorder)).
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{CeremonyMessage, ContributorRef, ParticipantRef};
+
+/// When a participant joined the coordinator's queue, and which round
+/// that caused it to participate in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueJoin {
+    /// The round that was running when the participant joined the
+    /// queue, or `None` if no round was running at the time (the
+    /// coordinator was waiting for participants).
+    pub joined_during_round: Option<u64>,
+    /// The first round which started after the participant joined
+    /// the queue, or `None` if no round has started since.
+    pub participating_round: Option<u64>,
+}
+
+/// Tracks the rounds that participants joined the queue in, and the
+/// rounds contributors made contributions in. Participants joining
+/// the queue while a round is running are expected to be queued for
+/// the next round, rather than the current one.
+#[derive(Debug, Default)]
+pub struct MembershipTracker {
+    /// The round which is currently running, if any.
+    running_round: Option<u64>,
+    /// The most recent queue join for each participant.
+    queue_joins: HashMap<ParticipantRef, QueueJoin>,
+    /// The number of successful contributions that each contributor
+    /// made in each round.
+    contributions: HashMap<ContributorRef, BTreeMap<u64, usize>>,
+}
+
+impl MembershipTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a tracker which has handled all the `messages`.
+    pub fn from_messages<'a>(messages: impl IntoIterator<Item = &'a CeremonyMessage>) -> Self {
+        let mut tracker = Self::new();
+        for message in messages {
+            tracker.handle_message(message);
+        }
+        tracker
+    }
+
+    /// Update the tracked membership with a message received from the
+    /// ceremony.
+    pub fn handle_message(&mut self, message: &CeremonyMessage) {
+        match message {
+            CeremonyMessage::RoundStarted(round) => {
+                self.running_round = Some(*round);
+                for queue_join in self.queue_joins.values_mut() {
+                    if queue_join.participating_round.is_none() {
+                        queue_join.participating_round = Some(*round);
+                    }
+                }
+            }
+            CeremonyMessage::RoundFinished(_) | CeremonyMessage::RoundWaitingForParticipants(_) => {
+                self.running_round = None;
+            }
+            CeremonyMessage::ParticipantJoinedQueue(participant) => {
+                self.queue_joins.insert(
+                    participant.clone(),
+                    QueueJoin {
+                        joined_during_round: self.running_round,
+                        participating_round: None,
+                    },
+                );
+            }
+            CeremonyMessage::SuccessfulContribution { contributor, .. } => {
+                if let Some(round) = self.running_round {
+                    *self
+                        .contributions
+                        .entry(contributor.clone())
+                        .or_default()
+                        .entry(round)
+                        .or_default() += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The most recent time the `participant` joined the queue, if it
+    /// has.
+    pub fn queue_join(&self, participant: &ParticipantRef) -> Option<&QueueJoin> {
+        self.queue_joins.get(participant)
+    }
+
+    /// The rounds in which the `contributor` made a successful
+    /// contribution.
+    pub fn contributed_rounds(&self, contributor: &ContributorRef) -> BTreeSet<u64> {
+        self.contributions
+            .get(contributor)
+            .map(|rounds| rounds.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// The number of successful contributions made by each
+    /// contributor which contributed in `round`.
+    pub fn contribution_counts(&self, round: u64) -> HashMap<ContributorRef, usize> {
+        self.contributions
+            .iter()
+            .filter_map(|(contributor, rounds)| {
+                rounds
+                    .get(&round)
+                    .map(|count| (contributor.clone(), *count))
+            })
+            .collect()
+    }
+
+    /// The number of distinct contributors which made a successful
+    /// contribution in each round.
+    pub fn contributors_per_round(&self) -> BTreeMap<u64, usize> {
+        let mut per_round: BTreeMap<u64, usize> = BTreeMap::new();
+        for rounds in self.contributions.values() {
+            for round in rounds.keys() {
+                *per_round.entry(*round).or_default() += 1;
+            }
+        }
+        per_round
+    }
+
+    /// The first round (at or after the round it was queued for, see
+    /// [QueueJoin::participating_round]) in which the `contributor`
+    /// made a contribution, i.e. the round it was admitted from the
+    /// queue. `None` if it has not been admitted (or never joined the
+    /// queue).
+    pub fn admitted_round(&self, contributor: &ContributorRef) -> Option<u64> {
+        let queued_for = self
+            .queue_join(&ParticipantRef::Contributor(contributor.clone()))?
+            .participating_round?;
+
+        self.contributed_rounds(contributor)
+            .range(queued_for..)
+            .next()
+            .copied()
+    }
+
+    /// Returns an error unless the coordinator held the contributors
+    /// which joined the queue beyond its capacity in the queue, rather
+    /// than admitting them: no round may have had more than
+    /// `max_contributors` active contributors (see
+    /// [check_contributor_band()]), and every contributor which joined
+    /// the queue must have been admitted in some later round (see
+    /// [MembershipTracker::admitted_round()]).
+    pub fn check_queue_capacity(&self, max_contributors: usize) -> eyre::Result<()> {
+        check_contributor_band(&self.contributors_per_round(), 0, max_contributors)?;
+
+        let mut never_admitted: Vec<String> = self
+            .queue_joins
+            .keys()
+            .filter_map(|participant| match participant {
+                ParticipantRef::Contributor(contributor) => Some(contributor),
+                ParticipantRef::Verifier(_) => None,
+            })
+            .filter(|contributor| self.admitted_round(contributor).is_none())
+            .map(|contributor| contributor.to_string())
+            .collect();
+
+        if !never_admitted.is_empty() {
+            never_admitted.sort();
+            return Err(eyre::eyre!(
+                "Contributors joined the queue but were never admitted to a round: {}",
+                never_admitted.join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error unless the `contributor` participated
+    /// (contributed) in `round`, but not in the round before it.
+    pub fn assert_participated_in_round_but_not_previous(
+        &self,
+        contributor: &ContributorRef,
+        round: u64,
+    ) -> eyre::Result<()> {
+        let rounds = self.contributed_rounds(contributor);
+
+        if !rounds.contains(&round) {
+            return Err(eyre::eyre!(
+                "Contributor {} did not participate in round {}, participated in rounds: {:?}",
+                contributor,
+                round,
+                rounds
+            ));
+        }
+
+        if round > 1 && rounds.contains(&(round - 1)) {
+            return Err(eyre::eyre!(
+                "Contributor {} unexpectedly participated in round {}",
+                contributor,
+                round - 1
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns an error if the contribution work in a round was not
+/// distributed fairly across the contributors (e.g. due to an
+/// imbalance in the coordinator's task scheduling). Each contributor's
+/// count in `round_counts` (see
+/// [MembershipTracker::contribution_counts()]) must be within
+/// `tolerance` (a fraction, e.g. `0.5` for 50%) of the mean count.
+pub fn check_contribution_fairness(
+    round_counts: &HashMap<ContributorRef, usize>,
+    tolerance: f64,
+) -> eyre::Result<()> {
+    if round_counts.is_empty() {
+        return Ok(());
+    }
+
+    let mean = round_counts.values().sum::<usize>() as f64 / round_counts.len() as f64;
+    let min = mean * (1.0 - tolerance);
+    let max = mean * (1.0 + tolerance);
+
+    let mut unfair: Vec<(&ContributorRef, usize)> = round_counts
+        .iter()
+        .filter(|(_, count)| (**count as f64) < min || (**count as f64) > max)
+        .map(|(contributor, count)| (contributor, *count))
+        .collect();
+
+    if !unfair.is_empty() {
+        unfair.sort_by_key(|(_, count)| *count);
+        return Err(eyre::eyre!(
+            "Contributions were not distributed fairly (mean {:.1}, tolerance {}): {}",
+            mean,
+            tolerance,
+            unfair
+                .iter()
+                .map(|(contributor, count)| format!("{} made {}", contributor, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns an error unless every round in `per_round_counts` (see
+/// [MembershipTracker::contributors_per_round()]) had between `min`
+/// and `max` (inclusive) active contributors, to check that the
+/// coordinator enforced the `minimum_contributors_per_round` and
+/// `maximum_contributors_per_round` of its
+/// [EnvironmentParameters](crate::coordinator::EnvironmentParameters).
+/// The error reports the first offending round.
+pub fn check_contributor_band(
+    per_round_counts: &BTreeMap<u64, usize>,
+    min: usize,
+    max: usize,
+) -> eyre::Result<()> {
+    if let Some((round, count)) = per_round_counts
+        .iter()
+        .find(|(_, count)| **count < min || **count > max)
+    {
+        return Err(eyre::eyre!(
+            "Round {} had {} active contributors, expected between {} and {}",
+            round,
+            count,
+            min,
+            max
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{
+        check_contribution_fairness, check_contributor_band, MembershipTracker, QueueJoin,
+    };
+    use crate::{CeremonyMessage, ContributorRef, ParticipantRef};
+
+    fn contributor(address: &str) -> ContributorRef {
+        ContributorRef {
+            address: address.parse().unwrap(),
+        }
+    }
+
+    /// Test that a contributor which joins the queue while round 1 is
+    /// running is recorded as participating in round 2.
+    #[test]
+    fn test_mid_round_join() {
+        let early = contributor("aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689");
+        let late = contributor("aleo1qgqkzmv9jzq3y2ykl4vdm2mcaa7wh8kl3jzjy2cm8tg63k9xcsqqyvsw5m");
+
+        let messages = vec![
+            CeremonyMessage::RoundWaitingForParticipants(1),
+            CeremonyMessage::ParticipantJoinedQueue(ParticipantRef::Contributor(early.clone())),
+            CeremonyMessage::RoundStarted(1),
+            CeremonyMessage::ParticipantJoinedQueue(ParticipantRef::Contributor(late.clone())),
+            CeremonyMessage::SuccessfulContribution {
+                contributor: early.clone(),
+                chunk: 0,
+                seq: 0,
+            },
+            CeremonyMessage::RoundFinished(1),
+            CeremonyMessage::RoundWaitingForParticipants(2),
+            CeremonyMessage::RoundStarted(2),
+            CeremonyMessage::SuccessfulContribution {
+                contributor: late.clone(),
+                chunk: 0,
+                seq: 0,
+            },
+            CeremonyMessage::RoundFinished(2),
+        ];
+
+        let tracker = MembershipTracker::from_messages(&messages);
+
+        assert_eq!(
+            Some(&QueueJoin {
+                joined_during_round: None,
+                participating_round: Some(1),
+            }),
+            tracker.queue_join(&ParticipantRef::Contributor(early.clone()))
+        );
+        assert_eq!(
+            Some(&QueueJoin {
+                joined_during_round: Some(1),
+                participating_round: Some(2),
+            }),
+            tracker.queue_join(&ParticipantRef::Contributor(late.clone()))
+        );
+
+        tracker
+            .assert_participated_in_round_but_not_previous(&late, 2)
+            .unwrap();
+        tracker
+            .assert_participated_in_round_but_not_previous(&early, 1)
+            .unwrap();
+        assert!(tracker
+            .assert_participated_in_round_but_not_previous(&early, 2)
+            .is_err());
+    }
+
+    /// Test that a lopsided distribution of contributions in a round
+    /// fails the fairness check.
+    #[test]
+    fn test_contribution_fairness() {
+        let first = contributor("aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689");
+        let second = contributor("aleo1qgqkzmv9jzq3y2ykl4vdm2mcaa7wh8kl3jzjy2cm8tg63k9xcsqqyvsw5m");
+
+        let mut messages = vec![CeremonyMessage::RoundStarted(1)];
+        for chunk in 0..10 {
+            let contributor = if chunk < 9 { &first } else { &second };
+            messages.push(CeremonyMessage::SuccessfulContribution {
+                contributor: contributor.clone(),
+                chunk,
+                seq: chunk,
+            });
+        }
+        messages.push(CeremonyMessage::RoundFinished(1));
+
+        let tracker = MembershipTracker::from_messages(&messages);
+        let counts = tracker.contribution_counts(1);
+        assert_eq!(Some(&9), counts.get(&first));
+        assert_eq!(Some(&1), counts.get(&second));
+        assert!(tracker.contribution_counts(2).is_empty());
+
+        let error = check_contribution_fairness(&counts, 0.5).unwrap_err();
+        assert!(error.to_string().contains(&second.to_string()));
+
+        let balanced: HashMap<_, _> = vec![(first, 5), (second, 6)].into_iter().collect();
+        check_contribution_fairness(&balanced, 0.5).unwrap();
+    }
+
+    /// Test that when 8 contributors join the queue with a maximum of
+    /// 5 per round, the 3 extras being held in the queue and admitted
+    /// in the second round passes the check, and over-admission or
+    /// never admitting the extras fails it.
+    #[test]
+    fn test_queue_capacity() {
+        let contributors: Vec<ContributorRef> = (0..8)
+            .map(|i| contributor(&format!("aleo1{:0>58}", i)))
+            .collect();
+        let max_contributors = 5;
+
+        let messages_for = |round_members: &[&[ContributorRef]]| {
+            let mut messages = vec![CeremonyMessage::RoundWaitingForParticipants(1)];
+            messages.extend(contributors.iter().map(|contributor| {
+                CeremonyMessage::ParticipantJoinedQueue(ParticipantRef::Contributor(
+                    contributor.clone(),
+                ))
+            }));
+            for (i, members) in round_members.iter().enumerate() {
+                let round = i as u64 + 1;
+                messages.push(CeremonyMessage::RoundStarted(round));
+                messages.extend(members.iter().enumerate().map(|(seq, contributor)| {
+                    CeremonyMessage::SuccessfulContribution {
+                        contributor: contributor.clone(),
+                        chunk: 0,
+                        seq: seq as u64,
+                    }
+                }));
+                messages.push(CeremonyMessage::RoundFinished(round));
+            }
+            messages
+        };
+
+        let (first, extras) = contributors.split_at(max_contributors);
+        let tracker = MembershipTracker::from_messages(&messages_for(&[first, extras]));
+        tracker.check_queue_capacity(max_contributors).unwrap();
+        for contributor in first {
+            assert_eq!(Some(1), tracker.admitted_round(contributor));
+        }
+        for contributor in extras {
+            assert_eq!(Some(2), tracker.admitted_round(contributor));
+        }
+
+        let (over, rest) = contributors.split_at(max_contributors + 1);
+        let error = MembershipTracker::from_messages(&messages_for(&[over, rest]))
+            .check_queue_capacity(max_contributors)
+            .unwrap_err();
+        assert!(error.to_string().contains("Round 1 had 6"));
+
+        let error = MembershipTracker::from_messages(&messages_for(&[first]))
+            .check_queue_capacity(max_contributors)
+            .unwrap_err();
+        assert!(error.to_string().contains("never admitted"));
+        assert!(error.to_string().contains(&extras[0].to_string()));
+    }
+
+    /// Test that a round with more contributors than the maximum
+    /// fails the check.
+    #[test]
+    fn test_contributor_band() {
+        let first = contributor("aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689");
+        let second = contributor("aleo1qgqkzmv9jzq3y2ykl4vdm2mcaa7wh8kl3jzjy2cm8tg63k9xcsqqyvsw5m");
+
+        let messages = vec![
+            CeremonyMessage::RoundStarted(1),
+            CeremonyMessage::SuccessfulContribution {
+                contributor: first.clone(),
+                chunk: 0,
+                seq: 0,
+            },
+            CeremonyMessage::RoundFinished(1),
+            CeremonyMessage::RoundStarted(2),
+            CeremonyMessage::SuccessfulContribution {
+                contributor: first,
+                chunk: 0,
+                seq: 0,
+            },
+            CeremonyMessage::SuccessfulContribution {
+                contributor: second,
+                chunk: 1,
+                seq: 1,
+            },
+            CeremonyMessage::RoundFinished(2),
+        ];
+
+        let per_round = MembershipTracker::from_messages(&messages).contributors_per_round();
+        assert_eq!(Some(&1), per_round.get(&1));
+        assert_eq!(Some(&2), per_round.get(&2));
+
+        check_contributor_band(&per_round, 1, 2).unwrap();
+        let error = check_contributor_band(&per_round, 1, 1).unwrap_err();
+        assert!(error.to_string().contains("Round 2 had 2"));
+    }
+}
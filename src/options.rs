@@ -25,4 +25,9 @@ pub struct CmdOptions {
     /// specification file.
     #[structopt(long = "id")]
     pub id: Option<TestId>,
+    /// Re-read the configuration file specified with `--config` when
+    /// a `SIGHUP` signal is received, applying any hot-reloadable
+    /// settings (such as the log filter). Only supported on Unix.
+    #[structopt(long = "reload-on-sighup")]
+    pub reload_on_sighup: bool,
 }
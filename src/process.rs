@@ -1,10 +1,15 @@
 //! Functions for starting/managing/interacting with external processes.
 
-use std::{fs::File, thread::JoinHandle, time::Duration};
+use std::{
+    fs::File,
+    sync::{mpsc, Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
 
 use eyre::Context;
 use mpmc_bus::{Receiver, Sender, TryRecvError};
-use subprocess::{Exec, Redirection};
+use subprocess::{Exec, Popen, Redirection};
 
 use crate::{join::MultiJoinable, CeremonyMessage, ShutdownReason};
 
@@ -22,10 +27,47 @@ pub fn default_parse_exit_status(exit_status: subprocess::ExitStatus) -> eyre::R
     }
 }
 
+/// Pause the process with the specified `pid` for `duration`, by
+/// sending it `SIGSTOP` and then `SIGCONT` once the `duration` has
+/// elapsed, or earlier if a message is received on `resume_rx` (or
+/// its sender is dropped). Blocks the current thread while the
+/// process is paused. Only supported on unix platforms (requires the
+/// `kill` command).
+pub fn pause_process(
+    pid: u32,
+    duration: Duration,
+    resume_rx: mpsc::Receiver<()>,
+) -> eyre::Result<()> {
+    let pid_string = pid.to_string();
+
+    tracing::debug!("Pausing process {} for {:?}.", pid, duration);
+    Exec::cmd("kill")
+        .args(&["-STOP", &pid_string])
+        .join()
+        .map_err(eyre::Error::from)
+        .and_then(default_parse_exit_status)
+        .wrap_err_with(|| eyre::eyre!("Unable to pause process {}", pid))?;
+
+    if let Err(mpsc::RecvTimeoutError::Timeout) = resume_rx.recv_timeout(duration) {
+        tracing::debug!("Resuming process {}.", pid);
+    } else {
+        tracing::debug!("Resuming process {} early.", pid);
+    }
+    Exec::cmd("kill")
+        .args(&["-CONT", &pid_string])
+        .join()
+        .map_err(eyre::Error::from)
+        .and_then(default_parse_exit_status)
+        .wrap_err_with(|| eyre::eyre!("Unable to resume process {}", pid))
+}
+
 /// A join handle for the threads created in [wait_start_process()]
 #[must_use]
 pub struct MonitorProcessJoin {
     id: String,
+    pid: Arc<Mutex<Option<u32>>>,
+    exit_status: Arc<Mutex<Option<subprocess::ExitStatus>>>,
+    monitor_tx: Sender<MonitorProcessMessage>,
     monitor_join: JoinHandle<()>,
     messages_join: JoinHandle<()>,
 }
@@ -37,6 +79,30 @@ impl std::fmt::Debug for MonitorProcessJoin {
 }
 
 impl MonitorProcessJoin {
+    /// The operating system process id of the monitored process (if
+    /// it was available when the process was started). This changes
+    /// if the process is relaunched, see
+    /// [MonitorProcessOptions::startup_retries].
+    pub fn pid(&self) -> Option<u32> {
+        *self.pid.lock().expect("error obtaining lock")
+    }
+
+    /// The exit status of the process, if it has exited and been
+    /// reaped by the messages thread.
+    pub fn exit_status(&self) -> Option<subprocess::ExitStatus> {
+        *self.exit_status.lock().expect("error obtaining lock")
+    }
+
+    /// Tell the messages thread to terminate the process (with
+    /// `SIGTERM`, followed by `SIGKILL` if it has not exited within
+    /// [MonitorProcessOptions::terminate_grace]), without shutting
+    /// down the rest of the ceremony.
+    pub fn terminate(&self) -> eyre::Result<()> {
+        self.monitor_tx
+            .broadcast(MonitorProcessMessage::Terminate)
+            .map_err(|error| eyre::eyre!("Error sending message to process monitor: {}", error))
+    }
+
     /// Join the threads
     pub fn join(self) -> std::thread::Result<()> {
         let span = tracing::error_span!("join", id = %self.id);
@@ -58,47 +124,160 @@ impl MultiJoinable for MonitorProcessJoin {
     }
 }
 
+/// Default value for [MonitorProcessOptions::terminate_grace].
+pub const DEFAULT_TERMINATE_GRACE: Duration = Duration::from_secs(10);
+
+/// The longest time waited before relaunching a process, see
+/// [MonitorProcessOptions::startup_backoff].
+pub const MAX_STARTUP_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// Block until the threads in `join` have completed (e.g. after a
+/// [CeremonyMessage::Shutdown] has been broadcast), or return an
+/// error if this takes longer than `timeout`. For a
+/// [MonitorProcessJoin] this means that the child process has exited
+/// and been reaped, and its output has been completely processed by
+/// the monitor (so log files are complete).
+pub fn wait_for_shutdown<J>(join: J, timeout: Duration) -> eyre::Result<()>
+where
+    J: MultiJoinable + Send + 'static,
+{
+    let description = format!("{:?}", join);
+    let (result_tx, result_rx) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        let result = Box::new(join).join();
+        // The receiver may have already given up waiting.
+        let _ = result_tx.send(result.is_ok());
+    });
+
+    match result_rx.recv_timeout(timeout) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(eyre::eyre!("A thread in {} panicked", description)),
+        Err(_) => Err(eyre::eyre!(
+            "Timed out after {:?} waiting for {} to shut down",
+            timeout,
+            description
+        )),
+    }
+}
+
 /// Message to the [run_monitor_process()] messages thread from the
 /// monitor thread.
 #[derive(Clone)]
 pub enum MonitorProcessMessage {
     /// Terminate the running process.
     Terminate,
+    /// The process's `stdout` has been closed, and the monitor has
+    /// completed.
+    StdoutClosed,
+    /// The process has started up successfully, see
+    /// [MonitorProcessOptions::startup_retries].
+    Ready,
+}
+
+/// Options for [run_monitor_process()].
+#[derive(Debug, Clone)]
+pub struct MonitorProcessOptions {
+    /// How long to wait for the process to exit after its `stdout`
+    /// has closed, before it is killed.
+    pub eof_grace: Duration,
+    /// How long a process is given to exit after being told to
+    /// terminate (with `SIGTERM`), before it is killed (with
+    /// `SIGKILL`).
+    pub terminate_grace: Duration,
+    /// The number of times to relaunch the process if it exits with
+    /// an error before the monitor has sent
+    /// [MonitorProcessMessage::Ready]. Failures after the process is
+    /// ready are never retried.
+    pub startup_retries: u32,
+    /// How long to wait before the first relaunch, see
+    /// [MonitorProcessOptions::startup_retries]. This is doubled for
+    /// each subsequent relaunch, up to [MAX_STARTUP_BACKOFF] (see
+    /// [startup_backoff()]).
+    pub startup_backoff: Duration,
+    /// If `true`, the process's `stderr` is piped separately to the
+    /// monitor (see [ProcessOutput::stderr]), instead of being merged
+    /// into its `stdout`.
+    pub separate_stderr: bool,
+    /// If set, the message produced by this function from the
+    /// process's exit status is broadcast to the ceremony each time
+    /// the process exits.
+    pub exit_message: Option<fn(subprocess::ExitStatus) -> CeremonyMessage>,
+    /// The reason which the ceremony is shut down with when the
+    /// process exits with an error and is not relaunched.
+    pub exit_error_reason: ShutdownReason,
+}
+
+impl Default for MonitorProcessOptions {
+    fn default() -> Self {
+        Self {
+            eof_grace: Duration::from_secs(5),
+            terminate_grace: DEFAULT_TERMINATE_GRACE,
+            startup_retries: 0,
+            startup_backoff: Duration::from_secs(1),
+            separate_stderr: false,
+            exit_message: None,
+            exit_error_reason: ShutdownReason::Error,
+        }
+    }
+}
+
+/// The output of a process started by [run_monitor_process()], which
+/// is passed to the monitor.
+#[derive(Debug)]
+pub struct ProcessOutput {
+    /// The process's `stdout`, which also includes its `stderr`
+    /// unless [MonitorProcessOptions::separate_stderr] is set.
+    pub stdout: File,
+    /// The process's `stderr`, if
+    /// [MonitorProcessOptions::separate_stderr] is set.
+    pub stderr: Option<File>,
 }
 
-/// Starts the process specified in `exec`, with `stdout` set to
-/// [Redirection::Pipe], which is fed into the specified `monitor`
-/// function which runs in a new thread. Another thread is also
+/// Starts the process constructed by `exec`, with `stdout` (and
+/// optionally `stderr`, see [MonitorProcessOptions::separate_stderr])
+/// set to [Redirection::Pipe], which is fed into the specified
+/// `monitor` function which runs in a new thread. Another thread is also
 /// spawned which watches for [CeremonyMessage::Shutdown] and kills
 /// the child process if that message is received. `parse_exit_status`
 /// determines whether the returned [subprocess::ExitStatus]
 /// constitutes an error, and returns an appropriate [eyre::Result].
-pub fn run_monitor_process<M>(
+/// When terminated, the process is sent `SIGTERM`, and then killed if
+/// it has not exited within [MonitorProcessOptions::terminate_grace].
+/// If the process does not exit within
+/// [MonitorProcessOptions::eof_grace] of its `stdout` closing, it is
+/// killed. If the process exits with an error before it is ready, it
+/// may be relaunched (see [MonitorProcessOptions::startup_retries]),
+/// in which case `exec` is called again to construct the command,
+/// and the `monitor` is called again with the output of the new
+/// process.
+pub fn run_monitor_process<E, M>(
     id: String,
-    exec: Exec,
+    exec: E,
     parse_exit_status: fn(subprocess::ExitStatus) -> eyre::Result<()>,
+    options: MonitorProcessOptions,
     ceremony_tx: Sender<CeremonyMessage>,
     mut ceremony_rx: Receiver<CeremonyMessage>,
     monitor: M,
 ) -> eyre::Result<(MonitorProcessJoin, Sender<MonitorProcessMessage>)>
 where
-    M: Fn(File, Sender<CeremonyMessage>, Sender<MonitorProcessMessage>) + Send + Sync + 'static,
+    E: Fn() -> eyre::Result<Exec> + Send + 'static,
+    M: Fn(ProcessOutput, Sender<CeremonyMessage>, Sender<MonitorProcessMessage>)
+        + Send
+        + Sync
+        + 'static,
 {
     tracing::info!("Starting process.");
 
-    let mut process = exec
-        .stdout(Redirection::Pipe)
-        .stderr(Redirection::Merge)
-        .popen()
-        .wrap_err("Error opening process")?;
+    let (mut process, output) = open_process(exec()?, options.separate_stderr)?;
+    let pid: Arc<Mutex<Option<u32>>> = Arc::new(Mutex::new(process.pid()));
 
-    // Extract the stdout [std::fs::File] from `process`, replacing it
-    // with a None. This is needed so we can both listen to stdout and
-    // interact with `process`'s mutable methods (to terminate it if
-    // required).
-    let mut stdout: Option<File> = None;
-    std::mem::swap(&mut process.stdout, &mut stdout);
-    let stdout = stdout.ok_or_else(|| eyre::eyre!("Unable to obtain process `stdout`."))?;
+    // The output of each process launched is sent to the monitor
+    // thread, which closes once the messages thread has closed.
+    let (output_tx, output_rx) = mpsc::channel::<ProcessOutput>();
+    output_tx
+        .send(output)
+        .expect("`output_rx` should not have been dropped");
 
     let monitor_bus = mpmc_bus::Bus::new(5);
     let return_monitor_tx = monitor_bus.broadcaster();
@@ -110,7 +289,13 @@ where
     let monitor_join = std::thread::spawn(move || {
         let _guard = monitor_span.enter();
 
-        monitor(stdout, monitor_ceremony_tx.clone(), monitor_tx);
+        for output in output_rx {
+            monitor(output, monitor_ceremony_tx.clone(), monitor_tx.clone());
+
+            if let Err(error) = monitor_tx.broadcast(MonitorProcessMessage::StdoutClosed) {
+                tracing::error!("Error notifying that stdout has closed: {}", error);
+            }
+        }
 
         tracing::debug!("Thread closing gracefully.")
     });
@@ -119,13 +304,34 @@ where
     // terminates the process if a `Shutdown` message is received. It
     // also monitors the exit status of the process, and if there was
     // an error it will request a `Shutdown` and panic with the error.
+    let exit_status: Arc<Mutex<Option<subprocess::ExitStatus>>> = Arc::default();
+    let messages_exit_status = exit_status.clone();
+    let messages_pid = pid.clone();
     let messages_span = tracing::error_span!("messages");
     let messages_join = std::thread::spawn(move || {
         let _guard = messages_span.enter();
         let mut monitor_rx = monitor_bus.subscribe();
+        let set_exit_status = |status: subprocess::ExitStatus| {
+            *messages_exit_status.lock().expect("error obtaining lock") = Some(status);
+
+            if let Some(exit_message) = options.exit_message {
+                if let Err(error) = ceremony_tx.broadcast(exit_message(status)) {
+                    tracing::error!("Error sending exit message: {}", error);
+                }
+            }
+        };
 
         // Terminate the process at the end of the loop, and break.
         let mut terminate_process = false;
+        // The time at which the process's `stdout` closed.
+        let mut stdout_closed: Option<Instant> = None;
+        // Whether the monitor has reported that the process is ready.
+        let mut ready = false;
+        // The number of times the process has been relaunched.
+        let mut relaunches: u32 = 0;
+        // The time at which the process is to be relaunched, while
+        // waiting for the startup backoff to elapse.
+        let mut relaunch_at: Option<Instant> = None;
 
         loop {
             // Sleep occasionally because otherwise this loop will run too fast.
@@ -134,6 +340,8 @@ where
             match monitor_rx.try_recv() {
                 Ok(message) => match message {
                     MonitorProcessMessage::Terminate => terminate_process = true,
+                    MonitorProcessMessage::StdoutClosed => stdout_closed = Some(Instant::now()),
+                    MonitorProcessMessage::Ready => ready = true,
                 },
                 Err(TryRecvError::Disconnected) => {
                     panic!("`monitor_rx` disconnected");
@@ -153,17 +361,83 @@ where
                 Err(TryRecvError::Empty) => {}
             }
 
-            if let Some(exit_result) = process.poll().map(parse_exit_status) {
+            if let Some(relaunch) = relaunch_at {
+                if terminate_process {
+                    tracing::info!("Process terminated before being relaunched.");
+                    break;
+                }
+
+                if Instant::now() >= relaunch {
+                    tracing::info!("Relaunching process.");
+                    match exec().and_then(|exec| open_process(exec, options.separate_stderr)) {
+                        Ok((new_process, output)) => {
+                            *messages_pid.lock().expect("error obtaining lock") = new_process.pid();
+                            process = new_process;
+                            output_tx
+                                .send(output)
+                                .expect("`output_rx` should not have been dropped");
+                        }
+                        Err(error) => {
+                            ceremony_tx
+                                .broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))
+                                .expect("Error sending shutdown message");
+                            panic!("Error while relaunching process: {:?}", error);
+                        }
+                    }
+                    relaunch_at = None;
+                    stdout_closed = None;
+                }
+
+                continue;
+            }
+
+            if let Some(exit_result) = process.poll().map(|status| {
+                set_exit_status(status);
+                parse_exit_status(status)
+            }) {
                 match exit_result {
                     Ok(_) => {
                         tracing::info!("Process successfully exited.");
                         break;
                     }
                     Err(error) => {
-                        ceremony_tx
-                            .broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))
-                            .expect("Error sending shutdown message");
-                        panic!("Error while running process: {}", error);
+                        let can_relaunch = |ready: bool, terminate_process: bool| {
+                            !ready && !terminate_process && relaunches < options.startup_retries
+                        };
+
+                        // Wait for the monitor to finish processing
+                        // the output, in case the process became
+                        // ready before exiting.
+                        if can_relaunch(ready, terminate_process) && stdout_closed.is_none() {
+                            for message in wait_stdout_closed(&mut monitor_rx, options.eof_grace) {
+                                match message {
+                                    MonitorProcessMessage::Terminate => terminate_process = true,
+                                    MonitorProcessMessage::Ready => ready = true,
+                                    MonitorProcessMessage::StdoutClosed => {}
+                                }
+                            }
+                        }
+
+                        if !can_relaunch(ready, terminate_process) {
+                            ceremony_tx
+                                .broadcast(CeremonyMessage::Shutdown(
+                                    options.exit_error_reason.clone(),
+                                ))
+                                .expect("Error sending shutdown message");
+                            panic!("Error while running process: {}", error);
+                        }
+
+                        let backoff = startup_backoff(options.startup_backoff, relaunches);
+                        relaunches += 1;
+                        tracing::warn!(
+                            "Process exited before it was ready ({}), relaunching it in {:?} \
+                            (attempt {} of {}).",
+                            error,
+                            backoff,
+                            relaunches,
+                            options.startup_retries
+                        );
+                        relaunch_at = Some(Instant::now() + backoff);
                     }
                 }
             } else if terminate_process {
@@ -174,9 +448,59 @@ where
 
                 if let Err(err) = process.terminate() {
                     tracing::error!("Error while terminating process: {}. Thread closing.", err);
+                    break;
+                }
+
+                // Wait for the process to exit so that it is reaped
+                // before this thread closes.
+                match process.wait_timeout(options.terminate_grace) {
+                    Ok(Some(status)) => {
+                        set_exit_status(status);
+                        tracing::info!("Process terminated.")
+                    }
+                    Ok(None) => {
+                        tracing::warn!(
+                            "Process did not terminate within {:?}, killing it.",
+                            options.terminate_grace
+                        );
+                        if let Err(err) = process.kill() {
+                            tracing::error!("Error while killing process: {}", err);
+                        } else {
+                            match process.wait() {
+                                Ok(status) => set_exit_status(status),
+                                Err(err) => {
+                                    tracing::error!(
+                                        "Error while waiting for killed process: {}",
+                                        err
+                                    )
+                                }
+                            }
+                        }
+                    }
+                    Err(err) => tracing::error!("Error while waiting for process: {}", err),
                 }
 
                 break;
+            } else if let Some(stdout_closed) = stdout_closed {
+                if stdout_closed.elapsed() > options.eof_grace {
+                    tracing::error!(
+                        "Process did not exit within {:?} of its stdout closing, killing it.",
+                        options.eof_grace
+                    );
+
+                    if let Err(err) = process.kill() {
+                        tracing::error!("Error while killing process: {}", err);
+                    } else {
+                        match process.wait() {
+                            Ok(status) => set_exit_status(status),
+                            Err(err) => {
+                                tracing::error!("Error while waiting for killed process: {}", err)
+                            }
+                        }
+                    }
+
+                    break;
+                }
             }
         }
 
@@ -186,6 +510,9 @@ where
     Ok((
         MonitorProcessJoin {
             id,
+            pid,
+            exit_status,
+            monitor_tx: return_monitor_tx.clone(),
             monitor_join,
             messages_join,
         },
@@ -193,23 +520,105 @@ where
     ))
 }
 
+/// Open the process specified in `exec`, with `stdout` set to
+/// [Redirection::Pipe], returning the process along with its output.
+/// If `separate_stderr` is `true`, `stderr` is also set to
+/// [Redirection::Pipe], otherwise it is merged into `stdout`.
+fn open_process(exec: Exec, separate_stderr: bool) -> eyre::Result<(Popen, ProcessOutput)> {
+    let stderr_redirection = if separate_stderr {
+        Redirection::Pipe
+    } else {
+        Redirection::Merge
+    };
+
+    let mut process = exec
+        .stdout(Redirection::Pipe)
+        .stderr(stderr_redirection)
+        .popen()
+        .wrap_err("Error opening process")?;
+
+    // Extract the stdout [std::fs::File] from `process`, replacing it
+    // with a None. This is needed so we can both listen to stdout and
+    // interact with `process`'s mutable methods (to terminate it if
+    // required).
+    let stdout = process
+        .stdout
+        .take()
+        .ok_or_else(|| eyre::eyre!("Unable to obtain process `stdout`."))?;
+
+    let stderr = if separate_stderr {
+        Some(
+            process
+                .stderr
+                .take()
+                .ok_or_else(|| eyre::eyre!("Unable to obtain process `stderr`."))?,
+        )
+    } else {
+        None
+    };
+
+    Ok((process, ProcessOutput { stdout, stderr }))
+}
+
+/// How long to wait before relaunching a process which has already
+/// been relaunched `relaunches` times, doubling the `initial` backoff
+/// for each relaunch, up to [MAX_STARTUP_BACKOFF] (or the `initial`
+/// backoff, if it is longer).
+fn startup_backoff(initial: Duration, relaunches: u32) -> Duration {
+    let max_backoff = MAX_STARTUP_BACKOFF.max(initial);
+    2u32.checked_pow(relaunches)
+        .and_then(|factor| initial.checked_mul(factor))
+        .map_or(max_backoff, |backoff| backoff.min(max_backoff))
+}
+
+/// Receive messages from `monitor_rx` until
+/// [MonitorProcessMessage::StdoutClosed] is received (inclusive), or
+/// `timeout` has elapsed, returning the messages received.
+fn wait_stdout_closed(
+    monitor_rx: &mut Receiver<MonitorProcessMessage>,
+    timeout: Duration,
+) -> Vec<MonitorProcessMessage> {
+    let start = Instant::now();
+    let mut messages = Vec::new();
+
+    while start.elapsed() < timeout {
+        match monitor_rx.try_recv() {
+            Ok(MonitorProcessMessage::StdoutClosed) => {
+                messages.push(MonitorProcessMessage::StdoutClosed);
+                break;
+            }
+            Ok(message) => messages.push(message),
+            Err(TryRecvError::Empty) => std::thread::sleep(Duration::from_millis(10)),
+            Err(TryRecvError::Disconnected) => {
+                panic!("`monitor_rx` disconnected");
+            }
+        }
+    }
+
+    messages
+}
+
 /// Create a monitor function to be used with [run_monitor_process()] that
 /// may return an [eyre::Result], if the result is an `Err` then a
 /// panic will occur and the ceremony will shut down with a
 /// [CeremonyMessage::Shutdown].
 pub fn fallible_monitor<M>(
     fallible_monitor: M,
-) -> impl Fn(File, Sender<CeremonyMessage>, Sender<MonitorProcessMessage>)
+) -> impl Fn(ProcessOutput, Sender<CeremonyMessage>, Sender<MonitorProcessMessage>)
 where
-    M: Fn(File, Sender<CeremonyMessage>, Sender<MonitorProcessMessage>) -> eyre::Result<()>
+    M: Fn(
+            ProcessOutput,
+            Sender<CeremonyMessage>,
+            Sender<MonitorProcessMessage>,
+        ) -> eyre::Result<()>
         + Send
         + Sync
         + 'static,
 {
-    move |stdout: File,
+    move |output: ProcessOutput,
           coordinator_tx: Sender<CeremonyMessage>,
           monitor_tx: Sender<MonitorProcessMessage>| {
-        if let Err(error) = fallible_monitor(stdout, coordinator_tx.clone(), monitor_tx) {
+        if let Err(error) = fallible_monitor(output, coordinator_tx.clone(), monitor_tx) {
             // tell the other threads to shut down
             let _ = coordinator_tx.broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error));
             // TODO: change this into something that records the fatal message, and requests a shutdown.
@@ -218,3 +627,269 @@ where
         }
     }
 }
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::{
+        fs::OpenOptions,
+        io::{BufRead, BufReader, Write},
+        time::{Duration, Instant},
+    };
+
+    use mpmc_bus::Bus;
+    use subprocess::{Exec, ExitStatus};
+
+    use super::{
+        default_parse_exit_status, fallible_monitor, run_monitor_process, startup_backoff,
+        wait_for_shutdown, MonitorProcessJoin, MonitorProcessMessage, MonitorProcessOptions,
+        MAX_STARTUP_BACKOFF,
+    };
+    use crate::{CeremonyMessage, ShutdownReason};
+
+    /// Test that once [wait_for_shutdown()] returns, the process has
+    /// exited and all of its output has been written to the log file.
+    #[test]
+    fn test_wait_for_shutdown() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let log_file_path = out_dir.path().join("process.log");
+        let monitor_log_file_path = log_file_path.clone();
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let (join, _) = run_monitor_process(
+            "test".to_string(),
+            || {
+                Ok(Exec::cmd("sh").arg("-c").arg(
+                    "trap 'echo shutting down; exit 0' TERM; \
+                    echo started; \
+                    while true; do sleep 0.1; done",
+                ))
+            },
+            default_parse_exit_status,
+            MonitorProcessOptions::default(),
+            bus.broadcaster(),
+            bus.subscribe(),
+            fallible_monitor(move |output, _ceremony_tx, _monitor_tx| {
+                let mut log_file = OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(&monitor_log_file_path)?;
+                for line in BufReader::new(output.stdout).lines() {
+                    writeln!(log_file, "{}", line?)?;
+                }
+                Ok(())
+            }),
+        )
+        .unwrap();
+
+        std::thread::sleep(Duration::from_millis(300));
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        wait_for_shutdown(join, Duration::from_secs(10)).unwrap();
+
+        let log = std::fs::read_to_string(&log_file_path).unwrap();
+        assert_eq!(
+            vec!["started", "shutting down"],
+            log.lines().collect::<Vec<_>>()
+        );
+    }
+
+    /// Run a process with the specified shell `script` which discards
+    /// its output, and wait for it to be joined.
+    fn run_script(script: &str, options: MonitorProcessOptions) -> MonitorProcessJoin {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let script = script.to_string();
+        let (join, _) = run_monitor_process(
+            "test".to_string(),
+            move || Ok(Exec::cmd("sh").arg("-c").arg(&script)),
+            default_parse_exit_status,
+            options,
+            bus.broadcaster(),
+            bus.subscribe(),
+            fallible_monitor(|output, _ceremony_tx, _monitor_tx| {
+                for line in BufReader::new(output.stdout).lines() {
+                    line?;
+                }
+                Ok(())
+            }),
+        )
+        .unwrap();
+        join
+    }
+
+    /// Test that the exit status is captured for a process which
+    /// closes its stdout, but stays alive briefly before exiting.
+    #[test]
+    fn test_exit_status_after_stdout_closed() {
+        let join = run_script(
+            "echo closing; exec >&- 2>&-; sleep 0.5; exit 0",
+            MonitorProcessOptions::default(),
+        );
+
+        let exit_status = wait_for_join(join);
+        assert_eq!(Some(ExitStatus::Exited(0)), exit_status);
+    }
+
+    /// Test that a process which does not exit within the grace
+    /// period after closing its stdout is killed.
+    #[test]
+    fn test_killed_after_eof_grace() {
+        let join = run_script(
+            "exec >&- 2>&-; sleep 10",
+            MonitorProcessOptions {
+                eof_grace: Duration::from_millis(200),
+                ..MonitorProcessOptions::default()
+            },
+        );
+
+        let exit_status = wait_for_join(join);
+        assert_eq!(Some(ExitStatus::Signaled(9)), exit_status);
+    }
+
+    /// Test that a process which ignores `SIGTERM` is killed once
+    /// [MonitorProcessOptions::terminate_grace] has elapsed after a
+    /// [CeremonyMessage::Shutdown].
+    #[test]
+    fn test_killed_after_terminate_grace() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let (join, _) = run_monitor_process(
+            "test".to_string(),
+            || {
+                Ok(Exec::cmd("sh")
+                    .arg("-c")
+                    .arg("trap '' TERM; while true; do sleep 0.1; done"))
+            },
+            default_parse_exit_status,
+            MonitorProcessOptions {
+                terminate_grace: Duration::from_millis(500),
+                ..MonitorProcessOptions::default()
+            },
+            bus.broadcaster(),
+            bus.subscribe(),
+            fallible_monitor(|output, _ceremony_tx, _monitor_tx| {
+                for line in BufReader::new(output.stdout).lines() {
+                    line?;
+                }
+                Ok(())
+            }),
+        )
+        .unwrap();
+
+        std::thread::sleep(Duration::from_millis(300));
+        let start = Instant::now();
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+
+        let exit_status = wait_for_join(join);
+        assert_eq!(Some(ExitStatus::Signaled(9)), exit_status);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    /// Test that [MonitorProcessJoin::terminate()] terminates the
+    /// process with `SIGTERM`.
+    #[test]
+    fn test_terminate() {
+        let join = run_script(
+            "while true; do sleep 0.1; done",
+            MonitorProcessOptions::default(),
+        );
+
+        std::thread::sleep(Duration::from_millis(300));
+        join.terminate().unwrap();
+
+        let exit_status = wait_for_join(join);
+        assert_eq!(Some(ExitStatus::Signaled(15)), exit_status);
+    }
+
+    /// Run a process with the specified shell `script`, and a monitor
+    /// which sends [MonitorProcessMessage::Ready] when the process
+    /// outputs `ready`.
+    fn run_ready_script(script: &str, options: MonitorProcessOptions) -> MonitorProcessJoin {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let script = script.to_string();
+        let (join, _) = run_monitor_process(
+            "test".to_string(),
+            move || Ok(Exec::cmd("sh").arg("-c").arg(&script)),
+            default_parse_exit_status,
+            options,
+            bus.broadcaster(),
+            bus.subscribe(),
+            fallible_monitor(|output, _ceremony_tx, monitor_tx| {
+                for line in BufReader::new(output.stdout).lines() {
+                    if line? == "ready" {
+                        monitor_tx
+                            .broadcast(MonitorProcessMessage::Ready)
+                            .map_err(|error| eyre::eyre!("{}", error))?;
+                    }
+                }
+                Ok(())
+            }),
+        )
+        .unwrap();
+        join
+    }
+
+    /// Test that a process which fails before it is ready is
+    /// relaunched, but not once it has become ready.
+    #[test]
+    fn test_startup_retries() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let launches_path = out_dir.path().join("launches");
+        let options = MonitorProcessOptions {
+            startup_retries: 3,
+            startup_backoff: Duration::from_millis(50),
+            ..MonitorProcessOptions::default()
+        };
+
+        // Fails on the first two launches, then becomes ready and
+        // exits successfully.
+        let join = run_ready_script(
+            &format!(
+                "echo launched >> {path}; \
+                if [ $(wc -l < {path}) -lt 3 ]; then exit 1; fi; \
+                echo ready; exit 0",
+                path = launches_path.display()
+            ),
+            options.clone(),
+        );
+        assert_eq!(Some(ExitStatus::Exited(0)), wait_for_join(join));
+        let launches = std::fs::read_to_string(&launches_path).unwrap();
+        assert_eq!(3, launches.lines().count());
+
+        // Fails after becoming ready, which is not retried.
+        std::fs::remove_file(&launches_path).unwrap();
+        let join = run_ready_script(
+            &format!(
+                "echo launched >> {path}; echo ready; sleep 0.2; exit 1",
+                path = launches_path.display()
+            ),
+            options,
+        );
+        assert!(join.join().is_err());
+        let launches = std::fs::read_to_string(&launches_path).unwrap();
+        assert_eq!(1, launches.lines().count());
+    }
+
+    /// Test that the relaunch backoff doubles for each relaunch, and
+    /// is capped instead of overflowing.
+    #[test]
+    fn test_startup_backoff() {
+        let initial = Duration::from_secs(1);
+        assert_eq!(initial, startup_backoff(initial, 0));
+        assert_eq!(Duration::from_secs(8), startup_backoff(initial, 3));
+        assert_eq!(MAX_STARTUP_BACKOFF, startup_backoff(initial, 20));
+        assert_eq!(MAX_STARTUP_BACKOFF, startup_backoff(initial, u32::MAX));
+        assert_eq!(
+            Duration::from_secs(u64::MAX),
+            startup_backoff(Duration::from_secs(u64::MAX), 1)
+        );
+    }
+
+    /// Join the threads of the `join`, returning the exit status
+    /// of the process.
+    fn wait_for_join(join: MonitorProcessJoin) -> Option<ExitStatus> {
+        let exit_status = join.exit_status.clone();
+        join.join().unwrap();
+        let status = *exit_status.lock().unwrap();
+        status
+    }
+}
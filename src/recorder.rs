@@ -0,0 +1,558 @@
+//! A utility for recording the [CeremonyMessage]s broadcast during a
+//! test, so that assertions can be made about them afterwards.
+
+use std::{
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use mpmc_bus::Receiver;
+
+use crate::{CeremonyMessage, ContributorRef, ParticipantRef, VerifierRef};
+
+/// A [CeremonyMessage] received by a [MessageRecorder].
+#[derive(Debug, Clone)]
+pub struct RecordedMessage {
+    /// The time that the message was received by the recorder.
+    pub received: Instant,
+    /// The message that was received.
+    pub message: CeremonyMessage,
+}
+
+/// A pattern to match against a [CeremonyMessage]. Each variant
+/// other than [CeremonyMessagePattern::Exact] matches any message of
+/// the [CeremonyMessage] variant with the same name, regardless of
+/// the data it contains.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CeremonyMessagePattern {
+    RoundStarted,
+    RoundStateWritten,
+    RoundStartedAggregation,
+    AggregationProgress,
+    RoundAggregated,
+    RoundFinished,
+    RoundTimings,
+    RoundRolledBack,
+    RoundRestarted,
+    RoundVerified,
+    CoordinatorReady,
+    RoundWaitingForParticipants,
+    ParticipantJoinedQueue,
+    ParticipantDropped,
+    ReplacementActivated,
+    SuccessfulContribution,
+    SuccessfulVerification,
+    DuplicateContribution,
+    ContributorRestarted,
+    VerifierDisconnected,
+    VerifierReconnected,
+    CoordinatorPaused,
+    CoordinatorResumed,
+    Heartbeat,
+    ResourceSample,
+    CoordinatorExited,
+    Shutdown,
+    /// Matches only a message which is equal to this message.
+    Exact(CeremonyMessage),
+}
+
+impl CeremonyMessagePattern {
+    /// Returns `true` if the `message` matches this pattern.
+    pub fn matches(&self, message: &CeremonyMessage) -> bool {
+        match self {
+            CeremonyMessagePattern::RoundStarted => {
+                matches!(message, CeremonyMessage::RoundStarted(_))
+            }
+            CeremonyMessagePattern::RoundStateWritten => {
+                matches!(message, CeremonyMessage::RoundStateWritten(_))
+            }
+            CeremonyMessagePattern::RoundStartedAggregation => {
+                matches!(message, CeremonyMessage::RoundStartedAggregation(_))
+            }
+            CeremonyMessagePattern::AggregationProgress => {
+                matches!(message, CeremonyMessage::AggregationProgress { .. })
+            }
+            CeremonyMessagePattern::RoundAggregated => {
+                matches!(message, CeremonyMessage::RoundAggregated(_))
+            }
+            CeremonyMessagePattern::RoundFinished => {
+                matches!(message, CeremonyMessage::RoundFinished(_))
+            }
+            CeremonyMessagePattern::RoundTimings => {
+                matches!(message, CeremonyMessage::RoundTimings { .. })
+            }
+            CeremonyMessagePattern::RoundRolledBack => {
+                matches!(message, CeremonyMessage::RoundRolledBack(_))
+            }
+            CeremonyMessagePattern::RoundRestarted => {
+                matches!(message, CeremonyMessage::RoundRestarted(_))
+            }
+            CeremonyMessagePattern::RoundVerified => {
+                matches!(message, CeremonyMessage::RoundVerified { .. })
+            }
+            CeremonyMessagePattern::CoordinatorReady => {
+                matches!(message, CeremonyMessage::CoordinatorReady)
+            }
+            CeremonyMessagePattern::RoundWaitingForParticipants => {
+                matches!(message, CeremonyMessage::RoundWaitingForParticipants(_))
+            }
+            CeremonyMessagePattern::ParticipantJoinedQueue => {
+                matches!(message, CeremonyMessage::ParticipantJoinedQueue(_))
+            }
+            CeremonyMessagePattern::ParticipantDropped => {
+                matches!(message, CeremonyMessage::ParticipantDropped(_))
+            }
+            CeremonyMessagePattern::ReplacementActivated => {
+                matches!(message, CeremonyMessage::ReplacementActivated { .. })
+            }
+            CeremonyMessagePattern::SuccessfulContribution => {
+                matches!(message, CeremonyMessage::SuccessfulContribution { .. })
+            }
+            CeremonyMessagePattern::SuccessfulVerification => {
+                matches!(message, CeremonyMessage::SuccessfulVerification { .. })
+            }
+            CeremonyMessagePattern::DuplicateContribution => {
+                matches!(message, CeremonyMessage::DuplicateContribution { .. })
+            }
+            CeremonyMessagePattern::ContributorRestarted => {
+                matches!(message, CeremonyMessage::ContributorRestarted(_))
+            }
+            CeremonyMessagePattern::VerifierDisconnected => {
+                matches!(message, CeremonyMessage::VerifierDisconnected(_))
+            }
+            CeremonyMessagePattern::VerifierReconnected => {
+                matches!(message, CeremonyMessage::VerifierReconnected(_))
+            }
+            CeremonyMessagePattern::CoordinatorPaused => {
+                matches!(message, CeremonyMessage::CoordinatorPaused)
+            }
+            CeremonyMessagePattern::CoordinatorResumed => {
+                matches!(message, CeremonyMessage::CoordinatorResumed)
+            }
+            CeremonyMessagePattern::Heartbeat => {
+                matches!(message, CeremonyMessage::Heartbeat { .. })
+            }
+            CeremonyMessagePattern::ResourceSample => {
+                matches!(message, CeremonyMessage::ResourceSample { .. })
+            }
+            CeremonyMessagePattern::CoordinatorExited => {
+                matches!(message, CeremonyMessage::CoordinatorExited { .. })
+            }
+            CeremonyMessagePattern::Shutdown => matches!(message, CeremonyMessage::Shutdown(_)),
+            CeremonyMessagePattern::Exact(expected) => expected == message,
+        }
+    }
+}
+
+/// Records all the [CeremonyMessage]s received in a background
+/// thread, up to and including the first [CeremonyMessage::Shutdown].
+pub struct MessageRecorder {
+    messages: Arc<Mutex<Vec<RecordedMessage>>>,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+impl std::fmt::Debug for MessageRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageRecorder")
+            .field("join_handle", &self.join_handle)
+            .finish()
+    }
+}
+
+impl MessageRecorder {
+    /// Spawn a thread which records the messages received via
+    /// `ceremony_rx`.
+    pub fn spawn(mut ceremony_rx: Receiver<CeremonyMessage>) -> Self {
+        let messages: Arc<Mutex<Vec<RecordedMessage>>> = Arc::default();
+        let thread_messages = messages.clone();
+        let span = tracing::error_span!("message_recorder");
+
+        let join_handle = std::thread::spawn(move || {
+            let _guard = span.enter();
+
+            loop {
+                let message = match ceremony_rx.recv() {
+                    Ok(message) => message,
+                    Err(error) => {
+                        tracing::error!("Error receiving message, thread closing: {}", error);
+                        break;
+                    }
+                };
+
+                let is_shutdown = matches!(message, CeremonyMessage::Shutdown(_));
+
+                thread_messages
+                    .lock()
+                    .expect("error obtaining lock")
+                    .push(RecordedMessage {
+                        received: Instant::now(),
+                        message,
+                    });
+
+                if is_shutdown {
+                    break;
+                }
+            }
+
+            tracing::debug!("Thread closing gracefully.")
+        });
+
+        Self {
+            messages,
+            join_handle: Some(join_handle),
+        }
+    }
+
+    /// Block until the recorder has received the
+    /// [CeremonyMessage::Shutdown] and stopped recording.
+    pub fn join(&mut self) -> std::thread::Result<()> {
+        match self.join_handle.take() {
+            Some(join_handle) => join_handle.join(),
+            None => Ok(()),
+        }
+    }
+
+    /// A copy of the messages that have been recorded so far.
+    pub fn messages(&self) -> Vec<RecordedMessage> {
+        self.messages.lock().expect("error obtaining lock").clone()
+    }
+
+    /// Returns an error listing the messages that were recorded which
+    /// do not match any of the `allowed` patterns.
+    pub fn assert_no_unexpected(&self, allowed: &[CeremonyMessagePattern]) -> eyre::Result<()> {
+        let unexpected: Vec<CeremonyMessage> = self
+            .messages()
+            .into_iter()
+            .map(|recorded| recorded.message)
+            .filter(|message| !allowed.iter().any(|pattern| pattern.matches(message)))
+            .collect();
+
+        if unexpected.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre::eyre!(
+                "Unexpected messages were received: {:?}",
+                unexpected
+            ))
+        }
+    }
+}
+
+/// Returns an error unless the first recorded message matching `from`
+/// is followed by a message matching `to` within `max` time, e.g. to
+/// check that aggregation started soon enough after the last
+/// contribution. Times are measured using [RecordedMessage::received].
+pub fn assert_duration_between(
+    recorder: &MessageRecorder,
+    from: &CeremonyMessagePattern,
+    to: &CeremonyMessagePattern,
+    max: Duration,
+) -> eyre::Result<()> {
+    let messages = recorder.messages();
+
+    let from_index = messages
+        .iter()
+        .position(|recorded| from.matches(&recorded.message))
+        .ok_or_else(|| eyre::eyre!("No message matching {:?} was recorded", from))?;
+    let from_message = &messages[from_index];
+
+    let to_message = messages[from_index + 1..]
+        .iter()
+        .find(|recorded| to.matches(&recorded.message))
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "No message matching {:?} was recorded after {:?}",
+                to,
+                from_message.message
+            )
+        })?;
+
+    let elapsed = to_message
+        .received
+        .saturating_duration_since(from_message.received);
+
+    if elapsed > max {
+        return Err(eyre::eyre!(
+            "{:?} was received {:?} after {:?}, expected within {:?}",
+            to_message.message,
+            elapsed,
+            from_message.message,
+            max
+        ));
+    }
+
+    Ok(())
+}
+
+/// Every distinct contributor and verifier observed in the messages
+/// recorded by the `recorder` (queue joins, contributions, drops and
+/// verifier connection changes), in the order they were first seen.
+/// Unlike the round state files, this includes transient participants
+/// which were dropped or never made it into a round.
+pub fn all_participants_seen(
+    recorder: &MessageRecorder,
+) -> (Vec<ContributorRef>, Vec<VerifierRef>) {
+    let mut contributors: Vec<ContributorRef> = Vec::new();
+    let mut verifiers: Vec<VerifierRef> = Vec::new();
+
+    let mut add_participant = |participant: &ParticipantRef| match participant {
+        ParticipantRef::Contributor(contributor) => {
+            if !contributors.contains(contributor) {
+                contributors.push(contributor.clone());
+            }
+        }
+        ParticipantRef::Verifier(verifier) => {
+            if !verifiers.contains(verifier) {
+                verifiers.push(verifier.clone());
+            }
+        }
+    };
+
+    for recorded in recorder.messages() {
+        match recorded.message {
+            CeremonyMessage::ParticipantJoinedQueue(participant)
+            | CeremonyMessage::ParticipantDropped(participant) => add_participant(&participant),
+            CeremonyMessage::ReplacementActivated {
+                dropped,
+                replacement,
+            } => {
+                add_participant(&ParticipantRef::Contributor(dropped));
+                add_participant(&ParticipantRef::Contributor(replacement));
+            }
+            CeremonyMessage::SuccessfulContribution { contributor, .. }
+            | CeremonyMessage::ContributorRestarted(contributor) => {
+                add_participant(&ParticipantRef::Contributor(contributor))
+            }
+            CeremonyMessage::SuccessfulVerification { verifier, .. }
+            | CeremonyMessage::VerifierDisconnected(verifier)
+            | CeremonyMessage::VerifierReconnected(verifier) => {
+                add_participant(&ParticipantRef::Verifier(verifier))
+            }
+            _ => {}
+        }
+    }
+
+    (contributors, verifiers)
+}
+
+/// Returns an error unless the round numbers of the recorded
+/// [CeremonyMessage::RoundStarted] messages, and of the
+/// [CeremonyMessage::RoundFinished] messages, each increase by exactly
+/// one from message to message, with no gaps or repeats. The error
+/// reports the first pair of messages which violates this.
+pub fn assert_rounds_monotonic(recorder: &MessageRecorder) -> eyre::Result<()> {
+    let messages: Vec<CeremonyMessage> = recorder
+        .messages()
+        .into_iter()
+        .map(|recorded| recorded.message)
+        .collect();
+
+    let started = messages.iter().filter_map(|message| match message {
+        CeremonyMessage::RoundStarted(round) => Some(*round),
+        _ => None,
+    });
+    let finished = messages.iter().filter_map(|message| match message {
+        CeremonyMessage::RoundFinished(round) => Some(*round),
+        _ => None,
+    });
+
+    check_monotonic("RoundStarted", started)?;
+    check_monotonic("RoundFinished", finished)
+}
+
+fn check_monotonic(name: &str, rounds: impl Iterator<Item = u64>) -> eyre::Result<()> {
+    let mut previous: Option<u64> = None;
+
+    for round in rounds {
+        if let Some(previous) = previous {
+            if round != previous + 1 {
+                return Err(eyre::eyre!(
+                    "{}({}) was followed by {}({}), expected {}({})",
+                    name,
+                    previous,
+                    name,
+                    round,
+                    name,
+                    previous + 1
+                ));
+            }
+        }
+        previous = Some(round);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use mpmc_bus::Bus;
+
+    use super::{
+        all_participants_seen, assert_duration_between, assert_rounds_monotonic,
+        CeremonyMessagePattern, MessageRecorder,
+    };
+    use crate::{CeremonyMessage, ContributorRef, ParticipantRef, ShutdownReason, VerifierRef};
+
+    /// Test that an unexpected [CeremonyMessage::ParticipantDropped]
+    /// is reported.
+    #[test]
+    fn test_assert_no_unexpected() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut recorder = MessageRecorder::spawn(bus.subscribe());
+
+        let contributor = ContributorRef {
+            address: "aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689"
+                .parse()
+                .unwrap(),
+        };
+
+        bus.broadcast(CeremonyMessage::RoundStarted(1)).unwrap();
+        bus.broadcast(CeremonyMessage::ParticipantDropped(
+            ParticipantRef::Contributor(contributor),
+        ))
+        .unwrap();
+        bus.broadcast(CeremonyMessage::RoundFinished(1)).unwrap();
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        recorder.join().unwrap();
+
+        assert_eq!(4, recorder.messages().len());
+
+        let allowed = vec![
+            CeremonyMessagePattern::RoundStarted,
+            CeremonyMessagePattern::Exact(CeremonyMessage::RoundFinished(1)),
+            CeremonyMessagePattern::Shutdown,
+        ];
+        let error = recorder.assert_no_unexpected(&allowed).unwrap_err();
+        assert!(error.to_string().contains("ParticipantDropped"));
+        assert!(!error.to_string().contains("RoundStarted"));
+
+        let mut allowed = allowed;
+        allowed.push(CeremonyMessagePattern::ParticipantDropped);
+        recorder.assert_no_unexpected(&allowed).unwrap();
+    }
+
+    /// Test the elapsed time between milestones in a timed message
+    /// sequence.
+    #[test]
+    fn test_assert_duration_between() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut recorder = MessageRecorder::spawn(bus.subscribe());
+
+        bus.broadcast(CeremonyMessage::RoundStarted(1)).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        bus.broadcast(CeremonyMessage::RoundStartedAggregation(1))
+            .unwrap();
+        bus.broadcast(CeremonyMessage::RoundAggregated(1)).unwrap();
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        recorder.join().unwrap();
+
+        assert_duration_between(
+            &recorder,
+            &CeremonyMessagePattern::RoundStarted,
+            &CeremonyMessagePattern::RoundStartedAggregation,
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert!(assert_duration_between(
+            &recorder,
+            &CeremonyMessagePattern::RoundStarted,
+            &CeremonyMessagePattern::RoundStartedAggregation,
+            Duration::from_millis(50),
+        )
+        .is_err());
+
+        // `to` must be received after `from`.
+        assert!(assert_duration_between(
+            &recorder,
+            &CeremonyMessagePattern::RoundAggregated,
+            &CeremonyMessagePattern::RoundStarted,
+            Duration::from_secs(10),
+        )
+        .is_err());
+    }
+
+    /// Test that the roster of participants includes every distinct
+    /// participant in the recorded events.
+    #[test]
+    fn test_all_participants_seen() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut recorder = MessageRecorder::spawn(bus.subscribe());
+
+        let contributor1 = ContributorRef {
+            address: "aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689"
+                .parse()
+                .unwrap(),
+        };
+        let contributor2 = ContributorRef {
+            address: "aleo1qgqkzmv9jzq3y2ykl4vdm2mcaa7wh8kl3jzjy2cm8tg63k9xcsqqyvsw5m"
+                .parse()
+                .unwrap(),
+        };
+        let verifier = VerifierRef {
+            address: "aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689"
+                .parse()
+                .unwrap(),
+        };
+
+        let messages = vec![
+            CeremonyMessage::ParticipantJoinedQueue(ParticipantRef::Contributor(
+                contributor1.clone(),
+            )),
+            CeremonyMessage::RoundStarted(1),
+            CeremonyMessage::SuccessfulContribution {
+                contributor: contributor1.clone(),
+                chunk: 0,
+                seq: 0,
+            },
+            CeremonyMessage::VerifierDisconnected(verifier.clone()),
+            CeremonyMessage::VerifierReconnected(verifier.clone()),
+            // Transient contributor which is dropped without appearing
+            // in any round.
+            CeremonyMessage::ParticipantDropped(ParticipantRef::Contributor(contributor2.clone())),
+            CeremonyMessage::RoundFinished(1),
+            CeremonyMessage::Shutdown(ShutdownReason::TestFinished),
+        ];
+        for message in messages {
+            bus.broadcast(message).unwrap();
+        }
+        recorder.join().unwrap();
+
+        let (contributors, verifiers) = all_participants_seen(&recorder);
+        assert_eq!(vec![contributor1, contributor2], contributors);
+        assert_eq!(vec![verifier], verifiers);
+    }
+
+    /// Test that a gap in the round numbers fails the assertion,
+    /// reporting the violating pair.
+    #[test]
+    fn test_assert_rounds_monotonic() {
+        let record = |rounds: &[u64]| {
+            let bus = Bus::<CeremonyMessage>::new(100);
+            let mut recorder = MessageRecorder::spawn(bus.subscribe());
+            for round in rounds {
+                bus.broadcast(CeremonyMessage::RoundStarted(*round))
+                    .unwrap();
+                bus.broadcast(CeremonyMessage::RoundFinished(*round))
+                    .unwrap();
+            }
+            bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+                .unwrap();
+            recorder.join().unwrap();
+            recorder
+        };
+
+        assert_rounds_monotonic(&record(&[1, 2, 3])).unwrap();
+
+        let error = assert_rounds_monotonic(&record(&[1, 2, 4])).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("RoundStarted(2) was followed by RoundStarted(4)"));
+
+        assert!(assert_rounds_monotonic(&record(&[1, 1])).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Re-reading the [Config] file while the test is running (e.g. on
+//! `SIGHUP`, see [spawn_sighup_reloader()]), for long running soak
+//! test harnesses.
+//!
+//! Only the following [Config] fields are hot-reloadable, and take
+//! effect as soon as the config is reloaded:
+//!
+//! + [Config::log_filter]
+//!
+//! All other fields (the repositories, `out_dir`, `clean`, `build`,
+//! `keep_repos`, `install_prerequisites` and `state_monitor`) are
+//! only read when the test starts, and changes to them require a
+//! restart. They are ignored when the config is reloaded.
+
+use std::path::{Path, PathBuf};
+
+use eyre::Context;
+
+use crate::{config::Config, reporting::LogFilterHandle};
+
+/// Apply the hot-reloadable fields of the `config` (see the [module
+/// documentation](self)).
+pub fn apply_config(config: &Config, log_filter: &LogFilterHandle) -> eyre::Result<()> {
+    if let Some(filter) = &config.log_filter {
+        log_filter.set_filter(filter)?;
+        tracing::info!("Log filter set to {:?}.", filter);
+    }
+
+    Ok(())
+}
+
+/// Re-read the [Config] file at `config_file`, and apply its
+/// hot-reloadable fields (see the [module documentation](self)).
+pub fn reload_config(config_file: &Path, log_filter: &LogFilterHandle) -> eyre::Result<Config> {
+    tracing::info!("Reloading configuration from file: {:?}", config_file);
+
+    let config_string = std::fs::read_to_string(config_file).wrap_err_with(|| {
+        eyre::eyre!("Error while reading configuration file {:?}", config_file)
+    })?;
+    let config: Config = ron::from_str(&config_string)
+        .wrap_err_with(|| eyre::eyre!("Error while parsing configuration {:?}", config_file))?;
+
+    apply_config(&config, log_filter)?;
+
+    Ok(config)
+}
+
+/// Spawn a thread which calls [reload_config()] each time the process
+/// receives a `SIGHUP` signal. Errors while reloading are logged, and
+/// the previous configuration remains in effect.
+#[cfg(unix)]
+pub fn spawn_sighup_reloader(
+    config_file: PathBuf,
+    log_filter: LogFilterHandle,
+) -> eyre::Result<std::thread::JoinHandle<()>> {
+    let mut signals = signal_hook::iterator::Signals::new(&[signal_hook::consts::SIGHUP])
+        .wrap_err("Unable to register SIGHUP handler")?;
+    let span = tracing::error_span!("config_reloader");
+
+    Ok(std::thread::spawn(move || {
+        let _guard = span.enter();
+
+        for _ in signals.forever() {
+            if let Err(error) = reload_config(&config_file, &log_filter) {
+                tracing::error!("Unable to reload configuration: {:?}", error);
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use tracing_subscriber::{prelude::__tracing_subscriber_SubscriberExt, EnvFilter};
+
+    use super::reload_config;
+    use crate::reporting::LogFilterHandle;
+
+    /// Test that reloading the config applies the new log filter.
+    #[test]
+    fn test_reload_log_filter() {
+        let (filter_layer, log_filter) = LogFilterHandle::new(EnvFilter::new("info"));
+        let _subscriber = tracing_subscriber::registry().with(filter_layer);
+        assert_eq!("info", log_filter.current_filter().unwrap());
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let config_file = out_dir.path().join("config.ron");
+        std::fs::write(
+            &config_file,
+            r#"(clean: false, keep_repos: true, out_dir: "out", log_filter: Some("debug"))"#,
+        )
+        .unwrap();
+
+        let config = reload_config(&config_file, &log_filter).unwrap();
+        assert_eq!(Some("debug"), config.log_filter.as_deref());
+        assert_eq!("debug", log_filter.current_filter().unwrap());
+
+        // An invalid config leaves the filter unchanged.
+        std::fs::write(&config_file, "(clean: ").unwrap();
+        assert!(reload_config(&config_file, &log_filter).is_err());
+        assert_eq!("debug", log_filter.current_filter().unwrap());
+    }
+}
@@ -7,9 +7,11 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use eyre::Context;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{
-    prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt, EnvFilter,
+    prelude::__tracing_subscriber_SubscriberExt, reload, util::SubscriberInitExt, EnvFilter,
+    Registry,
 };
 
 #[derive(Default, Debug)]
@@ -117,16 +119,58 @@ impl Write for LogFileWriter {
     }
 }
 
+/// A handle for changing the [EnvFilter] used to filter the log
+/// output while the application is running.
+#[derive(Clone, Debug)]
+pub struct LogFilterHandle {
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+impl LogFilterHandle {
+    /// Create a reloadable filter layer, and the handle to change it.
+    pub fn new(filter: EnvFilter) -> (reload::Layer<EnvFilter, Registry>, Self) {
+        let (layer, handle) = reload::Layer::new(filter);
+        (layer, Self { handle })
+    }
+
+    /// Replace the current filter with one parsed from `directives`
+    /// (in the same format as the `RUST_LOG` environment variable).
+    pub fn set_filter(&self, directives: &str) -> eyre::Result<()> {
+        let filter = EnvFilter::try_new(directives)
+            .wrap_err_with(|| eyre::eyre!("Invalid log filter {:?}", directives))?;
+        self.handle
+            .reload(filter)
+            .wrap_err("Unable to reload log filter")
+    }
+
+    /// The directives of the current filter.
+    pub fn current_filter(&self) -> eyre::Result<String> {
+        self.handle
+            .with_current(|filter| filter.to_string())
+            .wrap_err("Unable to read log filter")
+    }
+}
+
 #[must_use]
 pub struct ReportGuard {
     _guard: WorkerGuard,
+    log_filter: LogFilterHandle,
+}
+
+impl ReportGuard {
+    /// The handle for changing the log filter, see
+    /// [crate::reload].
+    pub fn log_filter(&self) -> &LogFilterHandle {
+        &self.log_filter
+    }
 }
 
 /// Set up [tracing] and [color-eyre](color_eyre).
 pub fn setup_reporting(log_writer: LogFileWriter) -> eyre::Result<ReportGuard> {
     color_eyre::install()?;
 
-    let filter_layer = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
+    let filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
+    let (filter_layer, log_filter) = LogFilterHandle::new(filter);
 
     let (tracing_log_writer, guard) = tracing_appender::non_blocking(log_writer);
     let fmt_layer = tracing_subscriber::fmt::layer().with_writer(tracing_log_writer);
@@ -139,7 +183,10 @@ pub fn setup_reporting(log_writer: LogFileWriter) -> eyre::Result<ReportGuard> {
         .with(error_layer)
         .init();
 
-    Ok(ReportGuard { _guard: guard })
+    Ok(ReportGuard {
+        _guard: guard,
+        log_filter,
+    })
 }
 
 #[cfg(test)]
@@ -0,0 +1,269 @@
+//! Functions for sampling the resource usage (memory/CPU) of a
+//! running process, such as the coordinator (see
+//! [MonitorProcessJoin::pid()](crate::process::MonitorProcessJoin::pid())).
+
+use std::{
+    fs::File,
+    io::Write,
+    path::PathBuf,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use eyre::Context;
+use mpmc_bus::{Receiver, Sender, TryRecvError};
+
+use crate::CeremonyMessage;
+
+/// Configuration for [run_resource_monitor()].
+#[derive(Debug, Clone)]
+pub struct ResourceMonitorConfig {
+    /// How often the process is sampled.
+    pub interval: Duration,
+    /// If `Some`, each sample is also appended to this CSV file
+    /// (columns: `timestamp,rss_bytes,cpu_pct`).
+    pub csv_file: Option<PathBuf>,
+}
+
+/// Raw resource usage statistics read for a process.
+#[derive(Debug, Clone, Copy)]
+struct ProcessStats {
+    /// Resident set size in bytes.
+    rss_bytes: u64,
+    /// Total user + system CPU time consumed by the process, in clock
+    /// ticks.
+    cpu_ticks: u64,
+}
+
+/// Clock ticks per second used by `/proc/<pid>/stat` (`USER_HZ`),
+/// which is 100 on all mainstream Linux configurations.
+#[cfg(target_os = "linux")]
+const USER_HZ: u64 = 100;
+
+/// Read the current resource usage of the process with the specified
+/// `pid` from `/proc/<pid>`.
+#[cfg(target_os = "linux")]
+fn read_process_stats(pid: u32) -> eyre::Result<Option<ProcessStats>> {
+    let status_path = format!("/proc/{}/status", pid);
+    let status = std::fs::read_to_string(&status_path)
+        .wrap_err_with(|| eyre::eyre!("Unable to read {:?}", status_path))?;
+
+    let rss_kb: u64 = status
+        .lines()
+        .find(|line| line.starts_with("VmRSS:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .map(str::parse::<u64>)
+        .transpose()
+        .wrap_err("Unable to parse VmRSS")?
+        .unwrap_or(0);
+
+    let stat_path = format!("/proc/{}/stat", pid);
+    let stat = std::fs::read_to_string(&stat_path)
+        .wrap_err_with(|| eyre::eyre!("Unable to read {:?}", stat_path))?;
+
+    // The process name (second field) is wrapped in parentheses and
+    // may contain spaces, so the remaining fields are split after the
+    // last closing parenthesis. `utime` and `stime` are fields 14 and
+    // 15, which are at index 11 and 12 after the process name.
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .map(|(_, rest)| rest.split_whitespace().collect())
+        .unwrap_or_default();
+
+    let parse_field = |index: usize| -> eyre::Result<u64> {
+        fields
+            .get(index)
+            .ok_or_else(|| eyre::eyre!("Missing field {} in {:?}", index, stat_path))?
+            .parse::<u64>()
+            .wrap_err_with(|| eyre::eyre!("Unable to parse field {} in {:?}", index, stat_path))
+    };
+
+    let cpu_ticks = parse_field(11)? + parse_field(12)?;
+
+    Ok(Some(ProcessStats {
+        rss_bytes: rss_kb * 1024,
+        cpu_ticks,
+    }))
+}
+
+/// Resource sampling is only supported on Linux, elsewhere no samples
+/// are produced.
+#[cfg(not(target_os = "linux"))]
+fn read_process_stats(_pid: u32) -> eyre::Result<Option<ProcessStats>> {
+    Ok(None)
+}
+
+/// Calculate the CPU usage percentage between two samples taken
+/// `elapsed` apart.
+#[cfg(target_os = "linux")]
+fn cpu_percentage(previous: &ProcessStats, current: &ProcessStats, elapsed: Duration) -> u32 {
+    let elapsed_secs = elapsed.as_secs_f64();
+    if elapsed_secs <= 0.0 {
+        return 0;
+    }
+    let ticks = current.cpu_ticks.saturating_sub(previous.cpu_ticks) as f64;
+    ((ticks / USER_HZ as f64) / elapsed_secs * 100.0).round() as u32
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpu_percentage(_previous: &ProcessStats, _current: &ProcessStats, _elapsed: Duration) -> u32 {
+    0
+}
+
+/// Run a thread which samples the memory and CPU usage of the process
+/// with the specified `pid` every [ResourceMonitorConfig::interval],
+/// broadcasting each sample as a [CeremonyMessage::ResourceSample]
+/// (and optionally writing it to [ResourceMonitorConfig::csv_file]).
+/// The thread stops when a [CeremonyMessage::Shutdown] is received,
+/// or when the process can no longer be sampled because it has
+/// exited.
+///
+/// Sampling is only supported on Linux (via `/proc/<pid>`), on other
+/// platforms this thread does nothing other than wait for the
+/// shutdown.
+pub fn run_resource_monitor(
+    pid: u32,
+    config: ResourceMonitorConfig,
+    mut ceremony_rx: Receiver<CeremonyMessage>,
+    ceremony_tx: Sender<CeremonyMessage>,
+) -> JoinHandle<eyre::Result<()>> {
+    let span = tracing::error_span!("resource_monitor", pid = pid);
+
+    std::thread::spawn(move || {
+        let _guard = span.enter();
+
+        let mut csv_file = match &config.csv_file {
+            Some(path) => {
+                let mut file = File::create(path)
+                    .wrap_err_with(|| eyre::eyre!("Unable to create csv file {:?}", path))?;
+                file.write_all(b"timestamp,rss_bytes,cpu_pct\n")?;
+                Some(file)
+            }
+            None => None,
+        };
+
+        let mut previous: Option<(Instant, ProcessStats)> = None;
+        let mut last_sample_time: Option<Instant> = None;
+
+        loop {
+            // Sleep occasionally because otherwise this loop will run too fast.
+            std::thread::sleep(Duration::from_millis(
+                config.interval.as_millis().min(100) as u64
+            ));
+
+            match ceremony_rx.try_recv() {
+                Ok(message) => {
+                    if let CeremonyMessage::Shutdown(_) = message {
+                        tracing::info!("Thread terminated gracefully");
+                        return Ok(());
+                    }
+                }
+                Err(TryRecvError::Disconnected) => {
+                    panic!("`ceremony_rx` disconnected");
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+
+            let now = Instant::now();
+            if let Some(last_sample_time) = last_sample_time {
+                if now.duration_since(last_sample_time) < config.interval {
+                    continue;
+                }
+            }
+            last_sample_time = Some(now);
+
+            let stats = match read_process_stats(pid) {
+                Ok(Some(stats)) => stats,
+                Ok(None) => continue,
+                Err(error) => {
+                    tracing::debug!(
+                        "Unable to sample process (it has probably exited), thread closing: {}",
+                        error
+                    );
+                    return Ok(());
+                }
+            };
+
+            let cpu_pct = match &previous {
+                Some((previous_time, previous_stats)) => {
+                    cpu_percentage(previous_stats, &stats, now.duration_since(*previous_time))
+                }
+                None => 0,
+            };
+            previous = Some((now, stats));
+
+            if let Some(csv_file) = &mut csv_file {
+                writeln!(
+                    csv_file,
+                    "{},{},{}",
+                    chrono::Utc::now().to_rfc3339(),
+                    stats.rss_bytes,
+                    cpu_pct
+                )?;
+            }
+
+            ceremony_tx.broadcast(CeremonyMessage::ResourceSample {
+                rss_bytes: stats.rss_bytes,
+                cpu_pct,
+            })?;
+        }
+    })
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use mpmc_bus::{Bus, TryRecvError};
+
+    use super::{run_resource_monitor, ResourceMonitorConfig};
+    use crate::{CeremonyMessage, ShutdownReason};
+
+    /// Test that samples are collected for a running fixture process.
+    #[test]
+    fn test_resource_monitor_collects_samples() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("10")
+            .spawn()
+            .unwrap();
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let csv_path = out_dir.path().join("resources.csv");
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let join = run_resource_monitor(
+            child.id(),
+            ResourceMonitorConfig {
+                interval: Duration::from_millis(50),
+                csv_file: Some(csv_path.clone()),
+            },
+            bus.subscribe(),
+            bus.broadcaster(),
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut samples = 0;
+        while samples < 2 && Instant::now() < deadline {
+            match rx.try_recv() {
+                Ok(CeremonyMessage::ResourceSample { rss_bytes, .. }) => {
+                    assert!(rss_bytes > 0);
+                    samples += 1;
+                }
+                Ok(_) => {}
+                Err(TryRecvError::Empty) => std::thread::sleep(Duration::from_millis(10)),
+                Err(TryRecvError::Disconnected) => panic!("`rx` disconnected"),
+            }
+        }
+
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        join.join().unwrap().unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(2, samples);
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(csv.lines().count() >= 3);
+    }
+}
@@ -10,8 +10,10 @@ use serde::Deserialize;
 use crate::{
     config::Config,
     reporting::LogFileWriter,
+    summary::CeremonySummary,
     test::{integration_test, TestOptions, TestRound},
     util::create_dir_if_not_exists,
+    watchdog::WatchdogConfig,
     Environment,
 };
 
@@ -58,6 +60,17 @@ pub struct SingleTestOptions {
 
     /// Configure the tests performed for each round of the ceremony.
     pub rounds: Vec<TestRound>,
+
+    /// (Optional) Whether to verify each round's transcript as soon
+    /// as it finishes. Default: `false`.
+    #[serde(default)]
+    pub incremental_verification: bool,
+
+    /// (Optional) Run the contributors which start with each round
+    /// under a watchdog which restarts them if they make no progress
+    /// within a window. Default: `None`.
+    #[serde(default)]
+    pub contributor_watchdog: Option<WatchdogConfig>,
 }
 
 /// Default value for [TestOptions::replacement_contributors].
@@ -139,6 +152,8 @@ impl Specification {
                         aleo_setup_repo: config.aleo_setup_repo.clone(),
                         aleo_setup_coordinator_repo: config.aleo_setup_coordinator_repo.clone(),
                         rounds: options.rounds.clone(),
+                        incremental_verification: options.incremental_verification,
+                        contributor_watchdog: options.contributor_watchdog.clone(),
                     }
                 } else {
                     TestOptions {
@@ -155,6 +170,8 @@ impl Specification {
                         aleo_setup_repo: config.aleo_setup_repo.clone(),
                         aleo_setup_coordinator_repo: config.aleo_setup_coordinator_repo.clone(),
                         rounds: options.rounds.clone(),
+                        incremental_verification: options.incremental_verification,
+                        contributor_watchdog: options.contributor_watchdog.clone(),
                     }
                 };
 
@@ -166,7 +183,17 @@ impl Specification {
 
                 tracing::info!("Running integration test with id {:?}", id);
 
-                integration_test(&options, log_writer)
+                let result = integration_test(&options, log_writer);
+
+                let summary = match &result {
+                    Ok(test_results) => test_results.summary(),
+                    Err(error) => CeremonySummary::from_error(error),
+                };
+                if let Err(error) = summary.write_json(options.out_dir.join("summary.json")) {
+                    tracing::error!("Unable to write the ceremony summary: {:?}", error);
+                }
+
+                result
                     .map(|test_results| {
                         let test_results_str =
                             ron::ser::to_string_pretty(&test_results, Default::default())
@@ -3,6 +3,7 @@
 use crate::{
     process::{
         default_parse_exit_status, fallible_monitor, run_monitor_process, MonitorProcessJoin,
+        MonitorProcessOptions,
     },
     CeremonyMessage,
 };
@@ -50,11 +51,16 @@ pub fn run_state_monitor(
         ));
     }
 
-    let exec = Exec::cmd(config.state_monitor_bin.canonicalize()?)
-        .arg("--transcript")
-        .arg(config.transcript_dir)
-        .arg("--address")
-        .arg(config.address.to_string());
+    let state_monitor_bin = config.state_monitor_bin.canonicalize()?;
+    let transcript_dir = config.transcript_dir;
+    let address = config.address.to_string();
+    let exec = move || {
+        Ok(Exec::cmd(&state_monitor_bin)
+            .arg("--transcript")
+            .arg(&transcript_dir)
+            .arg("--address")
+            .arg(&address))
+    };
 
     let log_file_path = config.out_dir.join("state_monitor.log");
 
@@ -62,10 +68,11 @@ pub fn run_state_monitor(
         "state_monitor".to_string(),
         exec,
         default_parse_exit_status,
+        MonitorProcessOptions::default(),
         ceremony_tx,
         ceremony_rx,
-        fallible_monitor(move |stdout, ceremony_tx, _monitor_tx| {
-            monitor_state_monitor(stdout, ceremony_tx, &log_file_path)
+        fallible_monitor(move |output, ceremony_tx, _monitor_tx| {
+            monitor_state_monitor(output.stdout, ceremony_tx, &log_file_path)
         }),
     )?;
 
@@ -0,0 +1,89 @@
+//! An async [Stream] adapter over the ceremony message bus, for
+//! test harnesses which run on [tokio]. Requires the `stream` feature.
+
+use std::time::Duration;
+
+use mpmc_bus::{Receiver, TryRecvError};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+
+use crate::CeremonyMessage;
+
+/// How often the bridging thread checks for new messages on the bus.
+const STREAM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Bridge the messages received on `rx` into a [Stream].
+///
+/// The [mpmc_bus] [Receiver] is blocking, so the messages are
+/// forwarded to the stream by a dedicated thread. The stream ends
+/// once every [mpmc_bus::Sender] for the bus has been dropped and
+/// the remaining messages have been yielded. Dropping the stream
+/// closes the thread.
+pub fn ceremony_stream(rx: Receiver<CeremonyMessage>) -> impl Stream<Item = CeremonyMessage> {
+    let (stream_tx, stream_rx) = mpsc::unbounded_channel();
+    let span = tracing::error_span!("ceremony_stream");
+
+    std::thread::spawn(move || {
+        let _guard = span.enter();
+        let mut rx = rx;
+
+        loop {
+            match rx.try_recv() {
+                Ok(message) => {
+                    if stream_tx.send(message).is_err() {
+                        tracing::debug!("Stream dropped.");
+                        break;
+                    }
+                }
+                Err(TryRecvError::Empty) => {
+                    if stream_tx.is_closed() {
+                        tracing::debug!("Stream dropped.");
+                        break;
+                    }
+                    std::thread::sleep(STREAM_POLL_INTERVAL);
+                }
+                Err(TryRecvError::Disconnected) => {
+                    tracing::debug!("`rx` disconnected, ending the stream.");
+                    break;
+                }
+            }
+        }
+
+        tracing::debug!("Thread closing gracefully.");
+    });
+
+    UnboundedReceiverStream::new(stream_rx)
+}
+
+#[cfg(test)]
+mod test {
+    use mpmc_bus::Bus;
+    use tokio_stream::StreamExt;
+
+    use super::ceremony_stream;
+    use crate::{CeremonyMessage, ShutdownReason};
+
+    /// Test that messages broadcast on the bus are yielded by the
+    /// stream, and that the stream ends when the bus is dropped.
+    #[tokio::test]
+    async fn test_ceremony_stream() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut stream = Box::pin(ceremony_stream(bus.subscribe()));
+
+        let messages = vec![
+            CeremonyMessage::RoundStarted(1),
+            CeremonyMessage::RoundFinished(1),
+            CeremonyMessage::Shutdown(ShutdownReason::TestFinished),
+        ];
+        for message in &messages {
+            bus.broadcast(message.clone()).unwrap();
+        }
+
+        for message in &messages {
+            assert_eq!(Some(message), stream.next().await.as_ref());
+        }
+
+        drop(bus);
+        assert_eq!(None, stream.next().await);
+    }
+}
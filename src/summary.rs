@@ -0,0 +1,152 @@
+//! A machine-readable summary of a ceremony run by the integration
+//! test, written as a JSON artifact that CI can parse and trend over
+//! time.
+
+use std::{fs::File, path::Path, time::Duration};
+
+use blake2::{Blake2b, Digest};
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+
+/// Summary of an individual round of the ceremony.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundSummary {
+    /// The round number.
+    pub round: u64,
+    /// The time between the start of the round, and the end of the
+    /// round.
+    #[serde(with = "humantime_serde")]
+    pub total_round_duration: Duration,
+    /// The time taken to perform aggregation at the end of the round.
+    #[serde(with = "humantime_serde")]
+    pub aggregation_duration: Duration,
+    /// Number of successful contributions observed during the round.
+    pub contributions: usize,
+    /// Number of verifications observed during the round.
+    pub verifications: usize,
+}
+
+/// Summary of the whole ceremony, see [CeremonySummary::write_json()].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CeremonySummary {
+    /// The version of the coordinator which ran the ceremony, if it
+    /// could be determined.
+    pub coordinator_version: Option<String>,
+    /// The hash of the coordinator's configuration file (see
+    /// [hash_config_file()]), to identify the configuration the
+    /// ceremony ran with.
+    pub config_hash: Option<String>,
+    /// The rounds which completed, in order.
+    pub rounds: Vec<RoundSummary>,
+    /// The errors which caused the ceremony to fail (if any).
+    pub failures: Vec<String>,
+}
+
+impl CeremonySummary {
+    /// Create a summary for a ceremony which failed with `error`
+    /// before any results were available.
+    pub fn from_error(error: &eyre::Error) -> Self {
+        Self {
+            failures: vec![format!("{:?}", error)],
+            ..Self::default()
+        }
+    }
+
+    /// Total number of contributions across all the rounds.
+    pub fn total_contributions(&self) -> usize {
+        self.rounds.iter().map(|round| round.contributions).sum()
+    }
+
+    /// Total time spent running the rounds.
+    pub fn total_duration(&self) -> Duration {
+        self.rounds
+            .iter()
+            .map(|round| round.total_round_duration)
+            .sum()
+    }
+
+    /// Whether the ceremony completed without any failures.
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Write this summary to a JSON file at `path`.
+    pub fn write_json(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .wrap_err_with(|| eyre::eyre!("Unable to create summary file {:?}", path))?;
+        serde_json::to_writer_pretty(file, self)
+            .wrap_err_with(|| eyre::eyre!("Unable to write summary file {:?}", path))
+    }
+
+    /// Read a summary previously written with
+    /// [CeremonySummary::write_json()].
+    pub fn read_json(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .wrap_err_with(|| eyre::eyre!("Unable to open summary file {:?}", path))?;
+        serde_json::from_reader(file)
+            .wrap_err_with(|| eyre::eyre!("Unable to parse summary file {:?}", path))
+    }
+}
+
+/// Calculate the hex encoded [BLAKE2b](blake2::Blake2b) hash of the
+/// configuration file at `path`.
+pub fn hash_config_file(path: impl AsRef<Path>) -> eyre::Result<String> {
+    let path = path.as_ref();
+    let contents = std::fs::read(path)
+        .wrap_err_with(|| eyre::eyre!("Unable to read config file {:?}", path))?;
+
+    Ok(Blake2b::digest(&contents)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{hash_config_file, CeremonySummary, RoundSummary};
+
+    /// Test that a summary written with
+    /// [CeremonySummary::write_json()] can be read back unchanged.
+    #[test]
+    fn test_summary_json_round_trip() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config_path = out_dir.path().join("config.toml");
+        std::fs::write(&config_path, "setup = \"development\"\n").unwrap();
+
+        let summary = CeremonySummary {
+            coordinator_version: Some("0.2.1".to_string()),
+            config_hash: Some(hash_config_file(&config_path).unwrap()),
+            rounds: vec![
+                RoundSummary {
+                    round: 1,
+                    total_round_duration: Duration::from_secs(90),
+                    aggregation_duration: Duration::from_secs(5),
+                    contributions: 4,
+                    verifications: 4,
+                },
+                RoundSummary {
+                    round: 2,
+                    total_round_duration: Duration::from_secs(60),
+                    aggregation_duration: Duration::from_secs(4),
+                    contributions: 2,
+                    verifications: 2,
+                },
+            ],
+            failures: vec!["Participant was unexpectedly dropped".to_string()],
+        };
+
+        let summary_path = out_dir.path().join("summary.json");
+        summary.write_json(&summary_path).unwrap();
+        let read_summary = CeremonySummary::read_json(&summary_path).unwrap();
+
+        assert_eq!(summary, read_summary);
+        assert_eq!(6, read_summary.total_contributions());
+        assert_eq!(Duration::from_secs(150), read_summary.total_duration());
+        assert!(!read_summary.is_success());
+        assert_eq!(128, read_summary.config_hash.unwrap().len());
+    }
+}
@@ -4,17 +4,24 @@
 use crate::{
     ceremony_waiter::spawn_contribution_waiter,
     contributor::{generate_contributor_key, run_contributor, Contributor, ContributorConfig},
-    coordinator::{check_participants_in_round, run_coordinator, CoordinatorConfig},
+    coordinator::{
+        check_participants_in_round, read_round_state, run_coordinator, wait_for_transcript_dir,
+        CoordinatorConfig, CoordinatorStateHistory,
+    },
     drop_participant::{monitor_drops, DropContributorConfig, MonitorDropsConfig},
     git::{clone_git_repository, LocalGitRepo, RemoteGitRepo},
     join::{join_multiple, JoinLater, JoinMultiple, MultiJoinable},
     reporting::LogFileWriter,
     rust::{build_rust_crate, install_rust_toolchain, RustToolchain},
     state_monitor::{run_state_monitor, StateMonitorConfig},
+    summary::{hash_config_file, CeremonySummary, RoundSummary},
     time_limit::ceremony_time_limit,
+    timeline::{run_round_state_timeline, RoundStateTimeline},
+    transcript::{run_incremental_verification, RoundParticipants},
     util::create_dir_if_not_exists,
     verifier::{generate_verifier_key, run_verifier, Verifier},
     waiter::{MessageWaiter, WaiterJoinCondition},
+    watchdog::{run_contributor_watchdog, WatchdogConfig, WatchdogReport},
     CeremonyMessage, ContributorRef, Environment, ShutdownReason,
 };
 
@@ -160,6 +167,16 @@ pub struct TestOptions {
 
     /// Configuration for each round of the ceremony that will be tested.
     pub rounds: Vec<TestRound>,
+
+    /// Verify each round's transcript as soon as the round finishes,
+    /// aborting the test early if it fails, see
+    /// [run_incremental_verification()].
+    pub incremental_verification: bool,
+
+    /// If `Some`, the contributors which start with the round are run
+    /// under a watchdog which restarts them if they get stuck, see
+    /// [run_contributor_watchdog()].
+    pub contributor_watchdog: Option<WatchdogConfig>,
 }
 
 /// Options for running the `aleo-setup-state-monitor`
@@ -174,6 +191,8 @@ pub struct StateMonitorOptions {
 
 #[derive(Serialize)]
 pub struct RoundResults {
+    /// The round number.
+    pub round: u64,
     /// The time between the start of the round, and the end of the
     /// round.
     #[serde(with = "humantime_serde")]
@@ -181,6 +200,10 @@ pub struct RoundResults {
     /// The time taken to perform aggregation at the end of a round.
     #[serde(with = "humantime_serde")]
     pub aggregation_duration: std::time::Duration,
+    /// Number of successful contributions observed during the round.
+    pub contributions: usize,
+    /// Number of verifications observed during the round.
+    pub verifications: usize,
 }
 
 /// URL used by the contributors and verifiers to connect to the
@@ -214,6 +237,38 @@ pub fn clone_git_repos(options: &TestOptions) -> eyre::Result<()> {
 #[derive(Serialize)]
 pub struct TestResults {
     round_results: Vec<RoundResults>,
+    /// The state of each round as it finished, see
+    /// [run_round_state_timeline()].
+    round_state_timeline: RoundStateTimeline,
+    /// The version of the coordinator which ran the ceremony, if it
+    /// was reported.
+    coordinator_version: Option<String>,
+    /// The hash of the coordinator's `config.toml`, see
+    /// [hash_config_file()].
+    config_hash: Option<String>,
+}
+
+impl TestResults {
+    /// Summarize the results of the test, see
+    /// [CeremonySummary::write_json()].
+    pub fn summary(&self) -> CeremonySummary {
+        CeremonySummary {
+            coordinator_version: self.coordinator_version.clone(),
+            config_hash: self.config_hash.clone(),
+            rounds: self
+                .round_results
+                .iter()
+                .map(|results| RoundSummary {
+                    round: results.round,
+                    total_round_duration: results.total_round_duration,
+                    aggregation_duration: results.aggregation_duration,
+                    contributions: results.contributions,
+                    verifications: results.verifications,
+                })
+                .collect(),
+            failures: Vec::new(),
+        }
+    }
 }
 
 fn state_monitor_bin_path(repo_dir: impl AsRef<Path>) -> PathBuf {
@@ -332,9 +387,13 @@ pub fn integration_test(
             let _span_guard = span.enter();
 
             let view_key_path = keys_dir_path.join(format!("{}.key", id));
-            generate_verifier_key(&view_key_bin_path, &view_key_path)?;
+            let address = generate_verifier_key(&view_key_bin_path, &view_key_path)?;
 
-            Ok(Verifier { id, view_key_path })
+            Ok(Verifier {
+                id,
+                view_key_path,
+                address,
+            })
         })
         .collect::<eyre::Result<Vec<Verifier>>>()?;
 
@@ -449,6 +508,7 @@ pub fn integration_test(
                         out_dir: contributor_out_dir,
                         drop,
                         start,
+                        contribution_delay: None,
                     })
                 })
                 .zip(contributors.iter())
@@ -497,6 +557,7 @@ pub fn integration_test(
                 out_dir: contributor_out_dir,
                 drop: None,
                 start: ContributorStartConfig::CeremonyStart,
+                contribution_delay: None,
             };
 
             Ok((contributor, contributor_config))
@@ -508,13 +569,15 @@ pub fn integration_test(
         .map(|c| c.0.as_contributor_ref())
         .collect();
 
-    let coordinator_config = CoordinatorConfig {
-        crate_dir: coordinator_dir.to_owned(),
-        setup_coordinator_bin: coordinator_bin_path,
-        environment: options.environment,
-        out_dir: create_dir_if_not_exists(options.out_dir.join("coordinator"))?,
-        replacement_contributors: replacement_contributor_refs,
-    };
+    let coordinator_config = CoordinatorConfig::builder()
+        .crate_dir(coordinator_dir)
+        .setup_coordinator_bin(coordinator_bin_path)
+        .environment(options.environment)
+        .out_dir(create_dir_if_not_exists(
+            options.out_dir.join("coordinator"),
+        )?)
+        .replacement_contributors(replacement_contributor_refs)
+        .build()?;
 
     // Create some mpmc channels for communicating between the various
     // components that run during the integration test.
@@ -536,6 +599,48 @@ pub fn integration_test(
         ceremony_rx.clone(),
     );
 
+    let round_state_timeline_join =
+        run_round_state_timeline(coordinator_config.clone(), ceremony_rx.clone());
+
+    let incremental_verification_join = if options.incremental_verification {
+        // The contributors which start with each round and are not
+        // dropped during it are expected to be in its final state.
+        let round_participants: HashMap<u64, RoundParticipants> = round_configs
+            .iter()
+            .map(|round_config| {
+                let contributors = round_config
+                    .contributors
+                    .iter()
+                    .filter(|(contributor, contributor_config)| {
+                        matches!(contributor_config.start, ContributorStartConfig::RoundStart)
+                            && !round_config
+                                .contributor_drops
+                                .contains_key(&contributor.as_contributor_ref())
+                    })
+                    .map(|(contributor, _)| contributor.clone())
+                    .collect();
+                let participants = RoundParticipants {
+                    contributors,
+                    verifiers: round_config.verifiers.clone(),
+                };
+                (round_config.round_number, participants)
+            })
+            .collect();
+
+        Some(run_incremental_verification(
+            coordinator_config.clone(),
+            round_participants,
+            ceremony_tx.clone(),
+            ceremony_rx.clone(),
+        ))
+    } else {
+        None
+    };
+
+    // Subscribed before the coordinator is launched so that
+    // `CoordinatorReady` is not missed.
+    let transcript_dir_rx = ceremony_rx.clone();
+
     // Run the coordinator.
     let coordinator_join = run_coordinator(
         &coordinator_config,
@@ -543,6 +648,7 @@ pub fn integration_test(
         ceremony_rx.clone(),
     )?;
 
+    let coordinator_state_history = coordinator_join.state_history().clone();
     process_joins.push(Box::new(coordinator_join));
 
     if let Some(state_monitor_options) = &options.state_monitor {
@@ -568,6 +674,11 @@ pub fn integration_test(
 
     tracing::info!("Coordinator started.");
 
+    if let Err(error) = wait_for_transcript_dir(&coordinator_config, transcript_dir_rx) {
+        ceremony_tx.broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))?;
+        return Err(error);
+    }
+
     if !replacement_contributors.is_empty() {
         tracing::info!(
             "Starting {} replacement contributors.",
@@ -603,6 +714,7 @@ pub fn integration_test(
             test_round(
                 round_config,
                 &coordinator_config,
+                &coordinator_state_history,
                 options,
                 &ceremony_tx,
                 &ceremony_rx,
@@ -628,7 +740,32 @@ pub fn integration_test(
         }
     }
 
-    Ok(TestResults { round_results })
+    let round_state_timeline = round_state_timeline_join
+        .join()
+        .expect("error while joining round state timeline thread")?;
+
+    if let Some(incremental_verification_join) = incremental_verification_join {
+        incremental_verification_join
+            .join()
+            .expect("error while joining incremental verification thread")?;
+    }
+
+    let config_hash = match hash_config_file(coordinator_config.out_dir.join("config.toml")) {
+        Ok(hash) => Some(hash),
+        Err(error) => {
+            tracing::warn!("Unable to hash coordinator config: {:?}", error);
+            None
+        }
+    };
+
+    Ok(TestResults {
+        round_results,
+        round_state_timeline,
+        coordinator_version: coordinator_state_history
+            .coordinator_version()
+            .map(|version| version.to_string()),
+        config_hash,
+    })
 }
 
 /// Configuration for running a round of the ceremony.
@@ -653,6 +790,7 @@ pub struct RoundConfig {
 fn test_round(
     round_config: RoundConfig,
     coordinator_config: &CoordinatorConfig,
+    coordinator_state_history: &CoordinatorStateHistory,
     options: &TestOptions,
     ceremony_tx: &Sender<CeremonyMessage>,
     ceremony_rx: &Receiver<CeremonyMessage>,
@@ -661,6 +799,7 @@ fn test_round(
     let _span_guard = span.enter();
 
     let mut process_joins: Vec<Box<dyn MultiJoinable>> = Vec::new();
+    let mut watchdog_joins: Vec<std::thread::JoinHandle<eyre::Result<WatchdogReport>>> = Vec::new();
 
     // Monitor the ceremony for dropped participants
     let drops_config = MonitorDropsConfig {
@@ -702,12 +841,25 @@ fn test_round(
             matches!(contributor_config.start, ContributorStartConfig::RoundStart)
         })
         .map(|(contributor, contributor_config)| {
-            let contributor_join = run_contributor(
-                contributor_config.clone(),
-                ceremony_tx.clone(),
-                ceremony_rx.clone(),
-            )?;
-            process_joins.push(Box::new(contributor_join));
+            match &options.contributor_watchdog {
+                Some(watchdog_config) => {
+                    let watchdog_join = run_contributor_watchdog(
+                        watchdog_config.clone(),
+                        contributor_config.clone(),
+                        ceremony_tx.clone(),
+                        ceremony_rx.clone(),
+                    )?;
+                    watchdog_joins.push(watchdog_join);
+                }
+                None => {
+                    let contributor_join = run_contributor(
+                        contributor_config.clone(),
+                        ceremony_tx.clone(),
+                        ceremony_rx.clone(),
+                    )?;
+                    process_joins.push(Box::new(contributor_join));
+                }
+            }
             Ok(contributor.clone())
         })
         .collect::<eyre::Result<Vec<Contributor>>>()?;
@@ -771,6 +923,17 @@ fn test_round(
                 ceremony_tx.broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))?;
                 round_errors.push(error);
             }
+
+            match read_round_state(coordinator_config, round_config.round_number) {
+                Ok(state) => coordinator_state_history.set_expected_contributions(
+                    round_config.round_number,
+                    state.chunks.len() * state.contributor_ids.len(),
+                ),
+                Err(error) => tracing::warn!(
+                    "Unable to read round state to calculate round progress: {:?}",
+                    error
+                ),
+            }
         }
     }
 
@@ -822,6 +985,23 @@ fn test_round(
     join_multiple(mid_round_contributor_joins)
         .expect("Error while joining mid round contributor join threads");
 
+    for watchdog_join in watchdog_joins {
+        match watchdog_join
+            .join()
+            .expect("Error while joining contributor watchdog thread")
+        {
+            Ok(report) => {
+                if report.restarts > 0 {
+                    tracing::info!(
+                        "Round recovered after {} contributor restarts.",
+                        report.restarts
+                    );
+                }
+            }
+            Err(error) => round_errors.push(error),
+        }
+    }
+
     tracing::debug!("Waiting for monitor_drops thread to join.");
     monitor_drops_join
         .join()
@@ -843,11 +1023,21 @@ fn test_round(
             .expect("expected at least one error to be present"));
     }
 
+    let progress = coordinator_state_history.progress();
+    let (contributions, verifications) = if progress.round == round_config.round_number {
+        (progress.contributions, progress.verifications)
+    } else {
+        (0, 0)
+    };
+
     let results = RoundResults {
+        round: round_config.round_number,
         total_round_duration: total_round_duration
             .unwrap_or_else(|| std::time::Duration::from_secs(0)),
         aggregation_duration: aggregation_duration
             .unwrap_or_else(|| std::time::Duration::from_secs(0)),
+        contributions,
+        verifications,
     };
 
     std::fs::write(
@@ -10,7 +10,10 @@ use crate::{CeremonyMessage, ShutdownReason};
 
 /// Run a time limit thread for the specified duration. If the
 /// ceremony exceeds the timer, then this will send a shutdown
-/// message.
+/// message. Time spent while the coordinator is paused for
+/// maintenance (between a [CeremonyMessage::CoordinatorPaused] and
+/// [CeremonyMessage::CoordinatorResumed]) does not count towards the
+/// time limit.
 pub fn ceremony_time_limit(
     duration: std::time::Duration,
     mut ceremony_rx: Receiver<CeremonyMessage>,
@@ -22,12 +25,46 @@ pub fn ceremony_time_limit(
     std::thread::spawn(move || {
         let _guard = span.enter();
         let start_time = Instant::now();
+        // Total time spent paused before the current pause (if any).
+        let mut paused_duration = Duration::from_secs(0);
+        // The time at which the current pause started.
+        let mut paused_since: Option<Instant> = None;
 
         loop {
             // Sleep occasionally because otherwise this loop will run too fast.
             std::thread::sleep(Duration::from_millis(100));
 
-            if start_time.elapsed() > duration {
+            loop {
+                match ceremony_rx.try_recv() {
+                    Ok(CeremonyMessage::Shutdown(_)) => {
+                        tracing::info!("Thread terminated gracefully");
+                        return Ok(());
+                    }
+                    Ok(CeremonyMessage::CoordinatorPaused) => {
+                        if paused_since.is_none() {
+                            tracing::info!("Coordinator paused, suspending the time limit.");
+                            paused_since = Some(Instant::now());
+                        }
+                    }
+                    Ok(CeremonyMessage::CoordinatorResumed) => {
+                        if let Some(since) = paused_since.take() {
+                            tracing::info!("Coordinator resumed, resuming the time limit.");
+                            paused_duration += since.elapsed();
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(TryRecvError::Disconnected) => {
+                        panic!("`ceremony_rx` disconnected");
+                    }
+                    Err(TryRecvError::Empty) => break,
+                }
+            }
+
+            if paused_since.is_some() {
+                continue;
+            }
+
+            if start_time.elapsed() - paused_duration > duration {
                 tracing::error!("Time limit exceeded, telling ceremony to shutdown.");
                 ceremony_tx.broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))?;
                 return Err(eyre::eyre!(
@@ -35,19 +72,48 @@ pub fn ceremony_time_limit(
                     &duration_formatted
                 ));
             }
-
-            match ceremony_rx.try_recv() {
-                Ok(message) => {
-                    if let CeremonyMessage::Shutdown(_) = message {
-                        tracing::info!("Thread terminated gracefully");
-                        return Ok(());
-                    }
-                }
-                Err(TryRecvError::Disconnected) => {
-                    panic!("`ceremony_rx` disconnected");
-                }
-                Err(TryRecvError::Empty) => {}
-            }
         }
     })
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use mpmc_bus::Bus;
+
+    use super::ceremony_time_limit;
+    use crate::{CeremonyMessage, ShutdownReason};
+
+    /// Test that the time limit is suspended while the coordinator is
+    /// paused for maintenance, and resumes afterwards.
+    #[test]
+    fn test_time_limit_suspended_while_paused() {
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let join = ceremony_time_limit(
+            Duration::from_millis(500),
+            bus.subscribe(),
+            bus.broadcaster(),
+        );
+
+        bus.broadcast(CeremonyMessage::CoordinatorPaused).unwrap();
+        std::thread::sleep(Duration::from_millis(800));
+        bus.broadcast(CeremonyMessage::CoordinatorResumed).unwrap();
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+        join.join().unwrap().unwrap();
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let join = ceremony_time_limit(
+            Duration::from_millis(500),
+            bus.subscribe(),
+            bus.broadcaster(),
+        );
+
+        bus.broadcast(CeremonyMessage::CoordinatorPaused).unwrap();
+        std::thread::sleep(Duration::from_millis(300));
+        bus.broadcast(CeremonyMessage::CoordinatorResumed).unwrap();
+        let error = join.join().unwrap().unwrap_err();
+        assert!(error.to_string().contains("Time limit"));
+    }
+}
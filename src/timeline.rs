@@ -0,0 +1,113 @@
+//! Records the history of the coordinator's round state over the
+//! course of the ceremony, for visualizing how the membership of the
+//! ceremony evolved from round to round.
+
+use std::thread::JoinHandle;
+
+use mpmc_bus::Receiver;
+
+use crate::{
+    coordinator::{read_round_state, CoordinatorConfig, RoundState},
+    CeremonyMessage,
+};
+
+/// The [RoundState] of each round which finished during the ceremony,
+/// in the order that they finished. Each entry is the round number
+/// and the state of that round.
+pub type RoundStateTimeline = Vec<(u64, RoundState)>;
+
+/// Spawn a thread which reads the [RoundState] of each round when a
+/// [CeremonyMessage::RoundFinished] is received, accumulating them
+/// into a [RoundStateTimeline]. The timeline is returned when the
+/// thread is joined, after a [CeremonyMessage::Shutdown] has been
+/// received.
+pub fn run_round_state_timeline(
+    config: CoordinatorConfig,
+    mut ceremony_rx: Receiver<CeremonyMessage>,
+) -> JoinHandle<eyre::Result<RoundStateTimeline>> {
+    let span = tracing::error_span!("round_state_timeline");
+
+    std::thread::spawn(move || {
+        let _guard = span.enter();
+        let mut timeline = RoundStateTimeline::new();
+
+        loop {
+            match ceremony_rx.recv()? {
+                CeremonyMessage::RoundFinished(round) => match read_round_state(&config, round) {
+                    Ok(state) => timeline.push((round, state)),
+                    Err(error) => {
+                        tracing::error!("Unable to read state for round {}: {:?}", round, error)
+                    }
+                },
+                CeremonyMessage::Shutdown(_) => break,
+                _ => {}
+            }
+        }
+
+        tracing::debug!("Thread closing gracefully.");
+        Ok(timeline)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use mpmc_bus::Bus;
+
+    use super::run_round_state_timeline;
+    use crate::{
+        coordinator::{CoordinatorConfig, CoordinatorConfigProfile},
+        CeremonyMessage, ShutdownReason,
+    };
+
+    /// Test that the timeline captures the membership of each round
+    /// in a multi-round fixture transcript.
+    #[test]
+    fn test_round_state_timeline() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = CoordinatorConfig::from_profile(
+            CoordinatorConfigProfile::Smoke,
+            out_dir.path().to_owned(),
+            out_dir.path().join("aleo-setup-coordinator"),
+            out_dir.path().to_owned(),
+        );
+
+        let memberships = [
+            (1, vec!["contributor1", "contributor2"], vec!["verifier1"]),
+            (2, vec!["contributor2"], vec!["verifier1", "verifier2"]),
+        ];
+
+        for (round, contributors, verifiers) in &memberships {
+            let round_dir = config.transcript_dir().join(format!("round_{}", round));
+            std::fs::create_dir_all(&round_dir).unwrap();
+            std::fs::write(
+                round_dir.join("state.json"),
+                serde_json::json!({
+                    "height": round,
+                    "contributorIds": contributors,
+                    "verifierIds": verifiers,
+                })
+                .to_string(),
+            )
+            .unwrap();
+        }
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let join = run_round_state_timeline(config, bus.subscribe());
+
+        bus.broadcast(CeremonyMessage::RoundFinished(1)).unwrap();
+        bus.broadcast(CeremonyMessage::RoundFinished(2)).unwrap();
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+
+        let timeline = join.join().unwrap().unwrap();
+        assert_eq!(2, timeline.len());
+
+        for ((round, state), (expected_round, contributors, verifiers)) in
+            timeline.iter().zip(memberships.iter())
+        {
+            assert_eq!(expected_round, round);
+            assert_eq!(contributors, &state.contributor_ids);
+            assert_eq!(verifiers, &state.verifier_ids);
+        }
+    }
+}
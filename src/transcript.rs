@@ -0,0 +1,861 @@
+//! Functions for validating the ceremony transcript produced by the
+//! coordinator once the ceremony has completed, or incrementally as
+//! each round finishes (see [run_incremental_verification()]).
+//!
+//! The transcript (located at [CoordinatorConfig::transcript_dir()])
+//! is expected to have the following structure:
+//!
+//! ```txt
+//! transcript_dir/
+//! ├── round_1/
+//! │   ├── state.json
+//! │   ├── round_1.verified
+//! │   ├── chunk_0/
+//! │   │   ├── contribution_0.verified
+//! │   │   ├── contribution_1.unverified
+//! │   │   └── contribution_1.verified
+//! │   └── chunk_1/
+//! │       └── ...
+//! ├── round_2/
+//! │   └── ...
+//! └── round_N/
+//!     └── ...
+//! ```
+//!
+//! + `state.json` is the round state, its `height` (if present) must
+//!   match the round number.
+//! + `round_N.verified` is the aggregation of all the verified
+//!   contributions in round `N`, which must be present and non-empty.
+//!   For the final round this is the final output of the ceremony.
+//! + `contribution_0.verified` in each chunk is the initial challenge
+//!   for the round. For rounds after the first this is derived from
+//!   the previous round's aggregation, and begins with the
+//!   [BLAKE2b](blake2::Blake2b) hash of `round_{N-1}.verified`, which
+//!   is what links the rounds together.
+//! + Each contribution file (other than `contribution_0.verified`)
+//!   begins with the [BLAKE2b](blake2::Blake2b) hash of the file it
+//!   was computed from: `contribution_N.unverified` starts with the
+//!   hash of `contribution_{N-1}.verified`, and
+//!   `contribution_N.verified` starts with the hash of
+//!   `contribution_N.unverified`. See [verify_transcript_hashes()].
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    thread::JoinHandle,
+};
+
+use blake2::{Blake2b, Digest};
+use eyre::Context;
+use mpmc_bus::{Receiver, Sender};
+
+use crate::{
+    contributor::Contributor,
+    coordinator::{check_participants_in_round, read_round_state, CoordinatorConfig},
+    verifier::Verifier,
+    CeremonyMessage, ContributorRef, ShutdownReason,
+};
+
+/// Check that the specified file exists and is not empty.
+fn check_non_empty_file(path: &Path) -> eyre::Result<()> {
+    let metadata = std::fs::metadata(path)
+        .wrap_err_with(|| eyre::eyre!("Expected transcript file {:?} is missing", path))?;
+
+    if !metadata.is_file() {
+        return Err(eyre::eyre!("Expected {:?} to be a file", path));
+    }
+
+    if metadata.len() == 0 {
+        return Err(eyre::eyre!("Transcript file {:?} is empty", path));
+    }
+
+    Ok(())
+}
+
+/// Check the transcript for an individual round, see the [module
+/// documentation](self) for the expected structure.
+fn verify_round_transcript(config: &CoordinatorConfig, round: u64) -> eyre::Result<()> {
+    let round_dir = config.round_dir(round);
+
+    if !round_dir.is_dir() {
+        return Err(eyre::eyre!(
+            "Transcript directory for round {} is missing: {:?}",
+            round,
+            round_dir
+        ));
+    }
+
+    let state = read_round_state(config, round)?;
+    if let Some(height) = state.height {
+        if height != round {
+            return Err(eyre::eyre!(
+                "Round state file for round {} has an unexpected height: {}",
+                round,
+                height
+            ));
+        }
+    }
+
+    check_non_empty_file(&round_dir.join(format!("round_{}.verified", round)))
+        .wrap_err_with(|| eyre::eyre!("Round {} has not been aggregated", round))?;
+
+    // The aggregation of the previous round, which the initial
+    // challenge of each chunk in this round is derived from.
+    let previous_aggregation = if round > 1 {
+        Some(
+            config
+                .round_dir(round - 1)
+                .join(format!("round_{}.verified", round - 1)),
+        )
+    } else {
+        None
+    };
+
+    let mut n_chunks = 0;
+    for entry in std::fs::read_dir(&round_dir)
+        .wrap_err_with(|| eyre::eyre!("Unable to read directory {:?}", round_dir))?
+    {
+        let entry = entry?;
+        let is_chunk_dir = entry.file_type()?.is_dir()
+            && entry
+                .file_name()
+                .to_str()
+                .map(|name| name.starts_with("chunk_"))
+                .unwrap_or(false);
+
+        if !is_chunk_dir {
+            continue;
+        }
+
+        let initial_challenge = entry.path().join("contribution_0.verified");
+        check_non_empty_file(&initial_challenge).wrap_err_with(|| {
+            eyre::eyre!(
+                "Round {} chunk {:?} is missing its initial challenge",
+                round,
+                entry.file_name()
+            )
+        })?;
+        if let Some(previous_aggregation) = &previous_aggregation {
+            check_contribution_hash(previous_aggregation, &initial_challenge).wrap_err_with(
+                || {
+                    eyre::eyre!(
+                        "Round {} chunk {:?} is not linked to round {}",
+                        round,
+                        entry.file_name(),
+                        round - 1
+                    )
+                },
+            )?;
+        }
+        n_chunks += 1;
+    }
+
+    if n_chunks == 0 {
+        return Err(eyre::eyre!(
+            "Transcript for round {} does not contain any chunks",
+            round
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verify that the complete ceremony transcript, up to and including
+/// `last_round`, is well-formed: every round has been aggregated, and
+/// every round's chunks contain an initial challenge which begins with
+/// the hash of the previous round's aggregation. See the [module documentation](self) for
+/// the structure that is validated.
+pub fn verify_final_transcript(config: &CoordinatorConfig, last_round: u64) -> eyre::Result<()> {
+    if last_round == 0 {
+        return Err(eyre::eyre!("No rounds have been completed"));
+    }
+
+    for round in 1..=last_round {
+        verify_round_transcript(config, round)
+            .wrap_err_with(|| eyre::eyre!("Invalid transcript for round {}", round))?;
+    }
+
+    Ok(())
+}
+
+/// Verify the transcript of a `round` which has just finished: the
+/// round's transcript must be well-formed (see the [module
+/// documentation](self)), contain the expected number of aggregation
+/// output files (see [check_aggregation_file_count()]), and the round
+/// state must include all the `contributors` and `verifiers` (see
+/// [check_participants_in_round()]).
+pub fn verify_finished_round(
+    config: &CoordinatorConfig,
+    round: u64,
+    contributors: &[Contributor],
+    verifiers: &[Verifier],
+) -> eyre::Result<()> {
+    verify_round_transcript(config, round)?;
+    check_aggregation_file_count(config, round, config.environment.aggregation_file_count())?;
+    check_participants_in_round(config, round, contributors, verifiers)?;
+    Ok(())
+}
+
+/// The participants which are expected to be in the state of a round
+/// once it has finished, see [run_incremental_verification()].
+#[derive(Debug, Clone, Default)]
+pub struct RoundParticipants {
+    pub contributors: Vec<Contributor>,
+    pub verifiers: Vec<Verifier>,
+}
+
+/// Spawn a thread which verifies the transcript of each round (see
+/// [verify_finished_round()]) as soon as a
+/// [CeremonyMessage::RoundFinished] is received, rather than waiting
+/// until the end of the ceremony, to catch corruption early in long
+/// ceremonies. The `participants` for each round (keyed by the round
+/// number) are expected to be present in its state, rounds without an
+/// entry are verified without checking their participants.
+///
+/// A [CeremonyMessage::RoundVerified] is broadcast for each round
+/// which passes verification. If a round fails verification, a
+/// [CeremonyMessage::Shutdown] is broadcast to abort the test and the
+/// error is returned when the thread is joined. Otherwise the thread
+/// exits when a [CeremonyMessage::Shutdown] is received.
+pub fn run_incremental_verification(
+    config: CoordinatorConfig,
+    participants: HashMap<u64, RoundParticipants>,
+    ceremony_tx: Sender<CeremonyMessage>,
+    mut ceremony_rx: Receiver<CeremonyMessage>,
+) -> JoinHandle<eyre::Result<()>> {
+    let span = tracing::error_span!("incremental_verification");
+
+    std::thread::spawn(move || {
+        let _guard = span.enter();
+
+        loop {
+            match ceremony_rx.recv()? {
+                CeremonyMessage::RoundFinished(round) => {
+                    let round_participants = participants.get(&round).cloned().unwrap_or_default();
+                    match verify_finished_round(
+                        &config,
+                        round,
+                        &round_participants.contributors,
+                        &round_participants.verifiers,
+                    ) {
+                        Ok(()) => {
+                            tracing::info!("Round {} transcript verified.", round);
+                            ceremony_tx.broadcast(CeremonyMessage::RoundVerified { round })?;
+                        }
+                        Err(error) => {
+                            tracing::error!(
+                                "Round {} transcript failed verification: {:?}",
+                                round,
+                                error
+                            );
+                            ceremony_tx
+                                .broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))?;
+                            return Err(
+                                error.wrap_err(format!("Invalid transcript for round {}", round))
+                            );
+                        }
+                    }
+                }
+                CeremonyMessage::Shutdown(_) => break,
+                _ => {}
+            }
+        }
+
+        tracing::debug!("Thread closing gracefully.");
+        Ok(())
+    })
+}
+
+/// Check that the specified `round`'s transcript directory contains
+/// the `expected` number of aggregation output files (all the files
+/// at the top level of the directory other than `state.json`), to
+/// detect a partial aggregation. See
+/// [Environment::aggregation_file_count()](crate::Environment::aggregation_file_count())
+/// for the number expected in each environment.
+pub fn check_aggregation_file_count(
+    config: &CoordinatorConfig,
+    round: u64,
+    expected: usize,
+) -> eyre::Result<()> {
+    let round_dir = config.round_dir(round);
+
+    let mut output_files = Vec::new();
+    for entry in std::fs::read_dir(&round_dir)
+        .wrap_err_with(|| eyre::eyre!("Unable to read directory {:?}", round_dir))?
+    {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && entry.file_name() != "state.json" {
+            output_files.push(entry.file_name());
+        }
+    }
+
+    if output_files.len() != expected {
+        return Err(eyre::eyre!(
+            "Expected {} aggregation output files for round {}, found {}: {:?}",
+            expected,
+            round,
+            output_files.len(),
+            output_files
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that the round state for `round` does not contain any
+/// unverified (partial) contributions made by the `dropped`
+/// contributors, which should have been discarded by the coordinator
+/// when they were dropped.
+pub fn check_no_partial_contributions(
+    config: &CoordinatorConfig,
+    round: u64,
+    dropped: &[ContributorRef],
+) -> eyre::Result<()> {
+    let state = read_round_state(config, round)?;
+    let dropped_ids: Vec<String> = dropped
+        .iter()
+        .map(|contributor| format!("{}.contributor", contributor.address))
+        .collect();
+
+    let mut partial_contributions: Vec<String> = Vec::new();
+    for chunk in &state.chunks {
+        for (contribution_id, contribution) in &chunk.contributions {
+            let is_dropped = contribution
+                .contributor_id
+                .as_ref()
+                .map(|id| dropped_ids.contains(id))
+                .unwrap_or(false);
+
+            if is_dropped && !contribution.verified {
+                partial_contributions.push(format!(
+                    "chunk {} contribution {} by {}",
+                    chunk.chunk_id,
+                    contribution_id,
+                    contribution.contributor_id.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+    }
+
+    if !partial_contributions.is_empty() {
+        return Err(eyre::eyre!(
+            "Round {} contains partial contributions from dropped contributors: {:?}",
+            round,
+            partial_contributions
+        ));
+    }
+
+    Ok(())
+}
+
+/// The length in bytes of the hash at the start of each contribution
+/// file.
+const CONTRIBUTION_HASH_LENGTH: usize = 64;
+
+/// Check that `file` begins with the hash of `previous_file`.
+fn check_contribution_hash(previous_file: &Path, file: &Path) -> eyre::Result<()> {
+    let previous = std::fs::read(previous_file)
+        .wrap_err_with(|| eyre::eyre!("Unable to read {:?}", previous_file))?;
+    let contents =
+        std::fs::read(file).wrap_err_with(|| eyre::eyre!("Unable to read {:?}", file))?;
+
+    if contents.len() < CONTRIBUTION_HASH_LENGTH {
+        return Err(eyre::eyre!(
+            "Contribution file {:?} is too short to contain a hash ({} bytes)",
+            file,
+            contents.len()
+        ));
+    }
+
+    let expected = Blake2b::digest(&previous);
+    if contents[..CONTRIBUTION_HASH_LENGTH] != expected[..] {
+        return Err(eyre::eyre!(
+            "Contribution file {:?} does not begin with the hash of {:?}",
+            file,
+            previous_file
+        ));
+    }
+
+    Ok(())
+}
+
+/// Verify the hash chain linking the contributions in each chunk of
+/// the specified `round`, see the [module documentation](self). This
+/// detects contribution files which have been modified or truncated
+/// after the contributions which followed them were computed.
+pub fn verify_transcript_hashes(config: &CoordinatorConfig, round: u64) -> eyre::Result<()> {
+    let round_dir = config.round_dir(round);
+
+    let mut chunk_dirs = Vec::new();
+    for entry in std::fs::read_dir(&round_dir)
+        .wrap_err_with(|| eyre::eyre!("Unable to read directory {:?}", round_dir))?
+    {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() && entry.file_name().to_string_lossy().starts_with("chunk_")
+        {
+            chunk_dirs.push(entry.path());
+        }
+    }
+    chunk_dirs.sort();
+
+    for chunk_dir in chunk_dirs {
+        let mut previous = chunk_dir.join("contribution_0.verified");
+        for contribution in 1.. {
+            let unverified = chunk_dir.join(format!("contribution_{}.unverified", contribution));
+            if !unverified.exists() {
+                break;
+            }
+            check_contribution_hash(&previous, &unverified)?;
+
+            let verified = chunk_dir.join(format!("contribution_{}.verified", contribution));
+            if !verified.exists() {
+                break;
+            }
+            check_contribution_hash(&unverified, &verified)?;
+            previous = verified;
+        }
+    }
+
+    Ok(())
+}
+
+/// The kinds of corruption which can be applied with
+/// [corrupt_transcript()].
+#[cfg(any(test, feature = "test-support"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorruptionKind {
+    /// Truncate the file to half of its length.
+    Truncate,
+    /// Invert the bits of the byte in the middle of the file.
+    FlipByte,
+    /// Replace the contents of the file with zeros, keeping its
+    /// length.
+    ZeroOut,
+}
+
+/// Corrupt the initial challenge (`contribution_0.verified`) for the
+/// specified chunk of a round, so that negative tests can confirm
+/// that the verification functions (e.g.
+/// [verify_transcript_hashes()]) catch the corruption. Requires the
+/// `test-support` feature.
+#[cfg(any(test, feature = "test-support"))]
+pub fn corrupt_transcript(
+    config: &CoordinatorConfig,
+    round: u64,
+    chunk: u64,
+    kind: CorruptionKind,
+) -> eyre::Result<()> {
+    let path = config
+        .round_dir(round)
+        .join(format!("chunk_{}", chunk))
+        .join("contribution_0.verified");
+
+    let mut contents =
+        std::fs::read(&path).wrap_err_with(|| eyre::eyre!("Unable to read {:?}", path))?;
+
+    if contents.is_empty() {
+        return Err(eyre::eyre!("Unable to corrupt empty file {:?}", path));
+    }
+
+    match kind {
+        CorruptionKind::Truncate => contents.truncate(contents.len() / 2),
+        CorruptionKind::FlipByte => {
+            let middle = contents.len() / 2;
+            contents[middle] = !contents[middle];
+        }
+        CorruptionKind::ZeroOut => contents.iter_mut().for_each(|byte| *byte = 0),
+    }
+
+    std::fs::write(&path, contents).wrap_err_with(|| eyre::eyre!("Unable to write {:?}", path))
+}
+
+/// The path to the latest verified contribution
+/// (`contribution_N.verified` with the highest `N`) for the specified
+/// chunk of a round.
+fn latest_verified_contribution(
+    config: &CoordinatorConfig,
+    round: u64,
+    chunk: u64,
+) -> eyre::Result<PathBuf> {
+    let chunk_dir = config.round_dir(round).join(format!("chunk_{}", chunk));
+
+    let mut latest: Option<(u64, PathBuf)> = None;
+    for entry in std::fs::read_dir(&chunk_dir)
+        .wrap_err_with(|| eyre::eyre!("Unable to read chunk directory {:?}", chunk_dir))?
+    {
+        let entry = entry?;
+        let contribution = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix("contribution_"))
+            .and_then(|name| name.strip_suffix(".verified"))
+            .and_then(|number| number.parse::<u64>().ok());
+
+        if let Some(contribution) = contribution {
+            if latest
+                .as_ref()
+                .map(|(n, _)| contribution > *n)
+                .unwrap_or(true)
+            {
+                latest = Some((contribution, entry.path()));
+            }
+        }
+    }
+
+    latest.map(|(_, path)| path).ok_or_else(|| {
+        eyre::eyre!(
+            "No verified contributions found in chunk directory {:?}",
+            chunk_dir
+        )
+    })
+}
+
+/// Check that the bytes at `offset` in the latest verified
+/// contribution file for the specified `chunk` of the `round` match
+/// `expected`. Only the requested range is read from the file, which
+/// makes this suitable for checking headers or magic bytes in large
+/// transcript files.
+pub fn assert_transcript_bytes(
+    config: &CoordinatorConfig,
+    round: u64,
+    chunk: u64,
+    offset: u64,
+    expected: &[u8],
+) -> eyre::Result<()> {
+    let path = latest_verified_contribution(config, round, chunk)?;
+    let mut file = File::open(&path)
+        .wrap_err_with(|| eyre::eyre!("Unable to open transcript file {:?}", path))?;
+
+    let file_len = file.metadata()?.len();
+    let end = offset
+        .checked_add(expected.len() as u64)
+        .ok_or_else(|| eyre::eyre!("Byte range at offset {} overflows", offset))?;
+
+    if end > file_len {
+        return Err(eyre::eyre!(
+            "Byte range {}..{} is out of range for transcript file {:?} which is {} bytes long",
+            offset,
+            end,
+            path,
+            file_len
+        ));
+    }
+
+    let mut actual = vec![0u8; expected.len()];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut actual)
+        .wrap_err_with(|| eyre::eyre!("Unable to read transcript file {:?}", path))?;
+
+    if actual != expected {
+        return Err(eyre::eyre!(
+            "Bytes {}..{} of transcript file {:?} do not match, expected: {:02x?}, actual: {:02x?}",
+            offset,
+            end,
+            path,
+            expected,
+            actual
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, path::Path};
+
+    use blake2::{Blake2b, Digest};
+    use mpmc_bus::Bus;
+
+    use super::{
+        assert_transcript_bytes, check_aggregation_file_count, check_no_partial_contributions,
+        corrupt_transcript, run_incremental_verification, verify_final_transcript,
+        verify_transcript_hashes, CorruptionKind,
+    };
+    use crate::{
+        coordinator::{CoordinatorConfig, CoordinatorConfigProfile},
+        CeremonyMessage, ContributorRef, ShutdownReason,
+    };
+
+    /// Create a fixture ceremony transcript with the specified number
+    /// of `rounds`, each with two chunks. The initial challenge of
+    /// each round after the first is linked to the previous round's
+    /// aggregation by its hash.
+    fn create_fixture_ceremony(config: &CoordinatorConfig, rounds: u64) {
+        for round in 1..=rounds {
+            let round_dir = config.round_dir(round);
+            let mut challenge = if round > 1 {
+                Blake2b::digest(b"aggregated").to_vec()
+            } else {
+                Vec::new()
+            };
+            challenge.extend_from_slice(b"challenge");
+            for chunk in 0..2 {
+                let chunk_dir = round_dir.join(format!("chunk_{}", chunk));
+                std::fs::create_dir_all(&chunk_dir).unwrap();
+                std::fs::write(chunk_dir.join("contribution_0.verified"), &challenge).unwrap();
+                std::fs::write(chunk_dir.join("contribution_1.verified"), b"response").unwrap();
+            }
+            let state_file = config.round_state_file(round);
+            std::fs::create_dir_all(state_file.parent().unwrap()).unwrap();
+            std::fs::write(
+                state_file,
+                format!(
+                    r#"{{"height": {}, "contributorIds": [], "verifierIds": []}}"#,
+                    round
+                ),
+            )
+            .unwrap();
+            std::fs::write(
+                round_dir.join(format!("round_{}.verified", round)),
+                b"aggregated",
+            )
+            .unwrap();
+        }
+    }
+
+    fn fixture_config(out_dir: &Path) -> CoordinatorConfig {
+        CoordinatorConfig::from_profile(
+            CoordinatorConfigProfile::Smoke,
+            out_dir.to_owned(),
+            out_dir.join("aleo-setup-coordinator"),
+            out_dir.to_owned(),
+        )
+    }
+
+    /// Test that incremental verification verifies the first round,
+    /// then aborts the test as soon as the second round fails
+    /// verification.
+    #[test]
+    fn test_incremental_verification_aborts() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = fixture_config(out_dir.path());
+        create_fixture_ceremony(&config, 2);
+        std::fs::remove_file(config.transcript_dir().join("round_2/round_2.verified")).unwrap();
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let mut rx = bus.subscribe();
+        let join = run_incremental_verification(
+            config,
+            HashMap::new(),
+            bus.broadcaster(),
+            bus.subscribe(),
+        );
+
+        bus.broadcast(CeremonyMessage::RoundFinished(1)).unwrap();
+        assert_eq!(CeremonyMessage::RoundFinished(1), rx.recv().unwrap());
+        assert_eq!(
+            CeremonyMessage::RoundVerified { round: 1 },
+            rx.recv().unwrap()
+        );
+
+        bus.broadcast(CeremonyMessage::RoundFinished(2)).unwrap();
+        assert_eq!(CeremonyMessage::RoundFinished(2), rx.recv().unwrap());
+        assert_eq!(
+            CeremonyMessage::Shutdown(ShutdownReason::Error),
+            rx.recv().unwrap()
+        );
+
+        let error = join.join().unwrap().unwrap_err();
+        assert!(format!("{:?}", error).contains("round 2"));
+    }
+
+    /// Test that a complete fixture ceremony passes verification.
+    #[test]
+    fn test_verify_final_transcript() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = fixture_config(out_dir.path());
+        create_fixture_ceremony(&config, 3);
+
+        verify_final_transcript(&config, 3).unwrap();
+    }
+
+    /// Test that a fixture ceremony with each round in its own out
+    /// directory passes verification when
+    /// [CoordinatorConfig::per_round_out_dir] is enabled.
+    #[test]
+    fn test_verify_final_transcript_per_round_out_dir() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let mut config = fixture_config(out_dir.path());
+        config.per_round_out_dir = true;
+        create_fixture_ceremony(&config, 2);
+
+        assert!(out_dir
+            .path()
+            .join("round_2/transcript/development/round_2/round_2.verified")
+            .is_file());
+        verify_final_transcript(&config, 2).unwrap();
+
+        config.per_round_out_dir = false;
+        assert!(verify_final_transcript(&config, 2).is_err());
+    }
+
+    /// Test that verification fails when a round has not been
+    /// aggregated, or when rounds are missing.
+    #[test]
+    fn test_verify_final_transcript_incomplete() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = fixture_config(out_dir.path());
+        create_fixture_ceremony(&config, 3);
+
+        assert!(verify_final_transcript(&config, 4).is_err());
+
+        std::fs::remove_file(config.transcript_dir().join("round_2/round_2.verified")).unwrap();
+        assert!(verify_final_transcript(&config, 3).is_err());
+    }
+
+    /// Test that verification fails when a round's initial challenge
+    /// is not linked to the previous round's aggregation.
+    #[test]
+    fn test_verify_final_transcript_unlinked_rounds() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = fixture_config(out_dir.path());
+        create_fixture_ceremony(&config, 3);
+
+        std::fs::write(
+            config.transcript_dir().join("round_1/round_1.verified"),
+            b"modified",
+        )
+        .unwrap();
+        verify_final_transcript(&config, 1).unwrap();
+        let error = verify_final_transcript(&config, 3).unwrap_err();
+        assert!(format!("{:?}", error).contains("is not linked to round 1"));
+    }
+
+    /// Test that a known header in the latest contribution of a
+    /// fixture chunk can be asserted, and that out of range offsets
+    /// produce an error.
+    #[test]
+    fn test_assert_transcript_bytes() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = fixture_config(out_dir.path());
+        create_fixture_ceremony(&config, 1);
+
+        let chunk_dir = config.transcript_dir().join("round_1/chunk_1");
+        std::fs::write(chunk_dir.join("contribution_2.verified"), b"HDR\x01payload").unwrap();
+
+        assert_transcript_bytes(&config, 1, 1, 0, b"HDR\x01").unwrap();
+        assert_transcript_bytes(&config, 1, 1, 4, b"pay").unwrap();
+        assert!(assert_transcript_bytes(&config, 1, 1, 0, b"XYZ").is_err());
+
+        let error = assert_transcript_bytes(&config, 1, 1, 10, b"load").unwrap_err();
+        assert!(error.to_string().contains("out of range"));
+    }
+
+    /// Test that a round which is missing its aggregation output file
+    /// fails the check.
+    #[test]
+    fn test_check_aggregation_file_count() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = fixture_config(out_dir.path());
+        create_fixture_ceremony(&config, 2);
+
+        let expected = config.environment.aggregation_file_count();
+        check_aggregation_file_count(&config, 1, expected).unwrap();
+
+        std::fs::remove_file(config.transcript_dir().join("round_2/round_2.verified")).unwrap();
+        let error = check_aggregation_file_count(&config, 2, expected).unwrap_err();
+        assert!(error.to_string().contains("found 0"));
+    }
+
+    /// Test that a partial contribution by a dropped contributor which
+    /// wrongly persists in the round state is detected.
+    #[test]
+    fn test_check_no_partial_contributions() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let config = fixture_config(out_dir.path());
+        create_fixture_ceremony(&config, 1);
+
+        let dropped_address = "aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689";
+        let remaining_address = "aleo1qgqkzmv9jzq3y2ykl4vdm2mcaa7wh8kl3jzjy2cm8tg63k9xcsqqyvsw5m";
+        let dropped = [ContributorRef {
+            address: dropped_address.parse().unwrap(),
+        }];
+
+        let write_state = |dropped_verified: bool| {
+            std::fs::write(
+                config.transcript_dir().join("round_1/state.json"),
+                serde_json::json!({
+                    "contributorIds": [format!("{}.contributor", remaining_address)],
+                    "verifierIds": [],
+                    "chunks": [{
+                        "chunkId": 0,
+                        "contributions": {
+                            "0": { "contributorId": null, "verified": true },
+                            "1": {
+                                "contributorId": format!("{}.contributor", remaining_address),
+                                "verified": true,
+                            },
+                            "2": {
+                                "contributorId": format!("{}.contributor", dropped_address),
+                                "verified": dropped_verified,
+                            },
+                        },
+                    }],
+                })
+                .to_string(),
+            )
+            .unwrap();
+        };
+
+        write_state(true);
+        check_no_partial_contributions(&config, 1, &dropped).unwrap();
+
+        write_state(false);
+        let error = check_no_partial_contributions(&config, 1, &dropped).unwrap_err();
+        assert!(error.to_string().contains("chunk 0 contribution 2"));
+    }
+
+    /// Create a fixture for round 1 with two chunks, each with two
+    /// contributions linked by their hashes.
+    fn create_fixture_hash_chain(config: &CoordinatorConfig) {
+        for chunk in 0..2 {
+            let chunk_dir = config.round_dir(1).join(format!("chunk_{}", chunk));
+            std::fs::create_dir_all(&chunk_dir).unwrap();
+
+            let mut previous = b"initial challenge".to_vec();
+            std::fs::write(chunk_dir.join("contribution_0.verified"), &previous).unwrap();
+
+            for contribution in 1..=2 {
+                for extension in &["unverified", "verified"] {
+                    let mut contents = Blake2b::digest(&previous).to_vec();
+                    contents
+                        .extend_from_slice(format!("{} {}", extension, contribution).as_bytes());
+                    std::fs::write(
+                        chunk_dir.join(format!("contribution_{}.{}", contribution, extension)),
+                        &contents,
+                    )
+                    .unwrap();
+                    previous = contents;
+                }
+            }
+        }
+    }
+
+    /// Test that each kind of corruption is detected by
+    /// [verify_transcript_hashes()].
+    #[test]
+    fn test_corrupt_transcript_detected() {
+        for kind in &[
+            CorruptionKind::Truncate,
+            CorruptionKind::FlipByte,
+            CorruptionKind::ZeroOut,
+        ] {
+            let out_dir = tempfile::tempdir().unwrap();
+            let config = fixture_config(out_dir.path());
+            create_fixture_hash_chain(&config);
+            verify_transcript_hashes(&config, 1).unwrap();
+
+            corrupt_transcript(&config, 1, 1, *kind).unwrap();
+            let error = verify_transcript_hashes(&config, 1).unwrap_err();
+            assert!(
+                error.to_string().contains("chunk_1"),
+                "{:?}: {}",
+                kind,
+                error
+            );
+        }
+    }
+}
@@ -14,3 +14,78 @@ where
     }
     Ok(path.into())
 }
+
+/// Recursively copy the contents of the directory at `from` into the
+/// directory at `to`, creating it (and any missing parents) if it
+/// doesn't yet exist. Existing files in `to` are overwritten.
+pub fn copy_dir_all(from: &Path, to: &Path) -> eyre::Result<()> {
+    std::fs::create_dir_all(to).wrap_err_with(|| format!("Error while creating path {:?}.", to))?;
+
+    for entry in
+        std::fs::read_dir(from).wrap_err_with(|| format!("Unable to read directory {:?}", from))?
+    {
+        let entry = entry?;
+        let to_path = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir_all(&entry.path(), &to_path)?;
+        } else {
+            std::fs::copy(entry.path(), &to_path)
+                .wrap_err_with(|| format!("Unable to copy {:?} to {:?}", entry.path(), to_path))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write an executable shell script named `name` to `dir`, e.g. to
+/// stand in for one of the ceremony's binaries in tests. Returns the
+/// path of the script.
+#[cfg(all(test, unix))]
+pub(crate) fn fake_bin(dir: &Path, name: &str, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin_path = dir.join(name);
+    std::fs::write(&bin_path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    std::fs::set_permissions(&bin_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    bin_path
+}
+
+/// The address of the participants in tests.
+#[cfg(test)]
+pub(crate) const TEST_ADDRESS: &str =
+    "aleo1hsr8czcmxxanpv6cvwct75wep5ldhd2s702zm8la47dwcxjveypqsv7689";
+
+/// A [crate::contributor::ContributorConfig] for use in tests, for a
+/// contributor with the [TEST_ADDRESS] which runs the
+/// `contributor_bin_path` (e.g. a [fake_bin()]). Its keys file is
+/// written to `dir`, which also stores its artifacts.
+#[cfg(all(test, unix))]
+pub(crate) fn fake_contributor_config(
+    dir: &Path,
+    contributor_bin_path: PathBuf,
+) -> crate::contributor::ContributorConfig {
+    let key_file_path = dir.join("contributor.keys");
+    std::fs::write(
+        &key_file_path,
+        format!(
+            r#"{{"encryptedSeed": "seed", "address": "{}"}}"#,
+            TEST_ADDRESS
+        ),
+    )
+    .unwrap();
+
+    crate::contributor::ContributorConfig {
+        id: "contributor1".to_string(),
+        contributor_ref: crate::ContributorRef {
+            address: TEST_ADDRESS.parse().unwrap(),
+        },
+        contributor_bin_path,
+        key_file_path,
+        environment: crate::Environment::Development,
+        coordinator_api_url: "http://localhost:9000".to_string(),
+        out_dir: dir.to_owned(),
+        drop: None,
+        start: crate::test::ContributorStartConfig::RoundStart,
+        contribution_delay: None,
+    }
+}
@@ -4,8 +4,9 @@
 use crate::{
     process::{
         default_parse_exit_status, fallible_monitor, run_monitor_process, MonitorProcessJoin,
+        MonitorProcessOptions,
     },
-    CeremonyMessage,
+    AleoPublicKey, CeremonyMessage,
 };
 
 use eyre::Context;
@@ -31,12 +32,14 @@ impl std::fmt::Display for VerifierViewKey {
     }
 }
 
-/// Use `setup1-contributor` to generate the contributor key file used
-/// in [run_contributor()].
+/// Use the `view-key` binary to generate the verifier view key file
+/// used in [run_verifier()]. Returns the verifier's address if the
+/// binary also printed it after the view key, see
+/// [Verifier::address].
 pub fn generate_verifier_key(
     view_key_bin_path: impl AsRef<Path> + std::fmt::Debug,
     view_key_path: impl AsRef<Path> + std::fmt::Debug,
-) -> eyre::Result<()> {
+) -> eyre::Result<Option<AleoPublicKey>> {
     tracing::info!("Generating verifier view key.");
 
     let capture = subprocess::Exec::cmd(view_key_bin_path.as_ref())
@@ -46,10 +49,13 @@ pub fn generate_verifier_key(
     default_parse_exit_status(capture.exit_status)?;
 
     let view_key_out = capture.stdout_str();
-    let view_key = view_key_out
-        .split('\n')
+    let mut view_key_lines = view_key_out.split('\n');
+    let view_key = view_key_lines
         .next()
         .expect("Expected to be able to split view key output with \\n");
+    let address = view_key_lines
+        .flat_map(|line| line.split_whitespace())
+        .find_map(|word| word.parse::<AleoPublicKey>().ok());
 
     assert!(!view_key.is_empty());
     tracing::info!("Generated view key: {}", view_key);
@@ -58,7 +64,15 @@ pub fn generate_verifier_key(
 
     std::fs::write(view_key_path, key.as_ref()).wrap_err("error writing view key for verifier")?;
 
-    Ok(())
+    match &address {
+        Some(address) => tracing::info!("Verifier address: {}", address),
+        None => tracing::warn!(
+            "The view key generator did not print the verifier's address, \
+                it will not be possible to check the verifier's participation in rounds."
+        ),
+    }
+
+    Ok(address)
 }
 
 /// Data relating to a verifier.
@@ -69,6 +83,23 @@ pub struct Verifier {
     pub id: String,
     /// This verifier's view key.
     pub view_key_path: PathBuf,
+    /// This verifier's aleo address, if it is known. The verifier
+    /// derives its address from the view key itself, so this is only
+    /// available when it has been supplied separately, or printed by
+    /// the key generator (see [generate_verifier_key()]). Required by
+    /// [check_participants_in_round()](crate::coordinator::check_participants_in_round()).
+    pub address: Option<AleoPublicKey>,
+}
+
+impl Verifier {
+    /// The id used to reference this verifier by the coordinator,
+    /// and within the ceremony transcript. `None` if the verifier's
+    /// [Verifier::address] is not known.
+    pub fn id_on_coordinator(&self) -> Option<String> {
+        self.address
+            .as_ref()
+            .map(|address| format!("{}.verifier", address))
+    }
 }
 
 /// Run the `setup1-verifier`.
@@ -88,13 +119,18 @@ pub fn run_verifier(
 
     tracing::info!("Running verifier.");
 
-    let exec = subprocess::Exec::cmd(verifier_bin_path.as_ref().canonicalize()?)
-        .cwd(&out_dir)
-        .env("RUST_LOG", "debug,hyper=warn")
-        .env("RUST_BACKTRACE", "1")
-        .args(&["--api-url", coordinator_api_url])
-        .arg("--view-key")
-        .arg(view_key_path);
+    let verifier_bin_path = verifier_bin_path.as_ref().canonicalize()?;
+    let exec_out_dir = out_dir.clone();
+    let coordinator_api_url = coordinator_api_url.to_string();
+    let exec = move || {
+        Ok(subprocess::Exec::cmd(&verifier_bin_path)
+            .cwd(&exec_out_dir)
+            .env("RUST_LOG", "debug,hyper=warn")
+            .env("RUST_BACKTRACE", "1")
+            .args(&["--api-url", &coordinator_api_url])
+            .arg("--view-key")
+            .arg(&view_key_path))
+    };
 
     let log_file_path = out_dir.join("verifier.log");
 
@@ -102,10 +138,11 @@ pub fn run_verifier(
         id.to_string(),
         exec,
         default_parse_exit_status,
+        MonitorProcessOptions::default(),
         ceremony_tx,
         ceremony_rx,
-        fallible_monitor(move |stdout, _ceremony_tx, _monitor_tx| {
-            verifier_monitor(stdout, &log_file_path)
+        fallible_monitor(move |output, _ceremony_tx, _monitor_tx| {
+            verifier_monitor(output.stdout, &log_file_path)
         }),
     )
     .wrap_err_with(|| format!("Error running verifier {:?}", verifier_bin_path.as_ref()))?;
@@ -142,3 +179,37 @@ fn verifier_monitor(stdout: File, log_file_path: impl AsRef<Path>) -> eyre::Resu
 
     Ok(())
 }
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use super::generate_verifier_key;
+    use crate::{
+        util::{fake_bin, TEST_ADDRESS},
+        AleoPublicKey,
+    };
+
+    /// Test that the verifier's address is returned when the view key
+    /// generator prints it after the view key, and `None` otherwise.
+    #[test]
+    fn test_generate_verifier_key_address() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let view_key_path = out_dir.path().join("verifier1.key");
+
+        for (script, expected) in &[
+            (
+                format!("echo AViewKey1test; echo \"Address: {}\"", TEST_ADDRESS),
+                Some(TEST_ADDRESS.parse::<AleoPublicKey>().unwrap()),
+            ),
+            ("echo AViewKey1test".to_string(), None),
+        ] {
+            let bin_path = fake_bin(out_dir.path(), "view-key", script);
+
+            let address = generate_verifier_key(&bin_path, &view_key_path).unwrap();
+            assert_eq!(expected, &address);
+            assert_eq!(
+                "AViewKey1test",
+                std::fs::read_to_string(&view_key_path).unwrap()
+            );
+        }
+    }
+}
@@ -1,6 +1,10 @@
-use std::marker::PhantomData;
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
 
-use mpmc_bus::Receiver;
+use mpmc_bus::{Receiver, TryRecvError};
 
 use crate::join::MultiJoinable;
 
@@ -173,13 +177,72 @@ where
     }
 }
 
+/// How often [wait_for_message()] checks for new messages.
+const WAIT_FOR_MESSAGE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The number of most recently received messages included in the
+/// error returned by [wait_for_message()] when it times out.
+const WAIT_FOR_MESSAGE_HISTORY: usize = 10;
+
+/// Receive messages from `rx` until one matches the `predicate`, and
+/// return it. Returns an error (including the last few messages which
+/// were received) if no matching message is received within
+/// `timeout`.
+pub fn wait_for_message<T, P>(
+    rx: &mut Receiver<T>,
+    predicate: P,
+    timeout: Duration,
+) -> eyre::Result<T>
+where
+    T: std::fmt::Debug,
+    P: Fn(&T) -> bool,
+{
+    let start = Instant::now();
+    let mut last_received: VecDeque<T> = VecDeque::with_capacity(WAIT_FOR_MESSAGE_HISTORY);
+
+    loop {
+        match rx.try_recv() {
+            Ok(message) => {
+                if predicate(&message) {
+                    return Ok(message);
+                }
+
+                if last_received.len() == WAIT_FOR_MESSAGE_HISTORY {
+                    last_received.pop_front();
+                }
+                last_received.push_back(message);
+            }
+            Err(TryRecvError::Empty) => {
+                if start.elapsed() > timeout {
+                    return Err(eyre::eyre!(
+                        "Timed out after {:?} waiting for a matching message. \
+                            The last messages received were: {:?}",
+                        timeout,
+                        last_received
+                    ));
+                }
+
+                std::thread::sleep(WAIT_FOR_MESSAGE_POLL_INTERVAL);
+            }
+            Err(TryRecvError::Disconnected) => {
+                return Err(eyre::eyre!(
+                    "`rx` disconnected while waiting for a matching message"
+                ));
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::sync::{Arc, Mutex};
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
 
     use crate::waiter::IsShutdownMessage;
 
-    use super::MessageWaiter;
+    use super::{wait_for_message, MessageWaiter};
     use mpmc_bus::Bus;
 
     impl IsShutdownMessage for u8 {
@@ -264,4 +327,38 @@ mod test {
         assert!(*has_joined.lock().unwrap());
         assert!(!*on_messages_received.lock().unwrap());
     }
+
+    #[test]
+    fn test_wait_for_message() {
+        let bus = Bus::<u8>::new(100);
+        let mut rx = bus.subscribe();
+
+        bus.broadcast(1).unwrap();
+        bus.broadcast(2).unwrap();
+        bus.broadcast(3).unwrap();
+
+        let message =
+            wait_for_message(&mut rx, |message| *message > 1, Duration::from_secs(5)).unwrap();
+        assert_eq!(2, message);
+
+        // The messages after the matching message are still available.
+        let message =
+            wait_for_message(&mut rx, |message| *message > 1, Duration::from_secs(5)).unwrap();
+        assert_eq!(3, message);
+    }
+
+    /// Test that an error including the last received messages is
+    /// returned if no matching message is received in time.
+    #[test]
+    fn test_wait_for_message_timeout() {
+        let bus = Bus::<u8>::new(100);
+        let mut rx = bus.subscribe();
+
+        bus.broadcast(4).unwrap();
+        bus.broadcast(5).unwrap();
+
+        let error = wait_for_message(&mut rx, |message| *message == 6, Duration::from_millis(100))
+            .unwrap_err();
+        assert!(error.to_string().contains("[4, 5]"));
+    }
 }
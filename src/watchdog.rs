@@ -0,0 +1,395 @@
+//! A watchdog for resilience tests, which kills and relaunches
+//! contributors that stop making progress during a round, in order
+//! to deterministically exercise the coordinator's drop/replace path.
+
+use std::{
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use mpmc_bus::{Receiver, Sender, TryRecvError};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    contributor::{run_contributor, ContributorConfig, ContributorJoin},
+    join::MultiJoinable,
+    CeremonyMessage, ContributorRef, ShutdownReason,
+};
+
+/// How often the watchdog checks for messages and stuck contributors.
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait for a stuck contributor process to exit after
+/// it has been told to terminate, before relaunching it.
+const WATCHDOG_EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Configuration for [run_contributor_watchdog()].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// If the contributor has not made a contribution within this
+    /// window (since the round started, or since its previous
+    /// contribution), it is considered to be stuck and is restarted.
+    /// Specified in the [humantime] format, e.g. `window: "30s"`.
+    #[serde(with = "humantime_serde")]
+    pub window: Duration,
+    /// The maximum number of times the contributor may be restarted
+    /// during the round. Exceeding this causes the ceremony to be
+    /// shut down with an error.
+    pub max_restarts: usize,
+}
+
+/// The outcome of a round which was monitored by
+/// [run_contributor_watchdog()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchdogReport {
+    /// The number of times the contributor was restarted.
+    pub restarts: usize,
+}
+
+/// Run the contributor specified by `contributor_config` (see
+/// [run_contributor()]) under a watchdog for the duration of a
+/// round. If the contributor makes no progress within
+/// [WatchdogConfig::window] while the round is running (and the
+/// coordinator is not paused for maintenance), the
+/// contributor process is terminated and relaunched, and a
+/// [CeremonyMessage::ContributorRestarted] is broadcast.
+///
+/// The thread closes when the round finishes or when a
+/// [CeremonyMessage::Shutdown] is received, after joining every
+/// contributor process it started. The join returns an error if the
+/// contributor was restarted but the ceremony did not recover (no
+/// further contribution was made and the round did not finish), or
+/// if [WatchdogConfig::max_restarts] was exceeded.
+pub fn run_contributor_watchdog(
+    config: WatchdogConfig,
+    contributor_config: ContributorConfig,
+    ceremony_tx: Sender<CeremonyMessage>,
+    ceremony_rx: Receiver<CeremonyMessage>,
+) -> eyre::Result<JoinHandle<eyre::Result<WatchdogReport>>> {
+    let span = tracing::error_span!("watchdog", id = %contributor_config.id);
+
+    let initial_join = run_contributor(
+        contributor_config.clone(),
+        ceremony_tx.clone(),
+        ceremony_rx.clone(),
+    )?;
+
+    Ok(std::thread::spawn(move || {
+        let _guard = span.enter();
+        let contributor_ref = contributor_config.contributor_ref.clone();
+
+        let mut watchdog = Watchdog {
+            config,
+            contributor_config,
+            contributor_ref: contributor_ref.clone(),
+            ceremony_tx,
+            ceremony_rx,
+            contributor_joins: vec![initial_join],
+            round_running: false,
+            round_finished: false,
+            last_progress: Instant::now(),
+            restarts: 0,
+            recovered: true,
+            paused: false,
+        };
+
+        // Any error is returned only after the contributors which
+        // have been started are stopped.
+        if let Err(error) = watchdog.watch() {
+            stop_contributors(watchdog.contributor_joins);
+            return Err(error);
+        }
+
+        for contributor_join in watchdog.contributor_joins {
+            let join: Box<dyn MultiJoinable> = Box::new(contributor_join);
+            join.join()
+                .map_err(|_| eyre::eyre!("A contributor thread panicked"))?;
+        }
+
+        if !watchdog.recovered && !watchdog.round_finished {
+            return Err(eyre::eyre!(
+                "Contributor {} was restarted, but the ceremony did not recover.",
+                contributor_ref
+            ));
+        }
+
+        tracing::debug!("Thread closing gracefully.");
+        Ok(WatchdogReport {
+            restarts: watchdog.restarts,
+        })
+    }))
+}
+
+/// The state of the watchdog started by [run_contributor_watchdog()].
+struct Watchdog {
+    config: WatchdogConfig,
+    contributor_config: ContributorConfig,
+    contributor_ref: ContributorRef,
+    ceremony_tx: Sender<CeremonyMessage>,
+    ceremony_rx: Receiver<CeremonyMessage>,
+    /// Every contributor which has been started, the last of which is
+    /// the one currently running.
+    contributor_joins: Vec<ContributorJoin>,
+    round_running: bool,
+    round_finished: bool,
+    last_progress: Instant,
+    restarts: usize,
+    recovered: bool,
+    paused: bool,
+}
+
+impl Watchdog {
+    /// Watch the running contributor, restarting it when it makes no
+    /// progress, until the round finishes or the ceremony shuts down.
+    fn watch(&mut self) -> eyre::Result<()> {
+        loop {
+            std::thread::sleep(WATCHDOG_POLL_INTERVAL);
+
+            loop {
+                match self.ceremony_rx.try_recv() {
+                    Ok(CeremonyMessage::RoundStarted(_)) => {
+                        self.round_running = true;
+                        self.last_progress = Instant::now();
+                    }
+                    Ok(CeremonyMessage::SuccessfulContribution { contributor, .. }) => {
+                        if contributor == self.contributor_ref {
+                            self.last_progress = Instant::now();
+                            if !self.recovered {
+                                tracing::info!(
+                                    "Contributor {} recovered after being restarted.",
+                                    self.contributor_ref
+                                );
+                                self.recovered = true;
+                            }
+                        }
+                    }
+                    Ok(CeremonyMessage::CoordinatorPaused) => self.paused = true,
+                    Ok(CeremonyMessage::CoordinatorResumed) => {
+                        // The contributor is not expected to make
+                        // progress while the coordinator is paused.
+                        self.paused = false;
+                        self.last_progress = Instant::now();
+                    }
+                    Ok(CeremonyMessage::RoundFinished(_)) => {
+                        self.round_finished = true;
+                        return Ok(());
+                    }
+                    Ok(CeremonyMessage::Shutdown(_)) => return Ok(()),
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        return Err(eyre::eyre!("`ceremony_rx` disconnected"));
+                    }
+                }
+            }
+
+            if !self.round_running
+                || self.paused
+                || self.last_progress.elapsed() <= self.config.window
+            {
+                continue;
+            }
+
+            if self.restarts >= self.config.max_restarts {
+                self.ceremony_tx
+                    .broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))?;
+                return Err(eyre::eyre!(
+                    "Contributor {} made no progress within {:?} after being restarted {} times.",
+                    self.contributor_ref,
+                    self.config.window,
+                    self.restarts
+                ));
+            }
+
+            tracing::warn!(
+                "Contributor {} made no progress within {:?}, restarting it.",
+                self.contributor_ref,
+                self.config.window
+            );
+
+            let stuck_join = self
+                .contributor_joins
+                .last()
+                .expect("expected at least one contributor to be running");
+            stuck_join.terminate()?;
+
+            let exit_start = Instant::now();
+            while stuck_join.exit_status().is_none() {
+                if exit_start.elapsed() > WATCHDOG_EXIT_TIMEOUT {
+                    self.ceremony_tx
+                        .broadcast(CeremonyMessage::Shutdown(ShutdownReason::Error))?;
+                    return Err(eyre::eyre!(
+                        "Timed out after {:?} waiting for stuck contributor {} to exit.",
+                        WATCHDOG_EXIT_TIMEOUT,
+                        self.contributor_ref
+                    ));
+                }
+                std::thread::sleep(WATCHDOG_POLL_INTERVAL);
+            }
+
+            self.contributor_joins.push(run_contributor(
+                self.contributor_config.clone(),
+                self.ceremony_tx.clone(),
+                self.ceremony_rx.clone(),
+            )?);
+            self.ceremony_tx
+                .broadcast(CeremonyMessage::ContributorRestarted(
+                    self.contributor_ref.clone(),
+                ))?;
+
+            self.restarts += 1;
+            self.recovered = false;
+            self.last_progress = Instant::now();
+        }
+    }
+}
+
+/// Terminate the contributors in `contributor_joins` which are still
+/// running, and join them, when the watchdog stops because of an
+/// error. Errors are logged, so that they don't hide the error which
+/// stopped the watchdog.
+fn stop_contributors(contributor_joins: Vec<ContributorJoin>) {
+    for contributor_join in &contributor_joins {
+        if contributor_join.exit_status().is_none() {
+            if let Err(error) = contributor_join.terminate() {
+                tracing::warn!("Unable to terminate contributor: {:?}", error);
+            }
+        }
+    }
+
+    for contributor_join in contributor_joins {
+        let join: Box<dyn MultiJoinable> = Box::new(contributor_join);
+        if join.join().is_err() {
+            tracing::error!("A contributor thread panicked");
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::{path::Path, time::Duration};
+
+    use mpmc_bus::Bus;
+    use subprocess::{Exec, Redirection};
+
+    use super::{run_contributor_watchdog, WatchdogConfig, WatchdogReport};
+    use crate::{
+        util::{fake_bin, fake_contributor_config},
+        waiter::{MessageWaiter, WaiterJoinCondition},
+        CeremonyMessage, ShutdownReason,
+    };
+
+    /// Create a fake `setup1-contributor` which records each launch
+    /// (with its process id) in a `launches` file in its working
+    /// directory, and then hangs without ever making progress.
+    fn stuck_contributor_bin(dir: &Path) -> std::path::PathBuf {
+        fake_bin(
+            dir,
+            "setup1-contributor",
+            "echo $$ >> launches\nwhile true; do sleep 0.05; done",
+        )
+    }
+
+    /// Test that a contributor which makes no progress is restarted
+    /// by the watchdog, and that the round then completes.
+    #[test]
+    fn test_stuck_contributor_restarted() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let contributor_config =
+            fake_contributor_config(out_dir.path(), stuck_contributor_bin(out_dir.path()));
+        let contributor_ref = contributor_config.contributor_ref.clone();
+        let watchdog_config = WatchdogConfig {
+            window: Duration::from_millis(500),
+            max_restarts: 1,
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let restarted = MessageWaiter::spawn_expected(
+            vec![CeremonyMessage::ContributorRestarted(
+                contributor_ref.clone(),
+            )],
+            || Ok(()),
+            bus.subscribe(),
+        );
+        let join = run_contributor_watchdog(
+            watchdog_config,
+            contributor_config,
+            bus.broadcaster(),
+            bus.subscribe(),
+        )
+        .unwrap();
+
+        bus.broadcast(CeremonyMessage::RoundStarted(1)).unwrap();
+        assert!(matches!(
+            restarted.join().unwrap(),
+            WaiterJoinCondition::MessagesReceived
+        ));
+
+        // The relaunched contributor recovers, and the round completes.
+        bus.broadcast(CeremonyMessage::SuccessfulContribution {
+            contributor: contributor_ref,
+            chunk: 0,
+            seq: 0,
+        })
+        .unwrap();
+        bus.broadcast(CeremonyMessage::RoundFinished(1)).unwrap();
+
+        let report = join.join().unwrap().unwrap();
+        assert_eq!(WatchdogReport { restarts: 1 }, report);
+
+        let launches = std::fs::read_to_string(out_dir.path().join("launches")).unwrap();
+        assert_eq!(2, launches.lines().count());
+
+        bus.broadcast(CeremonyMessage::Shutdown(ShutdownReason::TestFinished))
+            .unwrap();
+    }
+
+    /// Test that when [WatchdogConfig::max_restarts] is exceeded, the
+    /// watchdog stops the contributor it started before returning the
+    /// error.
+    #[test]
+    fn test_max_restarts_exceeded() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let contributor_config =
+            fake_contributor_config(out_dir.path(), stuck_contributor_bin(out_dir.path()));
+        let watchdog_config = WatchdogConfig {
+            window: Duration::from_millis(500),
+            max_restarts: 0,
+        };
+
+        let bus = Bus::<CeremonyMessage>::new(100);
+        let join = run_contributor_watchdog(
+            watchdog_config,
+            contributor_config,
+            bus.broadcaster(),
+            bus.subscribe(),
+        )
+        .unwrap();
+
+        bus.broadcast(CeremonyMessage::RoundStarted(1)).unwrap();
+        let error = join.join().unwrap().unwrap_err();
+        assert!(error.to_string().contains("made no progress"));
+
+        // The contributor process has exited by the time the error is
+        // returned.
+        let launches = std::fs::read_to_string(out_dir.path().join("launches")).unwrap();
+        let pids: Vec<&str> = launches.lines().collect();
+        assert_eq!(1, pids.len());
+        let exit_status = Exec::cmd("kill")
+            .args(&["-0", pids[0]])
+            .stderr(Redirection::Pipe)
+            .join()
+            .unwrap();
+        assert!(!exit_status.success());
+    }
+
+    /// Test that [WatchdogConfig::window] is deserialized from a
+    /// [humantime] duration.
+    #[test]
+    fn test_deserialize_watchdog_config() {
+        let config: WatchdogConfig =
+            ron::from_str("(window: \"1m 30s\", max_restarts: 2)").unwrap();
+        assert_eq!(Duration::from_secs(90), config.window);
+        assert_eq!(2, config.max_restarts);
+    }
+}